@@ -0,0 +1,262 @@
+//! Image decoding for `include_glyph!`: GIF
+//! (first frame, as the icons of the C SDK
+//! apps) and XBM, without dependencies.
+
+/// An image as color indexes
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// Colors as 0xRRGGBB, darkest first
+    pub colors: Vec<u32>,
+    /// Color index of each pixel, row after row
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Bits per pixel needed for the colors
+    pub fn bpp(&self) -> Result<usize, String> {
+        match self.colors.len() {
+            0..=2 => Ok(1),
+            3..=4 => Ok(2),
+            5..=16 => Ok(4),
+            n => Err(format!("{} colors, glyphs have at most 16", n)),
+        }
+    }
+
+    /// Pixels packed row after row without
+    /// padding, least significant bits first
+    pub fn bitmap(&self) -> Result<Vec<u8>, String> {
+        let bpp = self.bpp()?;
+        let mut out = vec![0u8; (self.pixels.len() * bpp).div_ceil(8)];
+        for (i, &p) in self.pixels.iter().enumerate() {
+            let bit = i * bpp;
+            out[bit / 8] |= p << (bit % 8);
+        }
+        Ok(out)
+    }
+}
+
+/// Decode `data` by its signature
+pub fn decode(data: &[u8]) -> Result<Image, String> {
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        gif(data)
+    } else if data.starts_with(b"\x89PNG") {
+        Err("PNG is not supported, convert the image to GIF".into())
+    } else {
+        xbm(std::str::from_utf8(data).map_err(|_| "not a GIF or XBM image")?)
+    }
+}
+
+/// Keep the colors the pixels use, darkest
+/// first, and index the pixels in them
+fn index(width: usize, height: usize, rgb: &[u32]) -> Image {
+    let mut colors: Vec<u32> = rgb.to_vec();
+    colors.sort_by_key(|&c| (((c >> 16) & 0xff) * 299 + ((c >> 8) & 0xff) * 587 + (c & 0xff) * 114, c));
+    colors.dedup();
+    let pixels = rgb.iter().map(|c| colors.iter().position(|x| x == c).unwrap() as u8).collect();
+    Image { width, height, colors, pixels }
+}
+
+fn gif(data: &[u8]) -> Result<Image, String> {
+    let mut r = Reader { data, pos: 6 };
+    r.u16()?;
+    r.u16()?;
+    let flags = r.u8()?;
+    r.skip(2)?;
+    let mut palette = Vec::new();
+    if flags & 0x80 != 0 {
+        palette = r.palette(flags)?;
+    }
+    let mut transparent = None;
+    loop {
+        match r.u8()? {
+            // Extension
+            0x21 => {
+                let label = r.u8()?;
+                let block = r.sub_blocks()?;
+                // Graphic control extension
+                if label == 0xf9 && block.len() >= 4 && block[0] & 1 != 0 {
+                    transparent = Some(block[3]);
+                }
+            }
+            // Image descriptor
+            0x2c => {
+                r.skip(4)?;
+                let width = r.u16()? as usize;
+                let height = r.u16()? as usize;
+                let flags = r.u8()?;
+                if flags & 0x80 != 0 {
+                    palette = r.palette(flags)?;
+                }
+                let min_code_size = r.u8()?;
+                let mut indexes = lzw(min_code_size, &r.sub_blocks()?)?;
+                if indexes.len() < width * height {
+                    return Err("truncated GIF image".into())
+                }
+                indexes.truncate(width * height);
+                if flags & 0x40 != 0 {
+                    indexes = deinterlace(&indexes, width, height);
+                }
+                // Transparent pixels show the black background
+                let rgb = indexes
+                    .iter()
+                    .map(|&i| match palette.get(i as usize) {
+                        Some(_) if Some(i) == transparent => Ok(0),
+                        Some(&c) => Ok(c),
+                        None => Err("GIF color index out of the palette".to_string()),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                return Ok(index(width, height, &rgb))
+            }
+            _ => return Err("no image in the GIF".into()),
+        }
+    }
+}
+
+/// Rows of an interlaced image in order
+fn deinterlace(indexes: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut out = vec![0; indexes.len()];
+    let mut rows = indexes.chunks(width);
+    for (start, step) in [(0, 8), (4, 8), (2, 4), (1, 2)] {
+        for y in (start..height).step_by(step) {
+            if let Some(row) = rows.next() {
+                out[y * width..(y + 1) * width].copy_from_slice(row);
+            }
+        }
+    }
+    out
+}
+
+/// Decompress GIF image data
+fn lzw(min_code_size: u8, data: &[u8]) -> Result<Vec<u8>, String> {
+    if !(1..=11).contains(&min_code_size) {
+        return Err("invalid GIF code size".into())
+    }
+    let clear = 1usize << min_code_size;
+    let end = clear + 1;
+    let reset = || (0..clear).map(|i| vec![i as u8]).chain([vec![], vec![]]).collect::<Vec<_>>();
+    let mut dict = reset();
+    let mut code_size = min_code_size as usize + 1;
+    let mut prev: Option<Vec<u8>> = None;
+    let mut out = Vec::new();
+    let mut bit = 0;
+    while bit + code_size <= data.len() * 8 {
+        let mut code = 0;
+        for k in 0..code_size {
+            let b = bit + k;
+            code |= ((data[b / 8] >> (b % 8)) as usize & 1) << k;
+        }
+        bit += code_size;
+        if code == clear {
+            dict = reset();
+            code_size = min_code_size as usize + 1;
+            prev = None;
+            continue
+        }
+        if code == end {
+            break
+        }
+        let entry = match (dict.get(code), &prev) {
+            (Some(e), _) => e.clone(),
+            (None, Some(p)) if code == dict.len() => {
+                let mut e = p.clone();
+                e.push(p[0]);
+                e
+            }
+            _ => return Err("corrupt GIF image data".into()),
+        };
+        out.extend_from_slice(&entry);
+        if let Some(mut p) = prev.take() {
+            if dict.len() < 4096 {
+                p.push(entry[0]);
+                dict.push(p);
+                if dict.len() == 1 << code_size && code_size < 12 {
+                    code_size += 1;
+                }
+            }
+        }
+        prev = Some(entry);
+    }
+    Ok(out)
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn u8(&mut self) -> Result<u8, String> {
+        let b = *self.data.get(self.pos).ok_or("truncated GIF")?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes([self.u8()?, self.u8()?]))
+    }
+
+    fn skip(&mut self, n: usize) -> Result<(), String> {
+        for _ in 0..n {
+            self.u8()?;
+        }
+        Ok(())
+    }
+
+    /// Color table whose size is given by `flags`
+    fn palette(&mut self, flags: u8) -> Result<Vec<u32>, String> {
+        (0..2 << (flags & 7))
+            .map(|_| Ok(u32::from_be_bytes([0, self.u8()?, self.u8()?, self.u8()?])))
+            .collect()
+    }
+
+    /// Data sub-blocks, concatenated
+    fn sub_blocks(&mut self) -> Result<Vec<u8>, String> {
+        let mut out = Vec::new();
+        loop {
+            let len = self.u8()? as usize;
+            if len == 0 {
+                return Ok(out)
+            }
+            for _ in 0..len {
+                out.push(self.u8()?);
+            }
+        }
+    }
+}
+
+/// `#define name_width`, `#define name_height`,
+/// then the rows, each starting on a new byte,
+/// least significant bit first. Set bits are
+/// lit pixels.
+fn xbm(text: &str) -> Result<Image, String> {
+    let define = |suffix: &str| {
+        text.lines()
+            .filter_map(|l| l.trim().strip_prefix("#define"))
+            .map(|l| l.split_whitespace().collect::<Vec<_>>())
+            .find(|w| w.len() == 2 && w[0].ends_with(suffix))
+            .and_then(|w| w[1].parse::<usize>().ok())
+            .ok_or(format!("not a GIF or XBM image: no {} defined", suffix))
+    };
+    let width = define("_width")?;
+    let height = define("_height")?;
+    let body = text.find('{').map(|i| &text[i + 1..]).ok_or("XBM without data")?;
+    let body = &body[..body.find('}').ok_or("XBM without data")?];
+    let bytes = body
+        .split(',')
+        .map(str::trim)
+        .filter(|b| !b.is_empty())
+        .map(|b| {
+            let hex = b.trim_start_matches("0x").trim_start_matches("0X");
+            u8::from_str_radix(hex, 16).map_err(|_| format!("bad XBM byte `{}`", b))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let row_len = width.div_ceil(8);
+    if bytes.len() < row_len * height {
+        return Err("truncated XBM image".into())
+    }
+    let pixels = (0..width * height)
+        .map(|i| (bytes[i / width * row_len + i % width / 8] >> (i % width % 8)) & 1)
+        .collect();
+    Ok(Image { width, height, colors: vec![0x000000, 0xffffff], pixels })
+}
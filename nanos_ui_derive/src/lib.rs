@@ -0,0 +1,247 @@
+//! `#[derive(UiScreen)]` and `include_glyph!`,
+//! re-exported by `nanos_ui` with the `derive`
+//! feature.
+//!
+//! Written against `proc_macro` alone, so the
+//! parsing only covers what the derive needs:
+//! a struct with named fields, and lifetime or
+//! unbounded type parameters.
+
+extern crate proc_macro;
+
+mod glyph;
+
+use proc_macro::{Delimiter, Group, Literal, TokenStream, TokenTree};
+
+/// Implements `Displayable` for a struct whose
+/// fields marked with `#[ui(..)]` are drawn:
+///
+/// ```ignore
+/// #[derive(UiScreen)]
+/// struct Confirm<'a> {
+///     #[ui(icon, x = 16, y = 12)]
+///     icon: Icon,
+///     #[ui(text, y = 12, bold)]
+///     title: &'a str,
+///     #[ui(text, y = 26)]
+///     value: &'a str,
+/// }
+/// ```
+///
+/// `text` fields are `&str` drawn as labels
+/// (default `x = 0`, `width = 128`,
+/// `height = 11`, optional `bold`). `icon`
+/// fields are `Icon`s drawn at `x`, `y`.
+#[proc_macro_derive(UiScreen, attributes(ui))]
+pub fn derive_ui_screen(input: TokenStream) -> TokenStream {
+    match expand(input) {
+        Ok(code) => code.parse().unwrap(),
+        Err(msg) => format!("compile_error!({:?});", msg).parse().unwrap(),
+    }
+}
+
+/// A field to draw and its layout
+struct Item {
+    field: String,
+    icon: bool,
+    x: String,
+    y: String,
+    width: String,
+    height: String,
+    bold: bool,
+}
+
+fn expand(input: TokenStream) -> Result<String, String> {
+    let mut tokens = input.into_iter().peekable();
+
+    // Skip attributes and visibility up to `struct`
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(i)) if i.to_string() == "struct" => break,
+            Some(TokenTree::Ident(i)) if i.to_string() == "enum" || i.to_string() == "union" => {
+                return Err("UiScreen can only be derived for structs".into())
+            }
+            Some(_) => (),
+            None => return Err("expected a struct".into()),
+        }
+    }
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(i)) => i.to_string(),
+        _ => return Err("expected the struct name".into()),
+    };
+
+    // Generic parameters, copied verbatim
+    let mut generics = String::new();
+    if matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == '<') {
+        let mut depth = 0;
+        for t in tokens.by_ref() {
+            if let TokenTree::Punct(p) = &t {
+                match p.as_char() {
+                    '<' => depth += 1,
+                    '>' => depth -= 1,
+                    _ => (),
+                }
+            }
+            generics.push_str(&t.to_string());
+            // Keep lifetimes glued to their quote
+            if !matches!(&t, TokenTree::Punct(p) if p.as_char() == '\'') {
+                generics.push(' ');
+            }
+            if depth == 0 {
+                break
+            }
+        }
+    }
+
+    let body = tokens
+        .find_map(|t| match t {
+            TokenTree::Group(g) if g.delimiter() == Delimiter::Brace => Some(g),
+            _ => None,
+        })
+        .ok_or("UiScreen needs a struct with named fields")?;
+
+    let mut paint = String::new();
+    for item in fields(body)? {
+        let text = if item.icon {
+            format!("self.{}.pos({}, {}).paint();", item.field, item.x, item.y)
+        } else {
+            format!(
+                "::nanos_ui::bagls::LabelLine::new().dims({}, {}).pos({}, {}).text(self.{}){}.paint();",
+                item.width, item.height, item.x, item.y, item.field,
+                if item.bold { ".bold()" } else { "" }
+            )
+        };
+        paint.push_str(&text);
+    }
+
+    Ok(format!(
+        "impl {g} ::nanos_ui::bagls::Displayable for {n} {g} {{ fn paint(&self) {{ {p} }} }}",
+        g = generics, n = name, p = paint
+    ))
+}
+
+/// Fields of `body` that have a `#[ui(..)]` attribute
+fn fields(body: Group) -> Result<Vec<Item>, String> {
+    let mut items = Vec::new();
+    let mut attr: Option<Group> = None;
+    let mut tokens = body.stream().into_iter().peekable();
+    while let Some(t) = tokens.next() {
+        match t {
+            TokenTree::Punct(p) if p.as_char() == '#' => {
+                if let Some(TokenTree::Group(g)) = tokens.next() {
+                    let mut inner = g.stream().into_iter();
+                    if let (Some(TokenTree::Ident(i)), Some(TokenTree::Group(args))) = (inner.next(), inner.next()) {
+                        if i.to_string() == "ui" {
+                            attr = Some(args);
+                        }
+                    }
+                }
+            }
+            TokenTree::Ident(i) if matches!(tokens.peek(), Some(TokenTree::Punct(p)) if p.as_char() == ':') => {
+                if let Some(args) = attr.take() {
+                    items.push(item(i.to_string(), args)?);
+                }
+                // Skip the type, up to the next field
+                let mut depth = 0;
+                for t in tokens.by_ref() {
+                    if let TokenTree::Punct(p) = &t {
+                        match p.as_char() {
+                            '<' => depth += 1,
+                            '>' => depth -= 1,
+                            ',' if depth == 0 => break,
+                            _ => (),
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(items)
+}
+
+/// Parse `text, x = 0, y = 12, bold`
+fn item(field: String, args: Group) -> Result<Item, String> {
+    let mut item = Item {
+        field,
+        icon: false,
+        x: "0".into(),
+        y: "20".into(),
+        width: "128".into(),
+        height: "11".into(),
+        bold: false,
+    };
+    let args = args.stream().to_string();
+    for (i, arg) in args.split(',').map(str::trim).filter(|a| !a.is_empty()).enumerate() {
+        let (key, value) = match arg.find('=') {
+            Some(eq) => (arg[..eq].trim(), Some(arg[eq + 1..].trim().replace(' ', ""))),
+            None => (arg, None),
+        };
+        match (key, value) {
+            ("text", None) if i == 0 => (),
+            ("icon", None) if i == 0 => item.icon = true,
+            ("bold", None) => item.bold = true,
+            ("x", Some(v)) => item.x = v,
+            ("y", Some(v)) => item.y = v,
+            ("width", Some(v)) => item.width = v,
+            ("height", Some(v)) => item.height = v,
+            _ => return Err(format!("unexpected `{}` in #[ui(..)] of `{}`", arg, item.field)),
+        }
+    }
+    Ok(item)
+}
+
+/// Converts an image to a `Glyph` at compile
+/// time, instead of pasting the output of the
+/// SDK's Python tooling:
+///
+/// ```ignore
+/// static LOGO: Glyph = include_glyph!("icons/logo.gif");
+/// ```
+///
+/// The path is relative to the crate root.
+/// GIF (first frame) and XBM images are
+/// supported. Colors are sorted darkest first,
+/// and 1, 2 or 4 bpp used depending on their
+/// count.
+#[proc_macro]
+pub fn include_glyph(input: TokenStream) -> TokenStream {
+    match expand_glyph(input) {
+        Ok(code) => code.parse().unwrap(),
+        Err(msg) => format!("compile_error!({:?})", msg).parse().unwrap(),
+    }
+}
+
+fn expand_glyph(input: TokenStream) -> Result<String, String> {
+    let mut tokens = input.into_iter();
+    let path = match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Literal(l)), None) => string_literal(l)?,
+        _ => return Err("include_glyph! expects the path of an image".into()),
+    };
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let path = std::path::Path::new(&root).join(path);
+    let data = std::fs::read(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let image = glyph::decode(&data).map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let bpp = image.bpp()?;
+    let mut colors = image.colors.clone();
+    colors.resize(1 << bpp, 0);
+    let colors: Vec<String> = colors.iter().map(|c| format!("{:#08x}", c)).collect();
+    let bitmap: Vec<String> = image.bitmap()?.iter().map(|b| format!("{:#04x}", b)).collect();
+    // `include_bytes!` rebuilds the crate when the image changes
+    Ok(format!(
+        "{{ const _: &[u8] = include_bytes!({p:?}); ::nanos_ui::glyph::Glyph {{ \
+         width: {w}, height: {h}, bpp: {b}, colors: &[{c}], bitmap: &[{m}] }} }}",
+        p = path.display().to_string(), w = image.width, h = image.height, b = bpp,
+        c = colors.join(", "), m = bitmap.join(", ")
+    ))
+}
+
+/// Value of a plain `"..."` literal
+fn string_literal(l: Literal) -> Result<String, String> {
+    let s = l.to_string();
+    match s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        Some(s) if !s.contains('\\') => Ok(s.to_string()),
+        _ => Err("include_glyph! expects a plain string literal".into()),
+    }
+}
@@ -0,0 +1,213 @@
+//! About screens showing the app name and
+//! version, taken from the app's own manifest
+//! by `app_info!` so they always match the
+//! binary, and optionally the device firmware:
+//!
+//! ```ignore
+//! app_info!("Bitcoin").device(os_version).show();
+//! ```
+//!
+//! The same information makes the app's home
+//! screen, ending with a "Quit app" entry:
+//!
+//! ```ignore
+//! match app_info!("Bitcoin").home().icon(APP_ICON).entries(&["Settings"]).show() {
+//!     0 => settings_menu(),
+//!     _ => (),
+//! }
+//! ```
+
+use crate::bagls::*;
+use crate::input::Action;
+use crate::ui::{Actions, HScroller, Page, WidgetState};
+use crate::widget::{run, Event, Widget};
+
+#[derive(Copy, Clone)]
+pub struct AppInfo<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) version: &'a str,
+    /// Firmware version, as reported by the SDK
+    pub(crate) device: Option<&'a str>,
+    /// Screen currently shown
+    pub(crate) index: usize,
+}
+
+impl<'a> AppInfo<'a> {
+    /// Prefer `app_info!`, which fills
+    /// in the crate name and version
+    pub const fn new(name: &'a str, version: &'a str) -> Self {
+        AppInfo { name, version, device: None, index: 0 }
+    }
+
+    /// Also show the device firmware version
+    pub const fn device(self, version: &'a str) -> Self {
+        AppInfo { device: Some(version), ..self }
+    }
+
+    /// Browse the screens until both
+    /// buttons are pressed
+    pub fn show(&self) {
+        run(&mut { *self })
+    }
+
+    /// Home screen of the app
+    pub const fn home(self) -> HomeScreen<'a> {
+        HomeScreen::new(self.name, self.version)
+    }
+
+    fn with_scroller<R>(&self, f: impl FnOnce(&mut HScroller) -> R) -> R {
+        let name = [self.name];
+        let version = ["Version", self.version];
+        let device = ["Device", self.device.unwrap_or("")];
+        let pages = [Page::new(&name), Page::new(&version), Page::new(&device)];
+        let count = if self.device.is_some() { 3 } else { 2 };
+        let mut scroller = HScroller { index: self.index, ..HScroller::new(&[]).pages(&pages[..count]) };
+        f(&mut scroller)
+    }
+}
+
+impl Widget for AppInfo<'_> {
+    type Output = ();
+
+    fn draw(&self) {
+        self.with_scroller(|s| s.draw());
+    }
+
+    fn handle(&mut self, event: Event) -> Option<()> {
+        let (output, index) = self.with_scroller(|s| {
+            let output = s.handle(event);
+            (output, s.index)
+        });
+        self.index = index;
+        output
+    }
+
+    fn state(&self) -> WidgetState {
+        self.with_scroller(|s| s.state())
+    }
+
+    fn on_idle(&mut self) -> Option<()> {
+        Some(())
+    }
+}
+
+/// Label of the last page of a `HomeScreen`
+pub const QUIT_APP: &str = "Quit app";
+
+/// Idle screen of an app: its icon and name,
+/// the app's own entries, its version, then
+/// "Quit app", which leaves the app
+#[derive(Copy, Clone)]
+pub struct HomeScreen<'a> {
+    pub(crate) name: &'a str,
+    pub(crate) version: &'a str,
+    /// App glyph shown next to the name
+    pub(crate) icon: Option<Icon>,
+    /// Entries between the name and the version
+    pub(crate) entries: &'a [&'a str],
+    /// Page currently shown
+    pub(crate) page: usize,
+}
+
+impl<'a> HomeScreen<'a> {
+    /// Prefer `app_info!(..).home()`
+    pub const fn new(name: &'a str, version: &'a str) -> Self {
+        HomeScreen { name, version, icon: None, entries: &[], page: 0 }
+    }
+
+    pub const fn icon(self, icon: Icon) -> Self {
+        HomeScreen { icon: Some(icon.pos(16, 12)), ..self }
+    }
+
+    /// Pages selectable by the user,
+    /// e.g. "Settings"
+    pub const fn entries(self, entries: &'a [&'a str]) -> Self {
+        HomeScreen { entries, ..self }
+    }
+
+    pub const fn page_count(&self) -> usize {
+        self.entries.len() + 3
+    }
+
+    /// Show the home screen until an entry is
+    /// selected, returning its index. Selecting
+    /// "Quit app" exits the app.
+    pub fn show(&self) -> usize {
+        #[cfg(feature = "nbgl")]
+        return match crate::nbgl::home(self.name, self.version, !self.entries.is_empty()) {
+            true => 0,
+            false => nanos_sdk::exit_app(0),
+        };
+        #[cfg(not(feature = "nbgl"))]
+        match run(&mut { *self }) {
+            Some(index) => index,
+            None => nanos_sdk::exit_app(0),
+        }
+    }
+}
+
+impl Widget for HomeScreen<'_> {
+    /// The selected entry, or None
+    /// for "Quit app"
+    type Output = Option<usize>;
+
+    fn draw(&self) {
+        let count = self.page_count();
+        BLANK.paint();
+        let (name, version) = ([self.name, "is ready"], ["Version", self.version]);
+        let page = match self.page {
+            0 => Page { icon: self.icon, ..Page::new(&name) },
+            p if p == count - 2 => Page::new(&version),
+            p if p == count - 1 => Page::new(&[QUIT_APP]).icon(Icons::CrossBadge),
+            p => Page::new(&self.entries[p - 1..p]),
+        };
+        page.paint();
+        if self.page > 0 {
+            LEFT_ARROW.paint();
+        }
+        if self.page + 1 < count {
+            RIGHT_ARROW.paint();
+        }
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Option<usize>> {
+        let count = self.page_count();
+        match event.action()? {
+            Action::Previous if self.page > 0 => {
+                self.page -= 1;
+                self.draw();
+            }
+            Action::Next if self.page + 1 < count => {
+                self.page += 1;
+                self.draw();
+            }
+            Action::Confirm if self.page == count - 1 => return Some(None),
+            Action::Confirm if self.page > 0 && self.page < count - 2 => {
+                return Some(Some(self.page - 1))
+            }
+            _ => ()
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        let count = self.page_count();
+        let selectable = self.page > 0 && self.page != count - 2;
+        WidgetState::new("HomeScreen", self.page, count)
+            .actions(Actions::PREVIOUS.when(self.page > 0)
+                .with(Actions::NEXT.when(self.page + 1 < count))
+                .with(Actions::CONFIRM.when(selectable)))
+    }
+}
+
+/// `AppInfo` for the calling crate, named
+/// after the crate or the given name
+#[macro_export]
+macro_rules! app_info {
+    () => {
+        $crate::about::AppInfo::new(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+    };
+    ($name:expr) => {
+        $crate::about::AppInfo::new($name, env!("CARGO_PKG_VERSION"))
+    };
+}
@@ -1,7 +1,11 @@
 #![allow(dead_code)] 
 
-use nanos_sdk::seph;
-use nanos_sdk::seph::SephTags;
+use crate::bidi::{self, Direction};
+use crate::canvas;
+use crate::glyph::Glyph;
+use crate::seph;
+use crate::seph::SephTags;
+use crate::style;
 
 #[repr(u8)]
 pub enum BaglTypes {
@@ -30,8 +34,38 @@ pub enum Icons {
   EyeBadge = 27,
 }
 
+pub const BAGL_FONT_ALIGNMENT_LEFT: u32 = 0;
+pub const BAGL_FONT_ALIGNMENT_RIGHT: u32 = 16384;
 pub const BAGL_FONT_ALIGNMENT_CENTER: u32 = 32768;
 
+/// Horizontal alignment of a label's text.
+/// Left and right are swapped for
+/// right-to-left text.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Alignment {
+  Left,
+  Center,
+  Right,
+}
+
+impl Alignment {
+  const fn flags(self) -> u16 {
+    (match self {
+      Alignment::Left => BAGL_FONT_ALIGNMENT_LEFT,
+      Alignment::Center => BAGL_FONT_ALIGNMENT_CENTER,
+      Alignment::Right => BAGL_FONT_ALIGNMENT_RIGHT,
+    }) as u16
+  }
+
+  const fn mirror(self) -> Self {
+    match self {
+      Alignment::Left => Alignment::Right,
+      Alignment::Center => Alignment::Center,
+      Alignment::Right => Alignment::Left,
+    }
+  }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct BaglComponent {
@@ -59,20 +93,87 @@ impl BaglComponent {
 
     seph::seph_send(&[SephTags::ScreenDisplayStatus as u8, 0, bagl_comp.len() as u8]);
     seph::seph_send(bagl_comp);
+    unsafe { DISPLAY_PENDING = true };
+  }
+
+  /// Send the component followed by the
+  /// `payload_len` bytes `payload` sends,
+  /// e.g. the bitmap of an icon
+  pub(crate) fn paint_with(&self, payload_len: usize, payload: impl FnOnce()) {
+    let bagl_comp = unsafe { core::slice::from_raw_parts(self
+                              as *const BaglComponent
+                              as *const u8,
+                              core::mem::size_of::<BaglComponent>()) };
+    let lenbytes = ((bagl_comp.len() + payload_len) as u16).to_be_bytes();
+    seph::seph_send(&[SephTags::ScreenDisplayStatus as u8, lenbytes[0], lenbytes[1]]);
+    seph::seph_send(bagl_comp);
+    payload();
+    unsafe { DISPLAY_PENDING = true };
+  }
+}
+
+/// Tag of the event telling that the
+/// last display status was processed
+pub const DISPLAY_PROCESSED_EVENT: u8 = 0x0D;
+/// Most events kept while waiting for the
+/// display; later ones are dropped
+pub const DEFERRED_MAX: usize = 4;
+
+/// Whether an element was sent and
+/// is not drawn yet
+static mut DISPLAY_PENDING: bool = false;
+/// Events received while waiting for the
+/// display, for `get_event` to give
+static mut DEFERRED: [[u8; 8]; DEFERRED_MAX] = [[0; 8]; DEFERRED_MAX];
+static mut DEFERRED_LEN: usize = 0;
+
+/// Wait for the answer to the status sent last.
+/// After an element, that is its display
+/// processed event: elements sent before it
+/// would be dropped. Other events coming first
+/// are kept for `get_event`, as the status
+/// they answer is sent again.
+pub(crate) fn wait_displayed() {
+  while seph::is_status_sent() {
+    let mut packet = [0u8; 8];
+    seph::seph_recv(&mut packet, 0);
+    if packet[0] == DISPLAY_PROCESSED_EVENT {
+      unsafe { DISPLAY_PENDING = false };
+      continue
+    }
+    unsafe {
+      if DEFERRED_LEN < DEFERRED_MAX {
+        DEFERRED[DEFERRED_LEN] = packet;
+        DEFERRED_LEN += 1;
+      }
+      if DISPLAY_PENDING {
+        seph::send_general_status();
+      }
+    }
+  }
+}
+
+/// Oldest event kept by `wait_displayed`
+pub(crate) fn take_deferred() -> Option<[u8; 8]> {
+  unsafe {
+    if DEFERRED_LEN == 0 {
+      return None
+    }
+    let deferred = &mut *core::ptr::addr_of_mut!(DEFERRED);
+    let packet = deferred[0];
+    deferred.copy_within(1..DEFERRED_LEN, 0);
+    DEFERRED_LEN -= 1;
+    Some(packet)
   }
 }
 
 pub trait Displayable {
   fn wait_for_status(&self) {
-    if seph::is_status_sent() {
-      // TODO: this does not seem like the right way to fix the problem...
-      let mut spi_buffer = [0u8; 16]; 
-      seph::seph_recv(&mut spi_buffer, 0); 
-    }
+    wait_displayed();
   }
   fn paint(&self);
   fn display(&self) {
-    BLANK.paint();
+    crate::screen::clear();
     self.paint();
   }
 }
@@ -119,7 +220,9 @@ pub struct bagl_element_rs<'a> {
 pub struct Icon {
   pub pos: (i16, i16),
   pub dims: (u16, u16),
-  pub glyph_id: u8
+  pub glyph_id: u8,
+  /// Bitmap drawn instead of `glyph_id`
+  pub glyph: Option<&'static Glyph>,
 }
 
 impl Icon {
@@ -127,7 +230,18 @@ impl Icon {
     Icon {
       pos: (12, 12),
       dims: (8, 8),
-      glyph_id: icon_id as u8
+      glyph_id: icon_id as u8,
+      glyph: None,
+    }
+  }
+
+  /// Icon drawing the app's own `glyph`
+  pub const fn from_glyph(glyph: &'static Glyph) -> Icon {
+    Icon {
+      pos: (12, 12),
+      dims: (glyph.width, glyph.height),
+      glyph_id: 0,
+      glyph: Some(glyph),
     }
   }
   pub const fn icon(self, id: u8) -> Self {
@@ -141,8 +255,24 @@ impl Icon {
   pub const fn dims(self, w: u16, h: u16) -> Self {
     Icon {dims: (w,h), ..self}
   }
+
+  /// x right of the icon. Built-in badges
+  /// are wider than `Icon::new` assumes.
+  pub const fn right(&self) -> i16 {
+    let width = match self.glyph {
+      Some(_) => self.dims.0,
+      None if self.dims.0 < BADGE_WIDTH => BADGE_WIDTH,
+      None => self.dims.0,
+    };
+    self.pos.0 + width as i16
+  }
 }
 
+/// Width of the built-in badge glyphs
+const BADGE_WIDTH: u16 = 14;
+/// Space between an icon and its text
+const ICON_TEXT_GAP: i16 = 4;
+
 
 #[derive(Copy,Clone)]
 #[repr(u8)]
@@ -165,12 +295,76 @@ pub enum Font {
   Symbols1,
 }
 
+impl Font {
+  /// Regular text, the default of labels
+  pub const REGULAR: Font = Font::OpenSansRegular11px;
+  /// Emphasized text, see `LabelLine::bold`
+  pub const BOLD: Font = Font::OpenSansExtrabold11px;
+  /// Text too long for a line in `REGULAR`
+  pub const SMALL: Font = Font::OpenSansRegular8_11px;
+
+  /// Width in pixels of `c`, approximated
+  /// from its shape in the 11px fonts
+  pub const fn char_width(self, c: char) -> usize {
+    let width: usize = match c {
+      'i' | 'j' | 'l' | '.' | ',' | ':' | ';' | '\'' | '!' | '|' => 2,
+      'f' | 'r' | 't' | 'I' | ' ' | '(' | ')' | '[' | ']' | '-' => 4,
+      'm' | 'w' | 'M' | 'W' | '@' | '%' => 9,
+      'A'..='Z' => 7,
+      _ => 6,
+    };
+    match self {
+      Font::LucidaConsole8px | Font::OpenSansRegular8_11px | Font::OpenSansSemibold8_11px => {
+        (width * 3).div_ceil(4)
+      }
+      Font::OpenSansExtrabold11px | Font::OpenSansSemibold10_13px | Font::OpenSansSemibold11_16px => {
+        width + 1
+      }
+      Font::OpenSansRegular13_18px | Font::OpenSansSemibold13_18px => (width * 5).div_ceil(4),
+      Font::OpenSansLight16_22px | Font::OpenSansLight16px => (width * 3).div_ceil(2),
+      Font::OpenSansRegular22_30px => width * 2,
+      _ => width,
+    }
+  }
+
+  /// Width in pixels of `text`, approximate
+  /// as `char_width`
+  pub fn width_of(self, text: &str) -> usize {
+    text.chars().map(|c| self.char_width(c)).sum()
+  }
+}
+
+/// Case conversion applied to a label's text
+/// when it is drawn. Only ASCII letters are
+/// converted, as the fonts do not cover others.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextCase {
+  Unchanged,
+  Upper,
+  Lower,
+}
+
+impl TextCase {
+  const fn apply(self, c: char) -> char {
+    match self {
+      TextCase::Unchanged => c,
+      TextCase::Upper => c.to_ascii_uppercase(),
+      TextCase::Lower => c.to_ascii_lowercase(),
+    }
+  }
+}
+
 #[derive(Copy, Clone)]
 pub struct LabelLine<'a> {
   pub pos: (i16, i16),
   pub dims: (u16, u16),
   pub font_id: Font,
-  pub text: Option<&'a str>
+  pub text: Option<&'a str>,
+  pub align: Alignment,
+  pub direction: Direction,
+  pub case: TextCase,
+  /// Black text on a white bar
+  pub inverted: bool,
 }
 
 impl<'a> LabelLine<'a> {
@@ -179,7 +373,11 @@ impl<'a> LabelLine<'a> {
       pos: (0, 20),
       dims: (128, 8),
       font_id: Font::OpenSansRegular11px,
-      text: None
+      text: None,
+      align: Alignment::Center,
+      direction: Direction::Auto,
+      case: TextCase::Unchanged,
+      inverted: false,
     }
   }
 
@@ -199,9 +397,47 @@ impl<'a> LabelLine<'a> {
   pub const fn font(self, font_id: Font) -> Self {
     LabelLine {font_id, ..self}
   }
-  pub fn text(self, m: &'a str) -> Self {
+  pub const fn text(self, m: &'a str) -> Self {
     LabelLine {text: Some(m), ..self}
   }
+  pub const fn align(self, align: Alignment) -> Self {
+    LabelLine {align, ..self}
+  }
+  /// Override the direction detected from
+  /// the first strong character
+  pub const fn direction(self, direction: Direction) -> Self {
+    LabelLine {direction, ..self}
+  }
+  /// Draw the text in upper case, without
+  /// copying it
+  pub const fn uppercase(self) -> Self {
+    LabelLine {case: TextCase::Upper, ..self}
+  }
+  pub const fn lowercase(self) -> Self {
+    LabelLine {case: TextCase::Lower, ..self}
+  }
+  /// Draw black text on a white bar, e.g.
+  /// to highlight a selected entry
+  pub const fn invert(self) -> Self {
+    LabelLine {inverted: true, ..self}
+  }
+  /// Keep only the part of the label right of
+  /// `icon`, so the text is centered in the
+  /// space left rather than under the icon
+  pub const fn beside(self, icon: &Icon) -> Self {
+    let x = icon.right() + ICON_TEXT_GAP;
+    let right = self.pos.0 + self.dims.0 as i16;
+    LabelLine {pos: (x, self.pos.1), dims: ((right - x) as u16, self.dims.1), ..self}
+  }
+  /// White bar behind the text when inverted,
+  /// one pixel above and below the line
+  pub const fn highlight(&self) -> Rect {
+    Rect::new()
+      .pos(self.pos.0, self.pos.1 - self.dims.1 as i16 + 1)
+      .dims(self.dims.0, self.dims.1 + 2)
+      .colors(0xffffff, 0)
+      .fill(true)
+  }
 }
 
 #[derive(Copy, Clone)]
@@ -236,7 +472,11 @@ impl Rect {
 
 impl Displayable for Icon {
   fn paint(&self) {
+    if canvas::divert(|c| c.draw(Bagl::ICON(*self))) {
+      return
+    }
     self.wait_for_status();
+    canvas::untracked();
     let baglcomp = BaglComponent {
       type_: BaglTypes::Icon as u8,
       userid: 0,
@@ -250,15 +490,72 @@ impl Displayable for Icon {
       fgcolor: 0xffffffu32,
       bgcolor: 0,
       font_id: 0,
-      icon_id: self.glyph_id,
+      icon_id: style::glyph(self.glyph_id),
     };
-    baglcomp.paint();
+    match self.glyph {
+      Some(glyph) => baglcomp.paint_with(glyph.payload_len(), || glyph.send()),
+      None => baglcomp.paint(),
+    }
+  }
+}
+
+/// 1 bpp bitmap drawn from data of any
+/// lifetime, lit pixels in white, see
+/// `screen::draw_bitmap`
+#[derive(Copy, Clone)]
+pub struct Bitmap<'a> {
+  pub pos: (i16, i16),
+  pub dims: (u16, u16),
+  /// Pixels row after row, without padding
+  /// between rows, least significant bits first
+  pub data: &'a [u8],
+}
+
+impl<'a> Bitmap<'a> {
+  pub const fn new(data: &'a [u8], w: u16, h: u16) -> Self {
+    Bitmap { pos: (0, 0), dims: (w, h), data }
+  }
+
+  pub const fn pos(self, x: i16, y: i16) -> Self {
+    Bitmap { pos: (x, y), ..self }
+  }
+}
+
+impl Displayable for Bitmap<'_> {
+  fn paint(&self) {
+    if canvas::divert(|c| c.bitmap(self)) {
+      return
+    }
+    self.wait_for_status();
+    canvas::untracked();
+    let baglcomp = BaglComponent {
+      type_: BaglTypes::Icon as u8,
+      userid: 0,
+      x: self.pos.0,
+      y: self.pos.1,
+      width: self.dims.0,
+      height: self.dims.1,
+      stroke: 0,
+      radius: 0,
+      fill: 0,
+      fgcolor: 0xffffffu32,
+      bgcolor: 0,
+      font_id: 0,
+      icon_id: 0,
+    };
+    let colors = &crate::glyph::MONOCHROME;
+    baglcomp.paint_with(1 + colors.len() * 4 + self.data.len(),
+      || crate::glyph::send_bitmap(1, colors, self.data));
   }
 }
 
 impl Displayable for Rect {
   fn paint(&self) {
+    if canvas::divert(|c| c.draw(Bagl::RECT(*self))) {
+      return
+    }
     self.wait_for_status();
+    canvas::untracked();
     let baglcomp = BaglComponent {
       type_: BaglTypes::Rectangle as u8,
       userid: self.userid,
@@ -280,7 +577,20 @@ impl Displayable for Rect {
 
 impl<'a> Displayable for LabelLine<'a> {
   fn paint(&self) {
+    if canvas::divert(|c| c.draw(Bagl::LABELLINE(*self))) {
+      return
+    }
     self.wait_for_status();
+    canvas::untracked();
+    let txt = self.text.unwrap(); 
+    let rtl = bidi::is_rtl(txt, self.direction);
+    let align = if rtl { self.align.mirror() } else { self.align };
+    let (fgcolor, bgcolor) = if self.inverted {
+      self.highlight().paint();
+      (0, 0xffffffu32)
+    } else {
+      (0xffffffu32, 0)
+    };
     let baglcomp = BaglComponent {
       type_: BaglTypes::LabelLine as u8,
       userid: 0,  // FIXME
@@ -291,9 +601,9 @@ impl<'a> Displayable for LabelLine<'a> {
       stroke: 0,
       radius: 0,
       fill: 0,
-      fgcolor: 0xffffffu32,
-      bgcolor: 0,
-      font_id: self.font_id as u16 | BAGL_FONT_ALIGNMENT_CENTER as u16,
+      fgcolor,
+      bgcolor,
+      font_id: style::font(self.font_id) as u16 | align.flags(),
       icon_id: 0,
     };
 
@@ -301,16 +611,56 @@ impl<'a> Displayable for LabelLine<'a> {
                               as *const BaglComponent 
                               as *const u8,
                               core::mem::size_of::<BaglComponent>()) };
-    let txt = self.text.unwrap(); 
     let lenbytes = ((bagl_comp.len() + txt.len()) as u16).to_be_bytes();
     seph::seph_send(&[SephTags::ScreenDisplayStatus as u8, lenbytes[0], lenbytes[1]]);
     seph::seph_send(bagl_comp);
-    seph::seph_send(txt.as_bytes());
+    // Reordering and case conversion
+    // keep the byte length
+    let case = self.case;
+    if rtl {
+      let mut sender = TextSender::new();
+      bidi::visual_chars(txt, &mut |c| sender.push(case.apply(c)));
+      sender.flush();
+    } else if case != TextCase::Unchanged {
+      let mut sender = TextSender::new();
+      txt.chars().for_each(|c| sender.push(case.apply(c)));
+      sender.flush();
+    } else {
+      seph::seph_send(txt.as_bytes());
+    }
+  }
+}
+
+/// Sends text generated on the fly
+/// through a small stack buffer
+struct TextSender {
+  buf: [u8; 32],
+  len: usize,
+}
+
+impl TextSender {
+  const fn new() -> Self {
+    TextSender { buf: [0; 32], len: 0 }
+  }
+
+  fn push(&mut self, c: char) {
+    if self.len + c.len_utf8() > self.buf.len() {
+      self.flush();
+    }
+    self.len += c.encode_utf8(&mut self.buf[self.len..]).len();
+  }
+
+  fn flush(&mut self) {
+    if self.len > 0 {
+      seph::seph_send(&self.buf[..self.len]);
+      self.len = 0;
+    }
   }
 }
 
 
-/// Some common constant Bagls
+/// Some common constant Bagls, laid out
+/// for the Nano S (see `screen`)
 pub const BLANK: Rect = Rect::new().pos(0,0).dims(128, 32).colors(0, 0xffffff).fill(true);
 
 pub const LEFT_ARROW: Icon = Icon::new(Icons::Left).pos(2, 12);
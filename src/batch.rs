@@ -0,0 +1,110 @@
+//! Elements of a screen queued, then sent
+//! together by `flush`:
+//!
+//! ```ignore
+//! let mut batch = Batch::new();
+//! batch.clear();
+//! batch.push(Bagl::ICON(screen.place(icon)));
+//! batch.push(Bagl::LABELLINE(title));
+//! pager.queue_arrows(&mut batch);
+//! batch.flush();
+//! ```
+//!
+//! `swap` shows the queued elements in place
+//! of the screen's instead, without clearing
+//! it: elements already shown are left alone,
+//! see `canvas`.
+//!
+//! SEPH takes one element per exchange, each
+//! waiting for the previous one to be processed.
+//! Knowing the whole screen, the batch leaves out
+//! the exchanges of elements hidden by a later
+//! opaque one, and of clearing a screen
+//! already blank.
+
+use crate::bagls::Bagl;
+use crate::canvas::{self, Frame, Region};
+use crate::screen;
+
+/// Most elements queued before
+/// `push` flushes the batch
+pub const BATCH_MAX_ELEMENTS: usize = 8;
+
+pub struct Batch<'a> {
+    elements: [Option<Bagl<'a>>; BATCH_MAX_ELEMENTS],
+    len: usize,
+    /// Clear the screen first
+    clear: bool,
+}
+
+impl<'a> Batch<'a> {
+    pub const fn new() -> Self {
+        Batch { elements: [None; BATCH_MAX_ELEMENTS], len: 0, clear: false }
+    }
+
+    /// Clear the screen before drawing.
+    /// Elements queued so far are dropped.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.clear = true;
+    }
+
+    /// Queue `element`, flushing
+    /// first if the batch is full
+    pub fn push(&mut self, element: Bagl<'a>) {
+        if self.len == BATCH_MAX_ELEMENTS {
+            self.flush();
+        }
+        self.elements[self.len] = Some(element);
+        self.len += 1;
+    }
+
+    /// Send the queued elements
+    pub fn flush(&mut self) {
+        if self.clear && !canvas::is_blank() {
+            screen::clear();
+        }
+        let elements = &self.elements[..self.len];
+        for (i, element) in elements.iter().enumerate() {
+            let element = match element {
+                Some(element) => element,
+                None => continue,
+            };
+            let region = Region::of(element);
+            let hidden = elements[i + 1..].iter().flatten()
+                .any(|later| opaque(later) && Region::of(later).contains(&region));
+            if !hidden {
+                element.paint();
+            }
+        }
+        self.len = 0;
+        self.clear = false;
+    }
+
+    /// Show the queued elements, and only them,
+    /// drawing those not already on the screen
+    pub fn swap(&mut self) {
+        let mut frame = Frame::begin();
+        for element in self.elements[..self.len].iter().flatten() {
+            frame.draw(*element);
+        }
+        frame.end();
+        self.len = 0;
+        self.clear = false;
+    }
+}
+
+impl Default for Batch<'_> {
+    fn default() -> Self {
+        Batch::new()
+    }
+}
+
+/// Whether `element` hides what is under it
+fn opaque(element: &Bagl) -> bool {
+    match element {
+        Bagl::RECT(rect) => rect.fill,
+        Bagl::LABELLINE(label) => label.inverted,
+        Bagl::ICON(_) => false,
+    }
+}
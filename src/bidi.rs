@@ -0,0 +1,120 @@
+//! Minimal bidirectional text support: labels
+//! in right-to-left scripts (Hebrew, Arabic)
+//! are reordered from logical to visual order
+//! before being sent to the screen.
+//!
+//! This is a simplified form of the Unicode
+//! bidi algorithm: a single embedding level,
+//! where neutral characters between two
+//! left-to-right ones stay left-to-right and
+//! all others follow the paragraph direction.
+
+/// Direction of a label's text
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Decided by the first strong character
+    Auto,
+    Ltr,
+    Rtl,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Class {
+    L,
+    R,
+    Neutral,
+}
+
+fn class(c: char) -> Class {
+    match c as u32 {
+        0x0590..=0x08ff | 0xfb1d..=0xfdff | 0xfe70..=0xfeff => Class::R,
+        _ if c.is_alphanumeric() => Class::L,
+        _ => Class::Neutral,
+    }
+}
+
+/// Whether `text` reads right to left
+/// when its direction is `dir`
+pub fn is_rtl(text: &str, dir: Direction) -> bool {
+    match dir {
+        Direction::Ltr => false,
+        Direction::Rtl => true,
+        Direction::Auto => {
+            text.chars().map(class).find(|c| *c != Class::Neutral) == Some(Class::R)
+        }
+    }
+}
+
+/// Resolved direction of the character
+/// starting at byte `i` of a right-to-left text
+fn resolved(text: &str, i: usize) -> Class {
+    let c = text[i..].chars().next().unwrap_or(' ');
+    match class(c) {
+        Class::Neutral => {
+            let strong = |c: &Class| *c != Class::Neutral;
+            let before = text[..i].chars().rev().map(class).find(strong);
+            let after = text[i + c.len_utf8()..].chars().map(class).find(strong);
+            if before == Some(Class::L) && after == Some(Class::L) {
+                Class::L
+            } else {
+                Class::R
+            }
+        }
+        strong => strong,
+    }
+}
+
+/// Mirrored form of paired punctuation,
+/// drawn reversed in right-to-left runs
+fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        _ => c,
+    }
+}
+
+/// Feed the characters of the right-to-left
+/// `text` to `f` in visual order, left to right
+pub fn visual_chars(text: &str, f: &mut dyn FnMut(char)) {
+    let mut end = text.len();
+    while let Some((last, _)) = text[..end].char_indices().next_back() {
+        // Extend the run backwards while the
+        // resolved direction does not change
+        let dir = resolved(text, last);
+        let mut start = last;
+        while let Some((i, _)) = text[..start].char_indices().next_back() {
+            if resolved(text, i) != dir {
+                break
+            }
+            start = i;
+        }
+        let run = &text[start..end];
+        if dir == Class::R {
+            run.chars().rev().map(mirror).for_each(&mut *f);
+        } else {
+            run.chars().for_each(&mut *f);
+        }
+        end = start;
+    }
+}
+
+/// Copy `text` in visual order, or `None`
+/// if `buf` is too small
+pub fn visual_order<'b>(text: &str, dir: Direction, buf: &'b mut [u8]) -> Option<&'b str> {
+    let buf = buf.get_mut(..text.len())?;
+    if !is_rtl(text, dir) {
+        buf.copy_from_slice(text.as_bytes());
+    } else {
+        let mut len = 0;
+        visual_chars(text, &mut |c| len += c.encode_utf8(&mut buf[len..]).len());
+    }
+    // Only whole characters were written
+    Some(unsafe { core::str::from_utf8_unchecked(buf) })
+}
@@ -0,0 +1,80 @@
+//! The warning shown before signing data the
+//! app cannot display, e.g. a contract call
+//! signed by its hash only:
+//!
+//! ```ignore
+//! if !settings.blind_signing {
+//!     return Err(SW_BLIND_SIGNING_DISABLED)
+//! }
+//! if BlindSignWarning::new().ask() != Outcome::Approved {
+//!     return Err(SW_DENIED)
+//! }
+//! ```
+//!
+//! The warning and its explanation come first,
+//! then the reject step, so that the user has
+//! to go past it to accept the risk.
+
+use crate::bagls::Icon;
+use crate::flow::{Flow, Layout, Step};
+use crate::glyph::{pack_rows, packed_len, Glyph};
+use crate::i18n::{tr, Text};
+use crate::widget::Outcome;
+
+/// Default explanation, one pair
+/// of lines per page
+pub const BLIND_SIGN_EXPLANATION: [[&str; 2]; 2] = [
+    ["This transaction", "cannot be verified"],
+    ["Accept only if you", "trust the source"],
+];
+
+const WARNING_ROWS: [u8; 28] = [
+    0xc0, 0x00, 0xc0, 0x00, 0xe0, 0x01, 0xe0, 0x01, 0x30, 0x03, 0x30, 0x03, 0x38, 0x07,
+    0x38, 0x07, 0x3c, 0x0f, 0xfc, 0x0f, 0x3e, 0x1f, 0x3e, 0x1f, 0xff, 0x3f, 0xff, 0x3f,
+];
+const WARNING_BITMAP: [u8; packed_len(14, 14, 1)] = pack_rows(&WARNING_ROWS, 14, 14);
+/// Warning sign, a triangle with an
+/// exclamation mark
+pub static WARNING: Glyph = Glyph::new(&WARNING_BITMAP, 14, 14);
+
+/// Step accepting the risk,
+/// after the reject step
+const ACCEPT_STEP: usize = 4;
+
+#[derive(Copy, Clone)]
+pub struct BlindSignWarning<'a> {
+    pub(crate) explanation: [[&'a str; 2]; 2],
+}
+
+impl<'a> BlindSignWarning<'a> {
+    pub const fn new() -> Self {
+        BlindSignWarning { explanation: BLIND_SIGN_EXPLANATION }
+    }
+
+    /// Explain the risk with `pages`
+    /// instead of the default text
+    pub const fn explanation(self, pages: [[&'a str; 2]; 2]) -> Self {
+        BlindSignWarning { explanation: pages }
+    }
+
+    /// Show the warning until the user accepts
+    /// the risk, giving `Approved`, or rejects,
+    /// giving `Rejected`
+    pub fn ask(&self) -> Outcome {
+        let [[a, b], [c, d]] = self.explanation;
+        let steps = [
+            Step::new(Layout::Pbn(Icon::from_glyph(&WARNING), "Blind signing", "ahead")),
+            Step::text(a, b),
+            Step::text(c, d),
+            Step::reject(tr(Text::Reject)),
+            Step::confirm(tr(Text::AcceptRisk)),
+        ];
+        Outcome::from(Flow::new(&steps).run() == ACCEPT_STEP)
+    }
+}
+
+impl Default for BlindSignWarning<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
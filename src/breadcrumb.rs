@@ -0,0 +1,104 @@
+//! Trail of the nested menus the user went
+//! through, shown as a compact header such as
+//! "Settings > Display" so they know where
+//! they are.
+//!
+//! Whoever opens a nested screen pushes its
+//! name and pops it when leaving:
+//!
+//! ```ignore
+//! breadcrumb::push("Settings");
+//! let choice = settings_menu.show();
+//! breadcrumb::pop();
+//! ```
+//!
+//! `NestedMenu` does so for its own submenus.
+//! While the trail is not empty, `Menu` shows
+//! it above the selected entry.
+
+use crate::bagls::*;
+use crate::text::UiString;
+
+/// Deepest trail kept; deeper names are ignored
+pub const MAX_DEPTH: usize = 4;
+/// Longest header, in characters
+pub const HEADER_MAX_CHARS: usize = 24;
+
+const SEPARATOR: &str = " > ";
+/// Replaces the first names of a header too long
+const ELLIPSIS: &str = "..";
+
+static mut TRAIL: [&str; MAX_DEPTH] = [""; MAX_DEPTH];
+static mut DEPTH: usize = 0;
+
+/// Enter the screen `name`. Returns false
+/// if the trail is full.
+pub fn push(name: &'static str) -> bool {
+    let depth = depth();
+    if depth == MAX_DEPTH {
+        return false
+    }
+    unsafe {
+        TRAIL[depth] = name;
+        DEPTH = depth + 1;
+    }
+    true
+}
+
+/// Leave the innermost screen
+pub fn pop() {
+    unsafe { DEPTH = depth().saturating_sub(1) }
+}
+
+pub fn clear() {
+    unsafe { DEPTH = 0 }
+}
+
+pub fn depth() -> usize {
+    unsafe { DEPTH }
+}
+
+/// Name at `level` of the trail, 0 being
+/// the outermost
+pub fn get(level: usize) -> Option<&'static str> {
+    match level < depth() {
+        true => Some(unsafe { TRAIL[level] }),
+        false => None,
+    }
+}
+
+/// The header text, dropping the outermost
+/// names while it is too long
+pub fn header() -> UiString<64> {
+    let names = unsafe { TRAIL };
+    let trail = &names[..depth()];
+    for skip in 0..trail.len() {
+        let mut text = UiString::new();
+        if skip > 0 {
+            text.push_str(ELLIPSIS);
+        }
+        for (i, name) in trail[skip..].iter().enumerate() {
+            if i > 0 || skip > 0 {
+                text.push_str(SEPARATOR);
+            }
+            text.push_str(name);
+        }
+        if text.chars().count() <= HEADER_MAX_CHARS || skip + 1 == trail.len() {
+            return text
+        }
+    }
+    UiString::new()
+}
+
+/// Paint the header at the top of the screen
+pub fn paint() {
+    let header = header();
+    if !header.is_empty() {
+        label(header.as_str()).paint();
+    }
+}
+
+/// Label showing `header` at the top of the screen
+pub(crate) fn label(header: &str) -> LabelLine<'_> {
+    LabelLine::new().font(Font::OpenSansRegular8_11px).dims(128, 8).pos(0, 9).text(header)
+}
@@ -0,0 +1,314 @@
+//! Button state machine turning the raw
+//! masks reported by SEPH button push events
+//! into press and release events.
+//!
+//! | state   | mask 0                 | mask 1 (left)          | mask 2 (right)          | mask 3 (both)     |
+//! |---------|------------------------|------------------------|-------------------------|-------------------|
+//! | `Idle`  | -                      | `Left`, left press     | `Right`, right press    | `Both`, both press|
+//! | `Left`  | `Idle`, left release   | - (auto-repeat)        | `Right`, left release + right press | `Both`, both press |
+//! | `Right` | `Idle`, right release  | `Left`, right release + left press | - (auto-repeat) | `Both`, both press |
+//! | `Both`  | `Idle`, both release   | - (latched)            | - (latched)             | -                 |
+//!
+//! Once both buttons are down, the machine stays
+//! in `Both` until every button is released, so
+//! releasing the buttons one after the other
+//! yields a single both release.
+//!
+//! Holding the buttons for `LONG_PRESS_TICKS`
+//! ticker events also yields a long press
+//! event, before the release.
+
+/// Events produced by the button state machine
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ButtonEvent {
+    LeftButtonPress,
+    RightButtonPress,
+    BothButtonsPress,
+    LeftButtonRelease,
+    RightButtonRelease,
+    BothButtonsRelease,
+    /// Held for `LONG_PRESS_TICKS`
+    LeftButtonLongPress,
+    RightButtonLongPress,
+    BothButtonsLongPress,
+}
+
+/// Ticker events (10 per second) a button
+/// must be held to give a long press
+pub const LONG_PRESS_TICKS: u32 = 20;
+
+/// Button events reported by the SDK's
+/// `io::Comm`, for apps driving the UI from it
+impl From<nanos_sdk::buttons::ButtonEvent> for ButtonEvent {
+    fn from(event: nanos_sdk::buttons::ButtonEvent) -> Self {
+        use nanos_sdk::buttons::ButtonEvent as Sdk;
+        match event {
+            Sdk::LeftButtonPress => ButtonEvent::LeftButtonPress,
+            Sdk::RightButtonPress => ButtonEvent::RightButtonPress,
+            Sdk::BothButtonsPress => ButtonEvent::BothButtonsPress,
+            Sdk::LeftButtonRelease => ButtonEvent::LeftButtonRelease,
+            Sdk::RightButtonRelease => ButtonEvent::RightButtonRelease,
+            Sdk::BothButtonsRelease => ButtonEvent::BothButtonsRelease,
+        }
+    }
+}
+
+/// States of the button state machine
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Buttons {
+    Idle,
+    Left,
+    Right,
+    Both,
+}
+
+/// Bits of the raw button mask
+pub const LEFT_MASK: u8 = 1;
+pub const RIGHT_MASK: u8 = 2;
+
+/// Outcome of feeding a mask to a state
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Transition {
+    pub from: Buttons,
+    pub mask: u8,
+    pub to: Buttons,
+    /// Events emitted, in order
+    pub events: &'static [ButtonEvent],
+}
+
+impl Buttons {
+    pub const ALL: [Buttons; 4] = [Buttons::Idle, Buttons::Left, Buttons::Right, Buttons::Both];
+
+    /// Apply a raw button mask. Bits other
+    /// than left and right are ignored.
+    pub const fn transition(self, mask: u8) -> Transition {
+        use ButtonEvent::*;
+        use Buttons::*;
+
+        let mask = mask & (LEFT_MASK | RIGHT_MASK);
+        let (to, events): (Buttons, &'static [ButtonEvent]) = match (self, mask) {
+            (Idle, 0) => (Idle, &[]),
+            (Idle, 1) => (Left, &[LeftButtonPress]),
+            (Idle, 2) => (Right, &[RightButtonPress]),
+            (Left, 0) => (Idle, &[LeftButtonRelease]),
+            (Left, 1) => (Left, &[]),
+            (Left, 2) => (Right, &[LeftButtonRelease, RightButtonPress]),
+            (Right, 0) => (Idle, &[RightButtonRelease]),
+            (Right, 1) => (Left, &[RightButtonRelease, LeftButtonPress]),
+            (Right, 2) => (Right, &[]),
+            (Both, 0) => (Idle, &[BothButtonsRelease]),
+            (Both, _) => (Both, &[]),
+            (_, _) => (Both, &[BothButtonsPress]),
+        };
+        Transition { from: self, mask, to, events }
+    }
+}
+
+/// Every (state, mask) transition of the machine
+pub fn transitions() -> impl Iterator<Item = Transition> {
+    Buttons::ALL.iter().flat_map(|s| (0..4).map(move |m| s.transition(m)))
+}
+
+/// Button state kept across calls to `get_event`
+pub struct ButtonsState {
+    pub state: Buttons,
+    /// Second event of the last transition,
+    /// returned by the next call to `get_event`
+    pub pending: Option<ButtonEvent>,
+    pub cmd_buffer: [u8; 8],
+    /// Ticker events since the last button event
+    pub idle_ticks: u32,
+    /// Where the finger went down, while
+    /// touching the screen
+    pub touch_start: Option<(u16, u16)>,
+    /// Ticker events since the buttons
+    /// reached their current state
+    pub held_ticks: u32,
+}
+
+impl ButtonsState {
+    pub const fn new() -> ButtonsState {
+        ButtonsState { state: Buttons::Idle, pending: None, cmd_buffer: [0; 8], idle_ticks: 0,
+            touch_start: None, held_ticks: 0 }
+    }
+
+    /// Count a ticker event, returning the long
+    /// press event once held long enough
+    pub fn tick(&mut self) -> Option<ButtonEvent> {
+        if self.state == Buttons::Idle {
+            return None
+        }
+        self.held_ticks = self.held_ticks.saturating_add(1);
+        if self.held_ticks != LONG_PRESS_TICKS {
+            return None
+        }
+        match self.state {
+            Buttons::Left => Some(ButtonEvent::LeftButtonLongPress),
+            Buttons::Right => Some(ButtonEvent::RightButtonLongPress),
+            Buttons::Both => Some(ButtonEvent::BothButtonsLongPress),
+            Buttons::Idle => None,
+        }
+    }
+}
+
+impl Default for ButtonsState {
+    fn default() -> Self {
+        ButtonsState::new()
+    }
+}
+
+/// Feed a new raw button mask to the state
+/// machine and return the resulting event.
+/// When a transition emits two events, the
+/// second one is stored in `buttons.pending`.
+pub fn get_button_event(buttons: &mut ButtonsState, new: u8) -> Option<ButtonEvent> {
+    let t = buttons.state.transition(new);
+    if t.to != t.from {
+        buttons.held_ticks = 0;
+    }
+    buttons.state = t.to;
+    buttons.pending = t.events.get(1).copied();
+    t.events.first().copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ButtonEvent::*;
+    use Buttons::*;
+
+    /// The table of the module documentation, as
+    /// (state, mask, next state, events emitted)
+    const TABLE: [(Buttons, u8, Buttons, &[ButtonEvent]); 16] = [
+        (Idle, 0, Idle, &[]),
+        (Idle, 1, Left, &[LeftButtonPress]),
+        (Idle, 2, Right, &[RightButtonPress]),
+        (Idle, 3, Both, &[BothButtonsPress]),
+        (Left, 0, Idle, &[LeftButtonRelease]),
+        (Left, 1, Left, &[]),
+        (Left, 2, Right, &[LeftButtonRelease, RightButtonPress]),
+        (Left, 3, Both, &[BothButtonsPress]),
+        (Right, 0, Idle, &[RightButtonRelease]),
+        (Right, 1, Left, &[RightButtonRelease, LeftButtonPress]),
+        (Right, 2, Right, &[]),
+        (Right, 3, Both, &[BothButtonsPress]),
+        (Both, 0, Idle, &[BothButtonsRelease]),
+        (Both, 1, Both, &[]),
+        (Both, 2, Both, &[]),
+        (Both, 3, Both, &[]),
+    ];
+
+    /// Machine in `state`, reached from idle
+    fn reach(state: Buttons) -> ButtonsState {
+        let mut buttons = ButtonsState::new();
+        let mask = match state {
+            Idle => 0,
+            Left => 1,
+            Right => 2,
+            Both => 3,
+        };
+        get_button_event(&mut buttons, mask);
+        buttons.pending = None;
+        assert_eq!(buttons.state, state);
+        buttons
+    }
+
+    /// Events of feeding `mask` to `buttons`: the
+    /// one returned, then the one left pending
+    fn step(buttons: &mut ButtonsState, mask: u8) -> [Option<ButtonEvent>; 2] {
+        let first = get_button_event(buttons, mask);
+        [first, buttons.pending.take()]
+    }
+
+    fn expected(events: &[ButtonEvent]) -> [Option<ButtonEvent>; 2] {
+        [events.first().copied(), events.get(1).copied()]
+    }
+
+    #[test]
+    fn every_transition() {
+        for &(from, mask, to, events) in TABLE.iter() {
+            let mut buttons = reach(from);
+            assert_eq!(step(&mut buttons, mask), expected(events), "{:?} on mask {}", from, mask);
+            assert_eq!(buttons.state, to, "{:?} on mask {}", from, mask);
+        }
+    }
+
+    #[test]
+    fn transitions_match_the_machine() {
+        assert_eq!(transitions().count(), TABLE.len());
+        for t in transitions() {
+            let mut buttons = reach(t.from);
+            assert_eq!(step(&mut buttons, t.mask), expected(t.events));
+            assert_eq!(buttons.state, t.to);
+        }
+    }
+
+    #[test]
+    fn extra_mask_bits_ignored() {
+        for &(from, mask, to, events) in TABLE.iter() {
+            let mut buttons = reach(from);
+            assert_eq!(step(&mut buttons, mask | 0xfc), expected(events));
+            assert_eq!(buttons.state, to);
+        }
+    }
+
+    /// Events of feeding `masks` from idle
+    fn feed(masks: &[u8]) -> ([Option<ButtonEvent>; 8], usize) {
+        let mut buttons = ButtonsState::new();
+        let mut events = [None; 8];
+        let mut n = 0;
+        for &mask in masks {
+            let first = get_button_event(&mut buttons, mask);
+            for event in [first, buttons.pending.take()].iter().flatten().copied() {
+                events[n] = Some(event);
+                n += 1;
+            }
+        }
+        (events, n)
+    }
+
+    #[test]
+    fn left_to_right_without_release() {
+        let (events, n) = feed(&[1, 2, 0]);
+        assert_eq!(&events[..n], &[Some(LeftButtonPress), Some(LeftButtonRelease),
+            Some(RightButtonPress), Some(RightButtonRelease)]);
+    }
+
+    #[test]
+    fn both_latched_until_all_released() {
+        let (events, n) = feed(&[1, 3, 2, 1, 0]);
+        assert_eq!(&events[..n], &[Some(LeftButtonPress), Some(BothButtonsPress),
+            Some(BothButtonsRelease)]);
+    }
+
+    #[test]
+    fn long_presses() {
+        for &(mask, long) in [(1, LeftButtonLongPress), (2, RightButtonLongPress),
+            (3, BothButtonsLongPress)].iter() {
+            let mut buttons = ButtonsState::new();
+            get_button_event(&mut buttons, mask);
+            for _ in 1..LONG_PRESS_TICKS {
+                assert_eq!(buttons.tick(), None);
+            }
+            assert_eq!(buttons.tick(), Some(long));
+            // Only once per press
+            assert_eq!(buttons.tick(), None);
+        }
+        assert_eq!(ButtonsState::new().tick(), None);
+    }
+
+    #[test]
+    fn long_press_restarts_on_new_state() {
+        let mut buttons = ButtonsState::new();
+        get_button_event(&mut buttons, 1);
+        for _ in 1..LONG_PRESS_TICKS {
+            buttons.tick();
+        }
+        get_button_event(&mut buttons, 3);
+        assert_eq!(buttons.tick(), None);
+        for _ in 2..LONG_PRESS_TICKS {
+            buttons.tick();
+        }
+        assert_eq!(buttons.tick(), Some(BothButtonsLongPress));
+    }
+}
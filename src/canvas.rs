@@ -0,0 +1,347 @@
+//! Tracking of what the screen shows, so that
+//! widgets only redraw what changed instead of
+//! clearing the whole screen, which flickers on
+//! the device:
+//!
+//! ```ignore
+//! let mut frame = Frame::begin();
+//! frame.draw(Bagl::ICON(screen.place(DOWN_ARROW)));
+//! frame.draw(Bagl::LABELLINE(LabelLine::new().text(entry)));
+//! frame.end();
+//! ```
+//!
+//! Elements already drawn by the previous frame
+//! are left alone, those it drew and this one
+//! does not are cleared. Anything painted
+//! outside of a frame makes the next frame
+//! start from a clear screen.
+//!
+//! Painting can also be diverted to a `Canvas`,
+//! to render a widget somewhere else than on the
+//! screen, e.g. in a unit test, see `capture`.
+
+use crate::bagls::{Bagl, Bitmap, Displayable, Rect};
+use crate::style;
+
+/// Most elements a frame tracks. A frame
+/// with more is redrawn from a clear screen.
+pub const CANVAS_MAX_ELEMENTS: usize = 12;
+
+/// Height of the text of the 11px fonts,
+/// above the baseline
+const TEXT_HEIGHT: u16 = 11;
+
+/// Area of the screen covered by an element
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Region {
+    /// Area `element` draws on
+    pub fn of(element: &Bagl) -> Self {
+        match element {
+            Bagl::LABELLINE(label) => {
+                // Text rises above short labels
+                let bar = label.dims(label.dims.0, label.dims.1.max(TEXT_HEIGHT)).highlight();
+                Region { x: bar.pos.0, y: bar.pos.1, width: bar.dims.0, height: bar.dims.1 }
+            }
+            Bagl::RECT(rect) => Region { x: rect.pos.0, y: rect.pos.1, width: rect.dims.0, height: rect.dims.1 },
+            Bagl::ICON(icon) => {
+                // Built-in glyphs are square
+                let width = (icon.right() - icon.pos.0) as u16;
+                let height = match icon.glyph {
+                    Some(_) => icon.dims.1,
+                    None => icon.dims.1.max(width),
+                };
+                Region { x: icon.pos.0, y: icon.pos.1, width, height }
+            }
+        }
+    }
+
+    pub const fn intersects(&self, other: &Region) -> bool {
+        self.x < other.x + other.width as i16
+            && other.x < self.x + self.width as i16
+            && self.y < other.y + other.height as i16
+            && other.y < self.y + self.height as i16
+    }
+
+    /// Whether `other` lies within this region
+    pub const fn contains(&self, other: &Region) -> bool {
+        self.x <= other.x
+            && self.y <= other.y
+            && other.x + other.width as i16 <= self.x + self.width as i16
+            && other.y + other.height as i16 <= self.y + self.height as i16
+    }
+
+    fn clear(&self) {
+        Rect::new().pos(self.x, self.y).dims(self.width, self.height)
+            .colors(0, 0xffffff).fill(true).paint();
+    }
+}
+
+/// An element on the screen: a hash of
+/// what it draws, and where
+#[derive(Copy, Clone)]
+struct Drawn {
+    key: u32,
+    region: Region,
+}
+
+impl Drawn {
+    const NONE: Drawn = Drawn { key: 0, region: Region { x: 0, y: 0, width: 0, height: 0 } };
+}
+
+#[derive(Copy, Clone)]
+struct Elements {
+    items: [Drawn; CANVAS_MAX_ELEMENTS],
+    len: usize,
+}
+
+impl Elements {
+    const EMPTY: Elements = Elements { items: [Drawn::NONE; CANVAS_MAX_ELEMENTS], len: 0 };
+
+    fn iter(&self) -> impl Iterator<Item = &Drawn> {
+        self.items[..self.len].iter()
+    }
+
+    /// False when full
+    fn push(&mut self, drawn: Drawn) -> bool {
+        if self.len == CANVAS_MAX_ELEMENTS {
+            return false
+        }
+        self.items[self.len] = drawn;
+        self.len += 1;
+        true
+    }
+
+    fn remove(&mut self, i: usize) -> Drawn {
+        let drawn = self.items[i];
+        self.items.copy_within(i + 1..self.len, i);
+        self.len -= 1;
+        drawn
+    }
+
+    /// Remove the element drawing `key`,
+    /// returning whether there was one
+    fn take(&mut self, key: u32) -> bool {
+        let found = self.iter().position(|d| d.key == key);
+        if let Some(i) = found {
+            self.remove(i);
+        }
+        found.is_some()
+    }
+}
+
+/// The elements on the screen, None if
+/// something else drew on it
+static mut CONTENT: Option<Elements> = None;
+/// Whether a frame is being drawn
+static mut IN_FRAME: bool = false;
+
+/// Where widgets draw. The screen is
+/// `Display`: other canvases record or
+/// forward what is drawn on them.
+pub trait Canvas {
+    fn draw(&mut self, element: Bagl);
+
+    /// Blank the whole canvas
+    fn clear(&mut self);
+
+    /// See `screen::draw_bitmap`
+    fn bitmap(&mut self, _bitmap: &Bitmap) {}
+}
+
+/// The screen
+pub struct Display;
+
+impl Canvas for Display {
+    fn draw(&mut self, element: Bagl) {
+        element.paint();
+    }
+
+    fn clear(&mut self) {
+        crate::screen::clear();
+    }
+
+    fn bitmap(&mut self, bitmap: &Bitmap) {
+        bitmap.paint();
+    }
+}
+
+/// Drops everything drawn on it
+pub(crate) struct Discard;
+
+impl Canvas for Discard {
+    fn draw(&mut self, _element: Bagl) {}
+
+    fn clear(&mut self) {}
+}
+
+/// Canvas painting is diverted to,
+/// while `capture` runs
+static mut TARGET: Option<*mut dyn Canvas> = None;
+
+/// Run `paint`, drawing on `canvas` whatever
+/// it paints instead of the screen. What the
+/// screen shows is left as it was.
+pub fn capture(canvas: &mut dyn Canvas, paint: impl FnOnce()) {
+    let target: *mut (dyn Canvas + '_) = canvas;
+    // Only used until `paint` returns
+    let target = unsafe { core::mem::transmute::<*mut (dyn Canvas + '_), *mut dyn Canvas>(target) };
+    let saved = unsafe { (TARGET, CONTENT, IN_FRAME) };
+    unsafe {
+        TARGET = Some(target);
+        // Frames draw every element
+        CONTENT = None;
+        IN_FRAME = false;
+    }
+    paint();
+    unsafe { (TARGET, CONTENT, IN_FRAME) = saved };
+}
+
+/// Hand the canvas of `capture` to `draw`,
+/// returning whether there was one. While it
+/// draws, painting goes to the screen.
+pub(crate) fn divert(draw: impl FnOnce(&mut dyn Canvas)) -> bool {
+    match unsafe { TARGET } {
+        Some(target) => {
+            unsafe { TARGET = None };
+            draw(unsafe { &mut *target });
+            unsafe { TARGET = Some(target) };
+            true
+        }
+        None => false,
+    }
+}
+
+/// Forget what the screen shows, e.g. after
+/// C code drew on it. The next frame starts
+/// from a clear screen.
+pub fn invalidate() {
+    unsafe { CONTENT = None };
+}
+
+/// The screen was just cleared
+pub(crate) fn cleared() {
+    unsafe { CONTENT = Some(Elements::EMPTY) };
+}
+
+/// Whether the screen is known to be blank
+pub(crate) fn is_blank() -> bool {
+    matches!(unsafe { CONTENT }, Some(Elements { len: 0, .. }))
+}
+
+/// Something is painted: unless it is part
+/// of a frame, the content is unknown
+pub(crate) fn untracked() {
+    if unsafe { !IN_FRAME } {
+        invalidate();
+    }
+}
+
+/// The elements of a screen, drawn over
+/// what the previous frame left
+pub struct Frame {
+    /// Elements of the previous frame not
+    /// drawn again yet
+    old: Elements,
+    new: Elements,
+    /// More than `CANVAS_MAX_ELEMENTS` drawn
+    overflow: bool,
+}
+
+impl Frame {
+    pub fn begin() -> Frame {
+        let old = match unsafe { CONTENT } {
+            Some(old) => old,
+            None => {
+                crate::screen::clear();
+                Elements::EMPTY
+            }
+        };
+        unsafe { IN_FRAME = true };
+        Frame { old, new: Elements::EMPTY, overflow: false }
+    }
+
+    /// Draw `element` unless the previous frame
+    /// already did. Elements of the previous
+    /// frame under it are cleared first.
+    pub fn draw(&mut self, element: Bagl) {
+        let drawn = Drawn { key: key(&element), region: Region::of(&element) };
+        let mut repaint = !self.old.take(drawn.key);
+        let mut i = 0;
+        while i < self.old.len {
+            if self.old.items[i].region.intersects(&drawn.region) {
+                self.old.remove(i).region.clear();
+                repaint = true;
+            } else {
+                i += 1;
+            }
+        }
+        if repaint {
+            element.paint();
+        }
+        if !self.new.push(drawn) {
+            self.overflow = true;
+        }
+    }
+
+    /// Clear what the previous frame drew
+    /// and this one did not
+    pub fn end(self) {
+        for drawn in self.old.iter() {
+            drawn.region.clear();
+        }
+        unsafe {
+            IN_FRAME = false;
+            CONTENT = if self.overflow { None } else { Some(self.new) };
+        }
+    }
+}
+
+/// FNV-1a hash of what `element` draws
+fn key(element: &Bagl) -> u32 {
+    let mut hash = Hash(0x811c9dc5);
+    match element {
+        Bagl::LABELLINE(label) => {
+            hash.push(&[1, style::font(label.font_id) as u8, label.align as u8,
+                label.direction as u8, label.case as u8, label.inverted as u8]);
+            hash.region(label.pos, label.dims);
+            hash.push(label.text.unwrap_or("").as_bytes());
+        }
+        Bagl::RECT(rect) => {
+            hash.push(&[2, rect.fill as u8]);
+            hash.region(rect.pos, rect.dims);
+            hash.push(&rect.colors.0.to_le_bytes());
+            hash.push(&rect.colors.1.to_le_bytes());
+        }
+        Bagl::ICON(icon) => {
+            hash.push(&[3, style::glyph(icon.glyph_id)]);
+            hash.region(icon.pos, icon.dims);
+            let glyph = icon.glyph.map_or(0, |g| g as *const _ as usize);
+            hash.push(&glyph.to_le_bytes());
+        }
+    }
+    hash.0
+}
+
+struct Hash(u32);
+
+impl Hash {
+    fn push(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = (self.0 ^ *b as u32).wrapping_mul(0x01000193);
+        }
+    }
+
+    fn region(&mut self, pos: (i16, i16), dims: (u16, u16)) {
+        self.push(&pos.0.to_le_bytes());
+        self.push(&pos.1.to_le_bytes());
+        self.push(&dims.0.to_le_bytes());
+        self.push(&dims.1.to_le_bytes());
+    }
+}
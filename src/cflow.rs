@@ -0,0 +1,71 @@
+//! Hand the screen to a flow of the C SDK and
+//! get it back, for apps migrating from C one
+//! screen at a time. Enabled with the `c-flow`
+//! feature; the app must link the C SDK's UX
+//! library and provide `io_event`.
+//!
+//! The C flow's final callbacks end it by
+//! calling `nanos_ui_flow_done(result)`:
+//!
+//! ```ignore
+//! // C side
+//! UX_STEP_CB(ux_approve_step, pb, nanos_ui_flow_done(1), {&C_icon_validate, "Approve"});
+//! ```
+//! ```ignore
+//! // Rust side
+//! extern "C" { static ux_sign_flow: [*const c_void; 0]; }
+//! let approved = unsafe { cflow::run(ux_sign_flow.as_ptr()) } == 1;
+//! ```
+
+use core::ffi::c_void;
+
+use crate::context::UiContext;
+use crate::seph;
+
+/// Size of the C SDK's SEPH buffer
+const SPI_BUFFER_LEN: usize = 128;
+
+extern "C" {
+    fn ux_flow_init(stack_slot: u32, steps: *const *const c_void, start_step: *const c_void);
+    /// The app's SEPH event handler, which
+    /// dispatches events to the C UX
+    fn io_event(channel: u8) -> u8;
+    static mut G_io_seproxyhal_spi_buffer: [u8; SPI_BUFFER_LEN];
+}
+
+/// Result of the C flow once it ended
+static mut DONE: Option<u32> = None;
+
+/// Called by the C flow to end it
+#[no_mangle]
+pub extern "C" fn nanos_ui_flow_done(result: u32) {
+    unsafe { DONE = Some(result) }
+}
+
+/// Run the C flow `steps` (a `ux_flow_step_t`
+/// array) until it calls `nanos_ui_flow_done`,
+/// then redraw the widget owning the display.
+///
+/// # Safety
+/// `steps` must be a valid flow of the linked
+/// C SDK, and nothing else may read SEPH
+/// meanwhile.
+pub unsafe fn run(steps: *const *const c_void) -> u32 {
+    DONE = None;
+    ux_flow_init(0, steps, core::ptr::null());
+    let result = loop {
+        if let Some(result) = DONE {
+            break result
+        }
+        if !seph::is_status_sent() {
+            seph::send_general_status();
+        }
+        let buffer = &mut *core::ptr::addr_of_mut!(G_io_seproxyhal_spi_buffer);
+        seph::seph_recv(buffer, 0);
+        io_event(0);
+    };
+    if let Some(widget) = UiContext::current() {
+        widget.redraw();
+    }
+    result
+}
@@ -0,0 +1,110 @@
+//! Tracks which widget currently owns the
+//! display, so background code (timers, APDU
+//! handlers...) does not paint over an active
+//! flow, and can restore it after an interruption:
+//!
+//! ```ignore
+//! if !UiContext::paint(|| status.paint()) {
+//!     // A widget is shown, leave it alone
+//! }
+//! // After drawing something else anyway
+//! if let Some(w) = UiContext::current() {
+//!     w.redraw();
+//! }
+//! ```
+//!
+//! `widget::run` registers each widget for as
+//! long as it runs; nested runs stack.
+
+use crate::ui::WidgetState;
+use crate::widget::Widget;
+
+/// Type-erased handle to a running widget
+#[derive(Copy, Clone)]
+pub struct ActiveWidget {
+    widget: *const (),
+    draw: fn(*const ()),
+    state: fn(*const ()) -> WidgetState,
+}
+
+impl ActiveWidget {
+    fn new<W: Widget>(widget: &W) -> Self {
+        fn draw<W: Widget>(w: *const ()) {
+            unsafe { (*(w as *const W)).draw() }
+        }
+        fn state<W: Widget>(w: *const ()) -> WidgetState {
+            unsafe { (*(w as *const W)).state() }
+        }
+        ActiveWidget {
+            widget: widget as *const W as *const (),
+            draw: draw::<W>,
+            state: state::<W>,
+        }
+    }
+
+    /// Draw the whole screen of the widget again
+    pub fn redraw(&self) {
+        crate::canvas::invalidate();
+        UiContext::exclusive(|| (self.draw)(self.widget));
+    }
+
+    /// Navigation state of the widget
+    pub fn state(&self) -> WidgetState {
+        (self.state)(self.widget)
+    }
+}
+
+/// The display owner, a singleton
+/// accessed through associated functions
+pub struct UiContext {
+    owner: Option<ActiveWidget>,
+    drawing: bool,
+}
+
+static mut CONTEXT: UiContext = UiContext { owner: None, drawing: false };
+
+impl UiContext {
+    /// The widget owning the display, if any
+    pub fn current() -> Option<ActiveWidget> {
+        unsafe { CONTEXT.owner }
+    }
+
+    /// Whether no widget owns the display
+    pub fn is_free() -> bool {
+        Self::current().is_none()
+    }
+
+    /// Run `f` if no widget owns the display.
+    /// Returns whether it ran.
+    pub fn paint(f: impl FnOnce()) -> bool {
+        Self::is_free() && Self::exclusive(f)
+    }
+
+    /// Run `f` unless another draw is in
+    /// progress, so draws never interleave.
+    /// Returns whether it ran.
+    pub fn exclusive(f: impl FnOnce()) -> bool {
+        unsafe {
+            if CONTEXT.drawing {
+                return false
+            }
+            CONTEXT.drawing = true;
+        }
+        f();
+        unsafe { CONTEXT.drawing = false };
+        true
+    }
+
+    /// Make `widget` the display owner,
+    /// returning the previous one
+    pub(crate) fn enter<W: Widget>(widget: &W) -> Option<ActiveWidget> {
+        let previous = Self::current();
+        unsafe { CONTEXT.owner = Some(ActiveWidget::new(widget)) };
+        previous
+    }
+
+    /// Give the display back to `previous`
+    pub(crate) fn leave(previous: Option<ActiveWidget>) {
+        unsafe { CONTEXT.owner = previous };
+    }
+}
@@ -0,0 +1,112 @@
+//! Registry of the currencies an app displays,
+//! so that amounts are formatted by currency id
+//! with the same rules everywhere:
+//!
+//! ```ignore
+//! static CURRENCIES: [Currency; 2] = [
+//!     Currency::new(0, "BTC", 8),
+//!     Currency::new(1, "$", 2).placement(Placement::Prefix).grouping(Grouping::COMMA),
+//! ];
+//! currency::register(&CURRENCIES);
+//! let amount = currency::format_amount(1, &value.to_be_bytes(), &mut buf);
+//! ```
+
+use crate::fmt::{amount_from_digits_be, finish, Grouping};
+
+/// Where the ticker goes relative to the amount
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Placement {
+    /// "1.5 ETH"
+    Suffix,
+    /// "$1.5", with no space in between
+    Prefix,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Currency {
+    pub id: u32,
+    pub ticker: &'static str,
+    pub decimals: u8,
+    pub placement: Placement,
+    pub grouping: Grouping,
+}
+
+impl Currency {
+    pub const fn new(id: u32, ticker: &'static str, decimals: u8) -> Self {
+        Currency { id, ticker, decimals, placement: Placement::Suffix, grouping: Grouping::None }
+    }
+
+    pub const fn placement(self, placement: Placement) -> Self {
+        Currency { placement, ..self }
+    }
+
+    pub const fn grouping(self, grouping: Grouping) -> Self {
+        Currency { grouping, ..self }
+    }
+
+    /// Format the big-endian integer `value`
+    /// (at most 32 bytes) as an amount of this
+    /// currency, or `None` if `buf` is too small
+    pub fn format<'b>(&self, value: &[u8], buf: &'b mut [u8]) -> Option<&'b str> {
+        match self.placement {
+            Placement::Suffix => {
+                amount_from_digits_be(value, self.decimals, self.ticker, self.grouping, buf)
+            }
+            Placement::Prefix => {
+                let ticker = self.ticker.as_bytes();
+                buf.get_mut(..ticker.len())?.copy_from_slice(ticker);
+                let len = amount_from_digits_be(value, self.decimals, "", self.grouping,
+                    &mut buf[ticker.len()..])?.len();
+                Some(finish(&mut buf[..ticker.len() + len], 0))
+            }
+        }
+    }
+}
+
+static mut CURRENCIES: &[Currency] = &[];
+
+/// Set the currencies known to the app,
+/// replacing any previous registration
+pub fn register(currencies: &'static [Currency]) {
+    unsafe { CURRENCIES = currencies }
+}
+
+/// Registered currency with the given id
+pub fn get(id: u32) -> Option<&'static Currency> {
+    unsafe { CURRENCIES }.iter().find(|c| c.id == id)
+}
+
+/// Format `value` with the rules of currency `id`.
+/// Returns `None` if `id` is not registered or
+/// `buf` is too small.
+pub fn format_amount<'b>(id: u32, value: &[u8], buf: &'b mut [u8]) -> Option<&'b str> {
+    get(id)?.format(value, buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static REGISTRY: [Currency; 2] = [
+        Currency::new(0, "BTC", 8),
+        Currency::new(1, "$", 2).placement(Placement::Prefix).grouping(Grouping::COMMA),
+    ];
+
+    // A single test, as the registry is global
+    #[test]
+    fn registered_currencies() {
+        let mut buf = [0u8; 32];
+        let value = 123_456_789u64.to_be_bytes();
+        register(&REGISTRY);
+        assert_eq!(format_amount(0, &150_000u64.to_be_bytes(), &mut buf), Some("0.0015 BTC"));
+        assert_eq!(format_amount(0, &value, &mut buf), Some("1.23456789 BTC"));
+        assert_eq!(format_amount(1, &value, &mut buf[..13]), Some("$1,234,567.89"));
+        assert_eq!(format_amount(1, &value, &mut buf[..12]), None);
+        assert_eq!(format_amount(1, &[], &mut buf[..1]), None);
+        assert_eq!(format_amount(1, &[], &mut buf), Some("$0"));
+        assert_eq!(format_amount(0, &[0; 33], &mut buf), None);
+        assert_eq!(format_amount(2, &value, &mut buf), None);
+        register(&[]);
+        assert_eq!(get(0), None);
+    }
+}
@@ -0,0 +1,128 @@
+//! Post-mortem widget state, enabled with the
+//! `state-dump` feature.
+//!
+//! Widgets keep their last reported state and
+//! the last SEPH events received in a static
+//! buffer. When an assertion fails inside a
+//! widget, the location is saved as well, so the
+//! app can send everything back (e.g. in reply to
+//! a debug APDU) with `serialize`.
+
+use crate::ui::WidgetState;
+
+/// Number of SEPH event headers kept
+pub const EVENT_HISTORY: usize = 8;
+
+struct Dump {
+    state: Option<WidgetState>,
+    /// Ring buffer of event headers
+    events: [[u8; 4]; EVENT_HISTORY],
+    /// Total number of events recorded
+    recorded: usize,
+    /// Location of the failed assertion
+    failure: Option<(&'static str, u32)>,
+}
+
+static mut DUMP: Dump = Dump {
+    state: None,
+    events: [[0; 4]; EVENT_HISTORY],
+    recorded: 0,
+    failure: None,
+};
+
+fn dump() -> &'static mut Dump {
+    unsafe { &mut *core::ptr::addr_of_mut!(DUMP) }
+}
+
+pub(crate) fn record_state(state: WidgetState) {
+    dump().state = Some(state);
+}
+
+pub(crate) fn record_event(packet: &[u8]) {
+    let d = dump();
+    let slot = &mut d.events[d.recorded % EVENT_HISTORY];
+    let n = packet.len().min(slot.len());
+    *slot = [0; 4];
+    slot[..n].copy_from_slice(&packet[..n]);
+    d.recorded += 1;
+}
+
+/// Save the location of a failed assertion.
+/// Called by widgets right before panicking.
+pub fn capture(file: &'static str, line: u32) {
+    dump().failure = Some((file, line));
+}
+
+/// Location of the failed assertion, if any
+pub fn failure() -> Option<(&'static str, u32)> {
+    dump().failure
+}
+
+/// Last state reported by a widget
+pub fn last_state() -> Option<WidgetState> {
+    dump().state
+}
+
+/// Recorded event headers, oldest first
+pub fn events() -> impl Iterator<Item = [u8; 4]> {
+    let d = dump();
+    let count = d.recorded.min(EVENT_HISTORY);
+    let first = d.recorded - count;
+    (first..d.recorded).map(move |i| d.events[i % EVENT_HISTORY])
+}
+
+/// Forget everything recorded so far
+pub fn clear() {
+    let d = dump();
+    d.state = None;
+    d.recorded = 0;
+    d.failure = None;
+}
+
+/// Serialize the dump into `buf`, returning the
+/// number of bytes written. Fields are truncated
+/// if `buf` is too small. Layout:
+///
+/// - failure line, u32 big endian (0: no failure)
+/// - file name length (u8) and bytes
+/// - widget name length (u8, 0: no state) and bytes
+/// - index and count, u16 big endian
+/// - number of events (u8), then 4 bytes per event
+pub fn serialize(buf: &mut [u8]) -> usize {
+    let mut w = Cursor { buf, len: 0 };
+    let (file, line) = failure().unwrap_or(("", 0));
+    w.put(&line.to_be_bytes());
+    w.put_str(file);
+    match last_state() {
+        Some(s) => {
+            w.put_str(s.widget);
+            w.put(&(s.index as u16).to_be_bytes());
+            w.put(&(s.count as u16).to_be_bytes());
+        }
+        None => w.put(&[0, 0, 0, 0, 0]),
+    }
+    w.put(&[dump().recorded.min(EVENT_HISTORY) as u8]);
+    for e in events() {
+        w.put(&e);
+    }
+    w.len
+}
+
+struct Cursor<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl Cursor<'_> {
+    fn put(&mut self, bytes: &[u8]) {
+        let n = bytes.len().min(self.buf.len() - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&bytes[..n]);
+        self.len += n;
+    }
+
+    fn put_str(&mut self, s: &str) {
+        let bytes = &s.as_bytes()[..s.len().min(u8::MAX as usize)];
+        self.put(&[bytes.len() as u8]);
+        self.put(bytes);
+    }
+}
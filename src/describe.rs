@@ -0,0 +1,254 @@
+//! Machine-readable description of widgets,
+//! so external tooling (review, localization
+//! checks, UX linting) can inspect flows
+//! without running them.
+//!
+//! The output is JSON of the form:
+//! `{"flow":"Menu","pages":[{"icon":"CheckBadge","lines":["..."],"action":"select"}]}`
+//! where `icon` and `action` are optional.
+
+use core::fmt::{self, Write};
+
+use crate::bagls::*;
+use crate::flow::{Flow, Layout};
+use crate::text::PageBuffer;
+use crate::i18n::{tr, Text};
+use crate::ui::*;
+
+/// Widgets that can describe their pages
+pub trait Describe {
+    fn describe(&self, out: &mut dyn Write) -> fmt::Result;
+}
+
+/// Name of a built-in glyph, as used in descriptions
+pub fn icon_name(glyph_id: u8) -> &'static str {
+    match glyph_id {
+        6 => "Check",
+        7 => "Cross",
+        8 => "CheckBadge",
+        9 => "Left",
+        10 => "Right",
+        11 => "Up",
+        12 => "Down",
+        14 => "CrossBadge",
+        24 => "TransactionBadge",
+        27 => "EyeBadge",
+        _ => "Custom",
+    }
+}
+
+fn write_str(out: &mut dyn Write, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+/// Writes the `{"flow":..,"pages":[` .. `]}` envelope
+struct Pages<'w> {
+    out: &'w mut dyn Write,
+    first: bool,
+}
+
+impl<'w> Pages<'w> {
+    fn start(out: &'w mut dyn Write, flow: &str) -> Result<Self, fmt::Error> {
+        out.write_str("{\"flow\":")?;
+        write_str(out, flow)?;
+        out.write_str(",\"pages\":[")?;
+        Ok(Pages { out, first: true })
+    }
+
+    fn page(&mut self, icon: Option<u8>, lines: &[&str], action: Option<&str>) -> fmt::Result {
+        if !self.first {
+            self.out.write_char(',')?;
+        }
+        self.first = false;
+        self.out.write_char('{')?;
+        if let Some(id) = icon {
+            self.out.write_str("\"icon\":")?;
+            write_str(self.out, icon_name(id))?;
+            self.out.write_char(',')?;
+        }
+        self.out.write_str("\"lines\":[")?;
+        for (i, l) in lines.iter().enumerate() {
+            if i > 0 {
+                self.out.write_char(',')?;
+            }
+            write_str(self.out, l)?;
+        }
+        self.out.write_char(']')?;
+        if let Some(a) = action {
+            self.out.write_str(",\"action\":")?;
+            write_str(self.out, a)?;
+        }
+        self.out.write_char('}')
+    }
+
+    fn end(self) -> fmt::Result {
+        self.out.write_str("]}")
+    }
+}
+
+impl Describe for Validator<'_> {
+    fn describe(&self, out: &mut dyn Write) -> fmt::Result {
+        let mut p = Pages::start(out, "Validator")?;
+        p.page(None, &[self.message], Some("approve"))?;
+        p.page(None, &[tr(Text::Cancel)], Some("reject"))?;
+        p.end()
+    }
+}
+
+impl Describe for MessageValidator<'_> {
+    fn describe(&self, out: &mut dyn Write) -> fmt::Result {
+        let mut p = Pages::start(out, "MessageValidator")?;
+        for m in self.message.iter() {
+            p.page(None, &[m], None)?;
+        }
+        for page in self.pages.iter() {
+            p.page(page.icon.map(|i| i.glyph_id), page.lines, None)?;
+        }
+        if let Some((count, provider)) = self.provider {
+            for i in 0..count {
+                let mut buf = PageBuffer::new();
+                provider(i, &mut buf);
+                p.page(None, &buf.lines()[..buf.len()], None)?;
+            }
+        }
+        p.page(Some(Icons::CheckBadge as u8), self.confirm, Some("approve"))?;
+        p.page(Some(Icons::CrossBadge as u8), self.cancel, Some("reject"))?;
+        p.end()
+    }
+}
+
+impl Describe for Menu<'_> {
+    fn describe(&self, out: &mut dyn Write) -> fmt::Result {
+        let mut p = Pages::start(out, "Menu")?;
+        for i in 0..self.len() {
+            let entry = self.entry(i);
+            let icon = self.icons.get(i).copied().flatten().map(|icon| icon.glyph_id);
+            match self.values.get(i).filter(|v| !v.is_empty()) {
+                Some(value) => p.page(icon, &[entry, value], Some("select"))?,
+                None => p.page(icon, &[entry], Some("select"))?,
+            }
+        }
+        p.end()
+    }
+}
+
+impl Describe for SingleMessage<'_> {
+    fn describe(&self, out: &mut dyn Write) -> fmt::Result {
+        let mut p = Pages::start(out, "SingleMessage")?;
+        p.page(None, &[self.message], Some("dismiss"))?;
+        p.end()
+    }
+}
+
+impl Describe for ScrollingLabel<'_> {
+    fn describe(&self, out: &mut dyn Write) -> fmt::Result {
+        let mut p = Pages::start(out, "ScrollingLabel")?;
+        match self.title {
+            Some(title) => p.page(None, &[title, self.text], Some("dismiss"))?,
+            None => p.page(None, &[self.text], Some("dismiss"))?,
+        }
+        p.end()
+    }
+}
+
+impl Describe for AmountPage<'_> {
+    fn describe(&self, out: &mut dyn Write) -> fmt::Result {
+        let mut p = Pages::start(out, "AmountPage")?;
+        p.page(None, &[self.title, self.amount], Some("dismiss"))?;
+        p.end()
+    }
+}
+
+impl Describe for Warning<'_> {
+    fn describe(&self, out: &mut dyn Write) -> fmt::Result {
+        let mut p = Pages::start(out, "Warning")?;
+        if self.ack {
+            p.page(Some(self.icon.glyph_id), &self.lines, None)?;
+            p.page(Some(Icons::CheckBadge as u8), &[tr(Text::Understood)], Some("approve"))?;
+        } else {
+            p.page(Some(self.icon.glyph_id), &self.lines, Some("dismiss"))?;
+        }
+        p.end()
+    }
+}
+
+impl Describe for Flow<'_> {
+    fn describe(&self, out: &mut dyn Write) -> fmt::Result {
+        let mut p = Pages::start(out, "Flow")?;
+        for step in self.steps.iter() {
+            let action = Some("confirm").filter(|_| step.terminal);
+            if let Layout::Provided(index, provider) = step.layout {
+                let mut buf = PageBuffer::new();
+                provider(index, &mut buf);
+                p.page(None, &buf.lines()[..buf.len()], action)?;
+                continue
+            }
+            let (lines, n) = step.layout.lines();
+            p.page(step.layout.glyph(), &lines[..n], action)?;
+        }
+        p.end()
+    }
+}
+
+impl Describe for MessageScroller<'_> {
+    fn describe(&self, out: &mut dyn Write) -> fmt::Result {
+        let mut p = Pages::start(out, "MessageScroller")?;
+        if let Some((count, provider)) = self.provider {
+            for i in 0..count {
+                let mut buf = PageBuffer::new();
+                provider(i, &mut buf);
+                p.page(None, &buf.lines()[..buf.len()], Some("exit"))?;
+            }
+        }
+        for chunk in self.pages() {
+            if self.lines == 0 {
+                p.page(None, &[chunk], Some("exit"))?;
+                continue
+            }
+            let mut lines = [""; SCROLLER_MAX_LINES];
+            let wrapped = wrap(chunk, self.layout.chars_per_page);
+            let count = lines.iter_mut().zip(wrapped).map(|(slot, line)| *slot = line).count();
+            p.page(None, &lines[..count], Some("exit"))?;
+        }
+        p.end()
+    }
+}
+
+impl Describe for HScroller<'_> {
+    fn describe(&self, out: &mut dyn Write) -> fmt::Result {
+        let mut p = Pages::start(out, "HScroller")?;
+        for screen in self.screens.iter() {
+            match screen {
+                Bagl::LABELLINE(l) => p.page(None, &[l.text.unwrap_or("")], Some("exit"))?,
+                Bagl::ICON(i) => p.page(Some(i.glyph_id), &[], Some("exit"))?,
+                Bagl::RECT(_) => p.page(None, &[], Some("exit"))?,
+            }
+        }
+        for screen in self.composite.iter() {
+            let mut lines = [""; PAGE_MAX_LINES];
+            let labels = screen.iter().filter_map(|b| match b {
+                Bagl::LABELLINE(l) => l.text,
+                _ => None,
+            });
+            let count = lines.iter_mut().zip(labels).map(|(slot, line)| *slot = line).count();
+            let icon = screen.iter().find_map(|b| match b {
+                Bagl::ICON(i) => Some(i.glyph_id),
+                _ => None,
+            });
+            p.page(icon, &lines[..count], Some("exit"))?;
+        }
+        for page in self.pages.iter() {
+            p.page(page.icon.map(|i| i.glyph_id), page.lines, Some("exit"))?;
+        }
+        p.end()
+    }
+}
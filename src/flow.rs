@@ -0,0 +1,558 @@
+//! Flows in the style of the C SDK's `UX_FLOW`:
+//! a sequence of steps, each drawn with one of
+//! the usual layouts, browsed with the left and
+//! right buttons. Confirming a step that has a
+//! callback runs it and ends the flow.
+//!
+//! ```ignore
+//! ux_flow!(REVIEW, [
+//!     pnn(Icons::EyeBadge, "Review", "transaction"),
+//!     bn("Network", "Mainnet"),
+//!     confirm("Approve") => on_approve,
+//!     reject("Reject") => on_reject,
+//! ]);
+//!
+//! REVIEW.run();
+//! ```
+//!
+//! Steps can also be declared without callbacks,
+//! the flow returning the terminal step chosen:
+//!
+//! ```ignore
+//! let steps = [
+//!     Step::icon_text(Icons::EyeBadge, "Review", "transaction"),
+//!     Step::review_field("Amount", amount),
+//!     Step::confirm("Approve"),
+//!     Step::reject("Reject"),
+//! ];
+//! let approved = Flow::new(&steps).wrap().run() == 2;
+//! ```
+
+use crate::bagls::*;
+use crate::batch::Batch;
+use crate::input::Action;
+use crate::pin;
+use crate::screen;
+use crate::style::{self, BOTTOM_LINE_Y};
+use crate::text::{PageBuffer, PageProvider, PageText};
+use crate::ui::{Actions, Error, WidgetState};
+use crate::widget::{self, Event, Widget};
+
+/// Screen layouts, named after the C SDK's:
+/// `p` is an icon, `b` a bold line and `n`
+/// a normal line
+#[derive(Copy, Clone)]
+pub enum Layout<'a> {
+    /// Bold line over a normal line
+    Bn(&'a str, &'a str),
+    /// Two normal lines
+    Nn(&'a str, &'a str),
+    /// Icon and a bold line
+    Pb(Icon, &'a str),
+    /// Icon and two normal lines
+    Pnn(Icon, &'a str, &'a str),
+    /// Icon, bold line and normal line
+    Pbn(Icon, &'a str, &'a str),
+    /// Bold line over a normal line, page
+    /// `index` of the provider, formatted
+    /// only when drawn
+    Provided(usize, PageProvider<'a>),
+}
+
+impl<'a> Layout<'a> {
+    pub fn draw(&self) {
+        let top = LabelLine::new().dims(128, 11).pos(0, style::get().top_line_y());
+        let bot = LabelLine::new().dims(128, 11).pos(0, BOTTOM_LINE_Y);
+        match *self {
+            Layout::Bn(a, b) => {
+                top.text(a).bold().display();
+                bot.text(b).paint();
+            }
+            Layout::Nn(a, b) => {
+                top.text(a).display();
+                bot.text(b).paint();
+            }
+            Layout::Pb(icon, a) => {
+                let icon = icon.pos(16, 12);
+                icon.display();
+                LabelLine::new().dims(128, 11).pos(0, 20).beside(&icon).text(a).bold().paint();
+            }
+            Layout::Pnn(icon, a, b) => {
+                let icon = icon.pos(16, 12);
+                icon.display();
+                top.beside(&icon).text(a).paint();
+                bot.beside(&icon).text(b).paint();
+            }
+            Layout::Pbn(icon, a, b) => {
+                let icon = icon.pos(16, 12);
+                icon.display();
+                top.beside(&icon).text(a).bold().paint();
+                bot.beside(&icon).text(b).paint();
+            }
+            Layout::Provided(index, provider) => {
+                let mut buf = PageBuffer::new();
+                provider(index, &mut buf);
+                let [a, b] = buf.lines();
+                top.text(a).bold().display();
+                bot.text(b).paint();
+            }
+        }
+    }
+
+    /// Glyph shown by the layout, if any
+    pub fn glyph(&self) -> Option<u8> {
+        match self {
+            Layout::Pb(i, _) | Layout::Pnn(i, _, _) | Layout::Pbn(i, _, _) => Some(i.glyph_id),
+            _ => None,
+        }
+    }
+
+    /// Lines of text, in display order,
+    /// and how many there are
+    pub(crate) fn lines(&self) -> ([&'a str; 2], usize) {
+        match *self {
+            Layout::Pb(_, a) => ([a, ""], 1),
+            Layout::Bn(a, b) | Layout::Nn(a, b) => ([a, b], 2),
+            Layout::Pnn(_, a, b) | Layout::Pbn(_, a, b) => ([a, b], 2),
+            Layout::Provided(..) => (["", ""], 0),
+        }
+    }
+}
+
+/// Constructors used by `ux_flow!`, named
+/// after the layouts
+pub mod layouts {
+    use super::Layout;
+    use crate::bagls::{Icon, Icons};
+
+    pub const fn bn<'a>(a: &'a str, b: &'a str) -> Layout<'a> {
+        Layout::Bn(a, b)
+    }
+    pub const fn nn<'a>(a: &'a str, b: &'a str) -> Layout<'a> {
+        Layout::Nn(a, b)
+    }
+    pub const fn pb(icon: Icons, a: &str) -> Layout<'_> {
+        Layout::Pb(Icon::new(icon), a)
+    }
+    pub const fn pnn<'a>(icon: Icons, a: &'a str, b: &'a str) -> Layout<'a> {
+        Layout::Pnn(Icon::new(icon), a, b)
+    }
+    pub const fn pbn<'a>(icon: Icons, a: &'a str, b: &'a str) -> Layout<'a> {
+        Layout::Pbn(Icon::new(icon), a, b)
+    }
+    /// Approval step: check icon and bold text
+    pub const fn confirm(a: &str) -> Layout<'_> {
+        Layout::Pb(Icon::new(Icons::CheckBadge), a)
+    }
+    /// Rejection step: cross icon and bold text
+    pub const fn reject(a: &str) -> Layout<'_> {
+        Layout::Pb(Icon::new(Icons::CrossBadge), a)
+    }
+}
+
+/// Step of a flow: a screen, and what
+/// entering and confirming it does
+#[derive(Copy, Clone)]
+pub struct Step<'a> {
+    pub layout: Layout<'a>,
+    pub callback: Option<fn()>,
+    pub enter: Option<fn()>,
+    /// Ask for the device PIN before showing the step
+    pub pin: bool,
+    /// Confirming the step ends the flow
+    pub terminal: bool,
+}
+
+impl<'a> Step<'a> {
+    pub const fn new(layout: Layout<'a>) -> Self {
+        Step { layout, callback: None, enter: None, pin: false, terminal: false }
+    }
+
+    /// Two normal lines
+    pub const fn text(line1: &'a str, line2: &'a str) -> Self {
+        Step::new(Layout::Nn(line1, line2))
+    }
+
+    /// Icon and two normal lines
+    pub const fn icon_text(icon: Icons, line1: &'a str, line2: &'a str) -> Self {
+        Step::new(layouts::pnn(icon, line1, line2))
+    }
+
+    /// Field name in bold over its value
+    pub const fn review_field(name: &'a str, value: &'a str) -> Self {
+        Step::new(Layout::Bn(name, value))
+    }
+
+    /// Page `index` of `provider`, formatted
+    /// only when shown, e.g. one step per
+    /// transaction output
+    pub const fn provided(index: usize, provider: PageProvider<'a>) -> Self {
+        Step::new(Layout::Provided(index, provider))
+    }
+
+    /// Terminal step approving the flow
+    pub const fn confirm(label: &'a str) -> Self {
+        Step { terminal: true, ..Step::new(layouts::confirm(label)) }
+    }
+
+    /// Terminal step rejecting the flow
+    pub const fn reject(label: &'a str) -> Self {
+        Step { terminal: true, ..Step::new(layouts::reject(label)) }
+    }
+
+    /// Ask for the device PIN before the step is
+    /// shown, once per run of the flow. See `pin`.
+    pub const fn require_pin(self) -> Self {
+        Step { pin: true, ..self }
+    }
+
+    /// Run `enter` each time the step is shown
+    pub const fn on_enter(self, enter: fn()) -> Self {
+        Step { enter: Some(enter), ..self }
+    }
+
+    /// Run `callback` and end the flow when
+    /// the step is confirmed
+    pub const fn on_confirm(self, callback: fn()) -> Self {
+        Step { callback: Some(callback), terminal: true, ..self }
+    }
+}
+
+/// Position in a sequence of pages, moved by
+/// the Previous and Next actions: the paging
+/// of flows and of the paged widgets
+/// Buffer of the text of a page indicator
+pub const INDICATOR_LEN: usize = 8;
+/// Width kept for the page indicator
+pub const INDICATOR_WIDTH: u16 = 24;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Pager {
+    pub index: usize,
+    pub count: usize,
+    /// Go from the last page to the
+    /// first one, and back
+    pub wrap: bool,
+}
+
+impl Pager {
+    pub const fn new(index: usize, count: usize) -> Self {
+        Pager { index, count, wrap: false }
+    }
+
+    pub const fn wrap(self, wrap: bool) -> Self {
+        Pager { wrap, ..self }
+    }
+
+    pub const fn has_previous(&self) -> bool {
+        self.index > 0 || (self.wrap && self.count > 1)
+    }
+
+    pub const fn has_next(&self) -> bool {
+        self.index + 1 < self.count || (self.wrap && self.count > 1)
+    }
+
+    /// Page shown after `action`, if it
+    /// moves to another page
+    pub const fn turn(&self, action: Action) -> Option<usize> {
+        match action {
+            Action::Previous if self.index > 0 => Some(self.index - 1),
+            Action::Previous if self.has_previous() => Some(self.count - 1),
+            Action::Next if self.index + 1 < self.count => Some(self.index + 1),
+            Action::Next if self.has_next() => Some(0),
+            _ => None,
+        }
+    }
+
+    /// Paint the arrows of the pages
+    /// before and after this one
+    pub fn paint_arrows(&self) {
+        let mut batch = Batch::new();
+        self.queue_arrows(&mut batch);
+        batch.flush();
+    }
+
+    /// Queue the arrows in `batch`
+    pub fn queue_arrows(&self, batch: &mut Batch) {
+        let screen = screen::current();
+        if self.has_previous() {
+            batch.push(Bagl::ICON(screen.place(LEFT_ARROW)));
+        }
+        if self.has_next() {
+            batch.push(Bagl::ICON(screen.place(RIGHT_ARROW)));
+        }
+    }
+
+    /// Queue the "k/n" page indicator, in the
+    /// small font. It sits in the top right
+    /// corner, over the end of long first lines,
+    /// and below the layout on 64 pixel high
+    /// screens. Nothing for a single page.
+    pub fn queue_indicator<'b>(&self, batch: &mut Batch<'b>, buf: &'b mut PageText<INDICATOR_LEN>) {
+        buf.clear();
+        if self.count < 2 || !buf.push_counter(self.index, self.count) {
+            return
+        }
+        let text = buf.as_str();
+        let screen = screen::current();
+        let y = if screen.y_offset() > 0 { screen.height as i16 - 2 } else { 8 };
+        let x = screen.width as i16 - INDICATOR_WIDTH as i16 - 1;
+        batch.push(Bagl::LABELLINE(LabelLine::new().text(text).font(Font::SMALL).pos(x, y)
+            .dims(INDICATOR_WIDTH, 8).align(Alignment::Right)));
+    }
+
+    /// Navigation actions accepted
+    pub const fn actions(&self) -> Actions {
+        Actions::PREVIOUS.when(self.has_previous()).with(Actions::NEXT.when(self.has_next()))
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct Flow<'a> {
+    pub(crate) steps: &'a [Step<'a>],
+    /// Step currently shown
+    pub(crate) index: usize,
+    /// Whether the PIN was entered during this run
+    pub(crate) unlocked: bool,
+    /// Go from the last step to the first one
+    pub(crate) wrap: bool,
+    /// Show the "k/n" page indicator
+    pub(crate) indicator: bool,
+}
+
+impl<'a> Flow<'a> {
+    pub const fn new(steps: &'a [Step<'a>]) -> Self {
+        Flow { steps, index: 0, unlocked: false, wrap: false, indicator: false }
+    }
+
+    /// Go from the last step to the
+    /// first one, and back
+    pub const fn wrap(self) -> Self {
+        Flow { wrap: true, ..self }
+    }
+
+    /// Show the number of the step in
+    /// a corner, see `Pager::queue_indicator`
+    pub const fn page_indicator(self) -> Self {
+        Flow { indicator: true, ..self }
+    }
+
+    fn pager(&self) -> Pager {
+        Pager::new(self.index, self.steps.len()).wrap(self.wrap)
+    }
+
+    pub fn try_new(steps: &'a [Step<'a>]) -> Result<Self, Error> {
+        match steps.first() {
+            None => Err(Error::Empty),
+            Some(first) if first.pin => Err(Error::PinFirst),
+            Some(_) => Ok(Self::new(steps)),
+        }
+    }
+
+    /// Show the flow until a terminal step is
+    /// confirmed. Its callback, if any, is run,
+    /// and the index of the step returned.
+    pub fn run(&self) -> usize {
+        widget_debug_assert!(!self.steps.is_empty(), "Flow needs at least one step");
+        widget_debug_assert!(!self.steps[0].pin, "the first step of a flow cannot require the PIN");
+        widget::run(&mut { *self })
+    }
+
+    /// `run`, awaited instead of
+    /// blocking, see `future`
+    pub async fn run_async(&self) -> usize {
+        widget_debug_assert!(!self.steps.is_empty(), "Flow needs at least one step");
+        widget_debug_assert!(!self.steps[0].pin, "the first step of a flow cannot require the PIN");
+        crate::future::run_async(&mut { *self }).await
+    }
+
+    /// Move to step `index`, unless it requires
+    /// the PIN and the user does not enter it
+    fn go(&mut self, index: usize) {
+        if self.steps[index].pin && !self.unlocked {
+            self.unlocked = pin::validate();
+            if !self.unlocked {
+                // The PIN screen drew over the flow
+                return self.draw()
+            }
+        }
+        self.index = index;
+        self.draw();
+    }
+}
+
+impl Widget for Flow<'_> {
+    type Output = usize;
+
+    fn draw(&self) {
+        let step = &self.steps[self.index];
+        if let Some(f) = step.enter {
+            f();
+        }
+        step.layout.draw();
+        let mut buf = PageText::new();
+        let mut batch = Batch::new();
+        self.pager().queue_arrows(&mut batch);
+        if self.indicator {
+            self.pager().queue_indicator(&mut batch, &mut buf);
+        }
+        batch.flush();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<usize> {
+        let action = event.action()?;
+        if let Some(index) = self.pager().turn(action) {
+            self.go(index);
+        } else if action == Action::Confirm && self.steps[self.index].terminal {
+            if let Some(f) = self.steps[self.index].callback {
+                f();
+            }
+            return Some(self.index)
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        let confirmable = self.steps[self.index].terminal;
+        WidgetState::new("Flow", self.index, self.steps.len())
+            .actions(self.pager().actions().with(Actions::CONFIRM.when(confirmable)))
+    }
+}
+
+/// Builds a flow of at most `N` steps at
+/// runtime, for flows whose steps depend on
+/// data, e.g. one step per transaction output:
+///
+/// ```ignore
+/// let mut builder = FlowBuilder::<8>::new();
+/// for output in outputs {
+///     builder.push(Step::new(layouts::bn("Output", output)));
+/// }
+/// builder.push(Step::new(layouts::confirm("Approve")).on_confirm(approve));
+/// builder.flow().run();
+/// ```
+pub struct FlowBuilder<'a, const N: usize> {
+    steps: [Step<'a>; N],
+    len: usize,
+}
+
+impl<'a, const N: usize> FlowBuilder<'a, N> {
+    pub const fn new() -> Self {
+        FlowBuilder { steps: [Step::new(Layout::Nn("", "")); N], len: 0 }
+    }
+
+    /// Append a step. Returns `false`, leaving
+    /// the flow unchanged, if it is full.
+    pub fn push(&mut self, step: Step<'a>) -> bool {
+        match self.steps.get_mut(self.len) {
+            Some(slot) => {
+                *slot = step;
+                self.len += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Chainable form of `push`
+    pub fn step(mut self, step: Step<'a>) -> Self {
+        let added = self.push(step);
+        widget_debug_assert!(added, "FlowBuilder is full");
+        self
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The steps added so far, as a flow
+    pub fn flow(&self) -> Flow<'_> {
+        Flow::new(&self.steps[..self.len])
+    }
+}
+
+impl<const N: usize> Default for FlowBuilder<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Declare a `static` flow from a list of steps,
+/// each a layout from `flow::layouts` optionally
+/// followed by `=> callback`:
+///
+/// ```ignore
+/// ux_flow!(SIGN, [
+///     bn("Amount", "0.1 BTC"),
+///     confirm("Approve") => approve,
+///     reject("Reject") => reject,
+/// ]);
+/// ```
+///
+/// Or build the steps of a flow from named
+/// steps, their values known at runtime:
+///
+/// ```ignore
+/// let steps = ux_flow! {
+///     step intro { icon: EyeBadge, text: "Review", "transaction" }
+///     step fee { title: "Fee", value: fee_str }
+///     accept
+///     reject
+/// };
+/// let approved = Flow::new(&steps).run() == 2;
+/// ```
+///
+/// A step is `text: a, b`, `title: t, value: v`,
+/// `icon: I, text: a, b` or `icon: I, title: t,
+/// text: a`, `I` naming one of `Icons`. The
+/// names are for the reader. `accept` and
+/// `reject` take an optional label, e.g.
+/// `accept("Sign")`.
+#[macro_export]
+macro_rules! ux_flow {
+    ($name:ident, [$($layout:ident ($($arg:expr),* $(,)?) $(=> $cb:expr)?),+ $(,)?]) => {
+        static $name: $crate::flow::Flow<'static> = $crate::flow::Flow::new(&[
+            $($crate::flow::Step::new($crate::flow::layouts::$layout($($arg),*))
+                $(.on_confirm($cb))?),+
+        ]);
+    };
+    (@steps [$($done:expr,)*]) => {
+        [$($done),*]
+    };
+    (@steps [$($done:expr,)*] step $step:ident { $($body:tt)* } $($rest:tt)*) => {
+        $crate::ux_flow!(@steps [$($done,)* $crate::ux_flow!(@step $($body)*),] $($rest)*)
+    };
+    (@steps [$($done:expr,)*] accept ($label:expr) $($rest:tt)*) => {
+        $crate::ux_flow!(@steps [$($done,)* $crate::flow::Step::confirm($label),] $($rest)*)
+    };
+    (@steps [$($done:expr,)*] accept $($rest:tt)*) => {
+        $crate::ux_flow!(@steps [$($done,)*
+            $crate::flow::Step::confirm($crate::i18n::tr($crate::i18n::Text::Approve)),] $($rest)*)
+    };
+    (@steps [$($done:expr,)*] reject ($label:expr) $($rest:tt)*) => {
+        $crate::ux_flow!(@steps [$($done,)* $crate::flow::Step::reject($label),] $($rest)*)
+    };
+    (@steps [$($done:expr,)*] reject $($rest:tt)*) => {
+        $crate::ux_flow!(@steps [$($done,)*
+            $crate::flow::Step::reject($crate::i18n::tr($crate::i18n::Text::Reject)),] $($rest)*)
+    };
+    (@step text: $a:expr, $b:expr $(,)?) => {
+        $crate::flow::Step::text($a, $b)
+    };
+    (@step title: $title:expr, value: $value:expr $(,)?) => {
+        $crate::flow::Step::review_field($title, $value)
+    };
+    (@step icon: $icon:ident, text: $a:expr, $b:expr $(,)?) => {
+        $crate::flow::Step::icon_text($crate::bagls::Icons::$icon, $a, $b)
+    };
+    (@step icon: $icon:ident, title: $title:expr, text: $a:expr $(,)?) => {
+        $crate::flow::Step::new($crate::flow::layouts::pbn($crate::bagls::Icons::$icon, $title, $a))
+    };
+    ($($steps:tt)+) => {
+        $crate::ux_flow!(@steps [] $($steps)+)
+    };
+}
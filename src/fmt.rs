@@ -0,0 +1,1106 @@
+//! Lightweight formatting of values into
+//! caller-provided buffers, without going
+//! through `core::fmt`.
+//!
+//! Helpers return the formatted `&str`, or
+//! `None` if `buf` is too small.
+
+/// Longest decimal representation of a `u32`
+pub const U32_DIGITS: usize = 10;
+/// Longest decimal representation of a `u64`
+pub const U64_DIGITS: usize = 20;
+
+/// Write `n` in decimal at the end of `buf`,
+/// returning the index of the first digit
+fn write_digits(mut n: u128, buf: &mut [u8]) -> Option<usize> {
+    let mut i = buf.len();
+    loop {
+        if i == 0 {
+            return None
+        }
+        i -= 1;
+        buf[i] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            return Some(i)
+        }
+    }
+}
+
+/// Move `buf[start..]` to the front of `buf`
+/// and return it as a `&str`
+pub(crate) fn finish(buf: &mut [u8], start: usize) -> &str {
+    let len = buf.len() - start;
+    buf.copy_within(start.., 0);
+    // Only ASCII or whole UTF-8 sequences are written
+    unsafe { core::str::from_utf8_unchecked(&buf[..len]) }
+}
+
+pub fn u64_to_str(n: u64, buf: &mut [u8]) -> Option<&str> {
+    let start = write_digits(n as u128, buf)?;
+    Some(finish(buf, start))
+}
+
+pub fn u32_to_str(n: u32, buf: &mut [u8]) -> Option<&str> {
+    u64_to_str(n as u64, buf)
+}
+
+/// Longest decimal representation of an `i64`
+pub const I64_DIGITS: usize = 20;
+
+pub fn i64_to_str(n: i64, buf: &mut [u8]) -> Option<&str> {
+    // unsigned_abs avoids overflowing on i64::MIN
+    let mut start = write_digits(n.unsigned_abs() as u128, buf)?;
+    if n < 0 {
+        if start == 0 {
+            return None
+        }
+        start -= 1;
+        buf[start] = b'-';
+    }
+    Some(finish(buf, start))
+}
+
+pub fn i32_to_str(n: i32, buf: &mut [u8]) -> Option<&str> {
+    i64_to_str(n as i64, buf)
+}
+
+/// Words used to display booleans,
+/// e.g. to translate them
+#[derive(Copy, Clone, Debug)]
+pub struct BoolLabels {
+    pub yes: &'static str,
+    pub no: &'static str,
+}
+
+impl BoolLabels {
+    pub const fn new(yes: &'static str, no: &'static str) -> Self {
+        BoolLabels { yes, no }
+    }
+
+    pub const fn get(&self, b: bool) -> &'static str {
+        if b { self.yes } else { self.no }
+    }
+}
+
+pub const YES_NO: BoolLabels = BoolLabels::new("Yes", "No");
+pub const ON_OFF: BoolLabels = BoolLabels::new("On", "Off");
+
+/// "Yes" or "No"
+pub const fn bool_to_str(b: bool) -> &'static str {
+    YES_NO.get(b)
+}
+
+/// "`page`/`count`", with `page` starting at 1
+pub fn page_counter(page: usize, count: usize, buf: &mut [u8]) -> Option<&str> {
+    let mut i = write_digits(count as u128, buf)?;
+    if i == 0 {
+        return None
+    }
+    i -= 1;
+    buf[i] = b'/';
+    let start = write_digits(page as u128, &mut buf[..i])?;
+    Some(finish(buf, start))
+}
+
+/// Appends to a buffer, failing once it is full
+pub(crate) struct Cursor<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl<'b> Cursor<'b> {
+    pub(crate) fn new(buf: &'b mut [u8]) -> Self {
+        Cursor { buf, len: 0 }
+    }
+
+    pub(crate) fn push(&mut self, bytes: &[u8]) -> Option<()> {
+        let end = self.len + bytes.len();
+        self.buf.get_mut(self.len..end)?.copy_from_slice(bytes);
+        self.len = end;
+        Some(())
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        // Only ASCII or whole UTF-8 sequences are written
+        unsafe { core::str::from_utf8_unchecked(&self.buf[..self.len]) }
+    }
+
+    pub(crate) fn into_str(self) -> &'b str {
+        // Only ASCII or whole UTF-8 sequences are written
+        unsafe { core::str::from_utf8_unchecked(&self.buf[..self.len]) }
+    }
+}
+
+/// Separator inserted between groups of
+/// three integer digits
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Grouping {
+    None,
+    With(char),
+}
+
+impl Grouping {
+    /// "1 234 567"
+    pub const SPACE: Grouping = Grouping::With(' ');
+    /// "1,234,567"
+    pub const COMMA: Grouping = Grouping::With(',');
+}
+
+/// `u64_to_str` with digits grouped by thousands
+pub fn u64_to_str_grouped(n: u64, grouping: Grouping, buf: &mut [u8]) -> Option<&str> {
+    let mut digits = [0u8; U64_DIGITS];
+    let start = write_digits(n as u128, &mut digits)?;
+    amount_from_digits(&digits[start..], 0, "", grouping, buf)
+}
+
+/// `i64_to_str` with digits grouped by
+/// thousands: "-1,234,567"
+pub fn i64_to_str_grouped(n: i64, grouping: Grouping, buf: &mut [u8]) -> Option<&str> {
+    if n >= 0 {
+        return u64_to_str_grouped(n as u64, grouping, buf)
+    }
+    *buf.first_mut()? = b'-';
+    let len = u64_to_str_grouped(n.unsigned_abs(), grouping, &mut buf[1..])?.len();
+    Some(finish(&mut buf[..len + 1], 0))
+}
+
+/// Longest decimal representation of a `u128`
+const U128_DIGITS: usize = 39;
+
+/// Format `value` as a fixed-point amount with
+/// `decimals` decimal places, followed by
+/// `ticker` if not empty:
+/// `format_amount(150000, 8, "BTC", buf)` gives
+/// "0.0015 BTC". Trailing zeros of the
+/// fractional part are trimmed.
+pub fn format_amount<'b>(value: u64, decimals: u8, ticker: &str,
+    buf: &'b mut [u8]) -> Option<&'b str> {
+    format_amount_u128(value as u128, decimals, ticker, buf)
+}
+
+/// `format_amount` with integer digits
+/// grouped by thousands
+pub fn format_amount_grouped<'b>(value: u128, decimals: u8, ticker: &str,
+    grouping: Grouping, buf: &'b mut [u8]) -> Option<&'b str> {
+    let mut digits = [0u8; U128_DIGITS];
+    let start = write_digits(value, &mut digits)?;
+    amount_from_digits(&digits[start..], decimals, ticker, grouping, buf)
+}
+
+/// `format_amount` for `u128` values
+pub fn format_amount_u128<'b>(value: u128, decimals: u8, ticker: &str,
+    buf: &'b mut [u8]) -> Option<&'b str> {
+    let mut digits = [0u8; U128_DIGITS];
+    let start = write_digits(value, &mut digits)?;
+    amount_from_digits(&digits[start..], decimals, ticker, Grouping::None, buf)
+}
+
+/// Place the decimal point in a string of
+/// `digits` without leading zeros ("0" for zero)
+pub(crate) fn amount_from_digits<'b>(digits: &[u8], decimals: u8, ticker: &str,
+    grouping: Grouping, buf: &'b mut [u8]) -> Option<&'b str> {
+    let decimals = decimals as usize;
+    let int_len = digits.len().saturating_sub(decimals);
+    let mut out = Cursor::new(buf);
+
+    if int_len == 0 {
+        out.push(b"0")?;
+    } else {
+        let mut sep = [0u8; 4];
+        let sep = match grouping {
+            Grouping::None => &[][..],
+            Grouping::With(c) => c.encode_utf8(&mut sep).as_bytes(),
+        };
+        for (i, d) in digits[..int_len].iter().enumerate() {
+            if i > 0 && (int_len - i).is_multiple_of(3) {
+                out.push(sep)?;
+            }
+            out.push(&[*d])?;
+        }
+    }
+
+    // Fractional digits, left-padded with zeros
+    // when the value is below 1
+    let frac = &digits[int_len..];
+    let padding = decimals - frac.len();
+    let frac_len = frac.iter().rposition(|d| *d != b'0').map_or(0, |i| i + 1);
+    if frac_len > 0 {
+        out.push(b".")?;
+        for _ in 0..padding {
+            out.push(b"0")?;
+        }
+        out.push(&frac[..frac_len])?;
+    }
+
+    if !ticker.is_empty() {
+        out.push(b" ")?;
+        out.push(ticker.as_bytes())?;
+    }
+    Some(out.into_str())
+}
+
+/// Longest decimal representation of a
+/// 256-bit integer
+pub const U256_DIGITS: usize = 78;
+
+/// Write the big-endian integer `bytes` (at
+/// most 32 of them) in decimal at the end of
+/// `buf`, returning the index of the first digit
+fn write_digits_be(bytes: &[u8], buf: &mut [u8]) -> Option<usize> {
+    let mut num = [0u8; 32];
+    let offset = num.len().checked_sub(bytes.len())?;
+    num[offset..].copy_from_slice(bytes);
+
+    // Long division by 10, skipping the leading
+    // bytes that already reached zero
+    let mut first = num.iter().position(|b| *b != 0).unwrap_or(num.len());
+    let mut i = buf.len();
+    loop {
+        if i == 0 {
+            return None
+        }
+        let mut rem = 0u16;
+        for b in num[first..].iter_mut() {
+            let cur = (rem << 8) | *b as u16;
+            *b = (cur / 10) as u8;
+            rem = cur % 10;
+        }
+        i -= 1;
+        buf[i] = b'0' + rem as u8;
+        while first < num.len() && num[first] == 0 {
+            first += 1;
+        }
+        if first == num.len() {
+            return Some(i)
+        }
+    }
+}
+
+/// Decimal representation of a big-endian
+/// unsigned integer of up to 32 bytes, e.g.
+/// an Ethereum `uint256`
+pub fn be_bytes_to_str<'b>(bytes: &[u8], buf: &'b mut [u8]) -> Option<&'b str> {
+    let start = write_digits_be(bytes, buf)?;
+    Some(finish(buf, start))
+}
+
+/// `format_amount_grouped` for a big-endian
+/// integer of up to 32 bytes
+pub fn format_amount_bytes<'b>(bytes: &[u8], decimals: u8, ticker: &str,
+    grouping: Grouping, buf: &'b mut [u8]) -> Option<&'b str> {
+    amount_from_digits_be(bytes, decimals, ticker, grouping, buf)
+}
+
+pub(crate) fn amount_from_digits_be<'b>(bytes: &[u8], decimals: u8, ticker: &str,
+    grouping: Grouping, buf: &'b mut [u8]) -> Option<&'b str> {
+    let mut digits = [0u8; U256_DIGITS];
+    let start = write_digits_be(bytes, &mut digits)?;
+    amount_from_digits(&digits[start..], decimals, ticker, grouping, buf)
+}
+
+/// Fallback used by `format_amount_fit` when an
+/// amount is wider than the space available
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Abbreviation {
+    /// Fail rather than show an approximation
+    None,
+    /// "~1.234e21 ETH"
+    Scientific,
+    /// "~1.23M ETH", for values of at least a thousand.
+    /// Other values use the scientific notation.
+    Suffix,
+}
+
+/// Prefix marking an abbreviated, hence
+/// approximate, amount
+pub const APPROX_MARK: &str = "~";
+
+/// Digits kept after the point of an
+/// abbreviated amount, at most
+const ABBREVIATION_PRECISION: usize = 4;
+
+/// Format an amount like `format_amount_bytes`,
+/// falling back to `abbreviation` when the result
+/// is more than `max_chars` characters long.
+/// Abbreviated amounts are truncated rather than
+/// rounded, and start with `APPROX_MARK` so they
+/// cannot be mistaken for the exact value.
+///
+/// Returns `None` if no form fits.
+pub fn format_amount_fit<'b>(bytes: &[u8], decimals: u8, ticker: &str,
+    grouping: Grouping, max_chars: usize, abbreviation: Abbreviation,
+    buf: &'b mut [u8]) -> Option<&'b str> {
+    let mut digits = [0u8; U256_DIGITS];
+    let start = write_digits_be(bytes, &mut digits)?;
+    let digits = &digits[start..];
+
+    let fits = |s: &str| s.chars().count() <= max_chars;
+    let exact = amount_from_digits(digits, decimals, ticker, grouping, buf).map(fits);
+    if exact == Some(true) || abbreviation == Abbreviation::None || digits == b"0" {
+        return match exact {
+            Some(true) => amount_from_digits(digits, decimals, ticker, grouping, buf),
+            _ => None,
+        }
+    }
+
+    // Decimal exponent of the leading digit
+    let exponent = digits.len() as i64 - 1 - decimals as i64;
+    let (int_len, suffix, sci) = match abbreviation {
+        Abbreviation::Suffix if (3..15).contains(&exponent) => {
+            let group = exponent / 3;
+            let suffix = [&b""[..], b"K", b"M", b"B", b"T"][group as usize];
+            ((exponent - 3 * group) as usize + 1, suffix, false)
+        }
+        _ => (1, &b""[..], true),
+    };
+
+    let mut precision = ABBREVIATION_PRECISION;
+    loop {
+        let mut out = Cursor::new(&mut *buf);
+        let written = abbreviated(&mut out, digits, int_len, precision, suffix,
+            sci.then_some(exponent), ticker);
+        if written.is_some() && fits(out.as_str()) {
+            break
+        }
+        if precision == 0 {
+            return None
+        }
+        precision -= 1;
+    }
+    // Written again as `out` borrowed `buf` in the loop
+    let mut out = Cursor::new(buf);
+    abbreviated(&mut out, digits, int_len, precision, suffix, sci.then_some(exponent), ticker)?;
+    Some(out.into_str())
+}
+
+/// Write "~" `digits` with a point after `int_len`
+/// digits and at most `precision` digits after it,
+/// then `suffix` or the exponent, and `ticker`
+fn abbreviated(out: &mut Cursor, digits: &[u8], int_len: usize, precision: usize,
+    suffix: &[u8], exponent: Option<i64>, ticker: &str) -> Option<()> {
+    out.push(APPROX_MARK.as_bytes())?;
+    out.push(&digits[..int_len])?;
+    let frac = &digits[int_len..(int_len + precision).min(digits.len())];
+    let frac_len = frac.iter().rposition(|d| *d != b'0').map_or(0, |i| i + 1);
+    if frac_len > 0 {
+        out.push(b".")?;
+        out.push(&frac[..frac_len])?;
+    }
+    out.push(suffix)?;
+    if let Some(e) = exponent {
+        let mut e_buf = [0u8; I64_DIGITS];
+        out.push(b"e")?;
+        out.push(i64_to_str(e, &mut e_buf)?.as_bytes())?;
+    }
+    if !ticker.is_empty() {
+        out.push(b" ")?;
+        out.push(ticker.as_bytes())?;
+    }
+    Some(())
+}
+
+/// Format `numerator / denominator` as a
+/// percentage with at most `precision` decimals
+/// (up to 16), truncated: `format_percent(1, 8,
+/// 2, buf)` gives "12.5%".
+/// Returns `None` if `denominator` is zero.
+pub fn format_percent(numerator: u64, denominator: u64, precision: u8,
+    buf: &mut [u8]) -> Option<&str> {
+    if denominator == 0 || precision > 16 {
+        return None
+    }
+    let scaled = numerator as u128 * 100 * 10u128.pow(precision as u32)
+        / denominator as u128;
+    let mut digits = [0u8; U128_DIGITS];
+    let start = write_digits(scaled, &mut digits)?;
+    let end = buf.len().checked_sub(1)?;
+    let len = amount_from_digits(&digits[start..], precision, "", Grouping::None,
+        &mut buf[..end])?.len();
+    buf[len] = b'%';
+    Some(finish(&mut buf[..len + 1], 0))
+}
+
+/// Format basis points (hundredths of a
+/// percent) as a percentage: 1250 gives "12.5%"
+pub fn format_bps(bps: u32, buf: &mut [u8]) -> Option<&str> {
+    format_percent(bps as u64, 10_000, 2, buf)
+}
+
+const MINUTE: u64 = 60;
+const HOUR: u64 = 60 * MINUTE;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// Format a duration as its two largest units,
+/// dropping the second one when it is zero:
+/// "3d 4h", "5m 12s", "2d". The remainder
+/// below the second unit is truncated.
+pub fn format_duration(secs: u64, buf: &mut [u8]) -> Option<&str> {
+    const UNITS: [(u64, &[u8]); 4] = [(DAY, b"d"), (HOUR, b"h"), (MINUTE, b"m"), (1, b"s")];
+
+    let first = UNITS.iter().position(|(len, _)| secs >= *len).unwrap_or(UNITS.len() - 1);
+    let (len, name) = UNITS[first];
+    let mut out = Cursor::new(buf);
+    let mut digits = [0u8; U64_DIGITS];
+    out.push(u64_to_str(secs / len, &mut digits)?.as_bytes())?;
+    out.push(name)?;
+    if let Some((sub_len, sub_name)) = UNITS.get(first + 1) {
+        let n = secs % len / sub_len;
+        if n != 0 {
+            out.push(b" ")?;
+            out.push(u64_to_str(n, &mut digits)?.as_bytes())?;
+            out.push(sub_name)?;
+        }
+    }
+    Some(out.into_str())
+}
+
+/// Format a duration in a single, spelled out
+/// unit, prefixed with `APPROX_MARK` when it is
+/// not exact: "~2 weeks", "1 day", "~3 hours"
+pub fn format_duration_approx(secs: u64, buf: &mut [u8]) -> Option<&str> {
+    const UNITS: [(u64, &str); 7] = [
+        (365 * DAY, "year"), (30 * DAY, "month"), (WEEK, "week"), (DAY, "day"),
+        (HOUR, "hour"), (MINUTE, "minute"), (1, "second"),
+    ];
+
+    let (len, name) = UNITS.iter().find(|(len, _)| secs >= *len).unwrap_or(&UNITS[6]);
+    let n = secs / len;
+    let mut out = Cursor::new(buf);
+    let mut digits = [0u8; U64_DIGITS];
+    if !secs.is_multiple_of(*len) {
+        out.push(APPROX_MARK.as_bytes())?;
+    }
+    out.push(u64_to_str(n, &mut digits)?.as_bytes())?;
+    out.push(b" ")?;
+    out.push(name.as_bytes())?;
+    if n != 1 {
+        out.push(b"s")?;
+    }
+    Some(out.into_str())
+}
+
+/// Duration of `blocks` blocks produced every
+/// `block_time` seconds, to be passed to the
+/// duration formatters
+pub const fn blocks_to_secs(blocks: u64, block_time: u64) -> u64 {
+    blocks.saturating_mul(block_time)
+}
+
+/// Letter case of hexadecimal digits
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Case {
+    Lower,
+    Upper,
+}
+
+const fn hex_digit(nibble: u8, case: Case) -> u8 {
+    match (nibble, case) {
+        (0..=9, _) => b'0' + nibble,
+        (_, Case::Lower) => b'a' + nibble - 10,
+        (_, Case::Upper) => b'A' + nibble - 10,
+    }
+}
+
+/// Hexadecimal encoding of `bytes`, without prefix
+pub fn hex<'b>(bytes: &[u8], buf: &'b mut [u8], case: Case) -> Option<&'b str> {
+    hex_chunked(bytes, buf, case, 0, "")
+}
+
+/// Hexadecimal encoding of `bytes` with
+/// `separator` inserted every `chunk` digits:
+/// `hex_chunked(&[0xde, 0xad, 0xbe, 0xef], buf,
+/// Case::Upper, 4, " ")` gives "DEAD BEEF".
+/// A `chunk` of 0 disables separators.
+pub fn hex_chunked<'b>(bytes: &[u8], buf: &'b mut [u8], case: Case, chunk: usize,
+    separator: &str) -> Option<&'b str> {
+    let mut out = Cursor::new(buf);
+    let digits = bytes.iter().flat_map(|b| [b >> 4, b & 0xf]);
+    for (i, nibble) in digits.enumerate() {
+        if chunk != 0 && i != 0 && i % chunk == 0 {
+            out.push(separator.as_bytes())?;
+        }
+        out.push(&[hex_digit(nibble, case)])?;
+    }
+    Some(out.into_str())
+}
+
+/// Copy `s` inserting `separator` every `chunk`
+/// characters
+pub fn chunk_str<'b>(s: &str, chunk: usize, separator: &str,
+    buf: &'b mut [u8]) -> Option<&'b str> {
+    let mut out = Cursor::new(buf);
+    let mut c_buf = [0u8; 4];
+    for (i, c) in s.chars().enumerate() {
+        if chunk != 0 && i != 0 && i % chunk == 0 {
+            out.push(separator.as_bytes())?;
+        }
+        out.push(c.encode_utf8(&mut c_buf).as_bytes())?;
+    }
+    Some(out.into_str())
+}
+
+/// Encoding of an address, which decides
+/// how it is split for display
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AddressFormat {
+    /// Groups of 4 characters
+    Base58,
+    /// Human readable part and separator kept
+    /// whole, then groups of 4 characters:
+    /// "bc1 qar0 srrr ..."
+    Bech32,
+    /// "0x" prefix kept whole, then groups
+    /// of 4 characters
+    Hex,
+}
+
+/// Characters per group of `chunk_address`
+pub const ADDRESS_CHUNK: usize = 4;
+
+/// Split an address into space separated groups,
+/// to ease visual comparison with the address
+/// shown by a companion wallet
+pub fn chunk_address<'b>(address: &str, format: AddressFormat,
+    buf: &'b mut [u8]) -> Option<&'b str> {
+    let prefix_len = match format {
+        AddressFormat::Base58 => 0,
+        // The separator is the last '1', as
+        // the human readable part may contain some
+        AddressFormat::Bech32 => address.rfind('1').map_or(0, |i| i + 1),
+        AddressFormat::Hex if address.starts_with("0x") || address.starts_with("0X") => 2,
+        AddressFormat::Hex => 0,
+    };
+    let (prefix, data) = address.split_at(prefix_len);
+    let mut out = Cursor::new(&mut *buf);
+    out.push(prefix.as_bytes())?;
+    if !prefix.is_empty() && !data.is_empty() {
+        out.push(b" ")?;
+    }
+    let len = out.as_str().len();
+    let data_len = chunk_str(data, ADDRESS_CHUNK, " ", &mut buf[len..])?.len();
+    Some(finish(&mut buf[..len + data_len], 0))
+}
+
+/// Output of `sanitize`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sanitized<'b> {
+    pub text: &'b str,
+    /// Number of characters replaced
+    pub replaced: usize,
+}
+
+impl Sanitized<'_> {
+    /// Whether the text differs from the input, in
+    /// which case the user should be warned
+    pub const fn changed(&self) -> bool {
+        self.replaced != 0
+    }
+}
+
+/// Replaces characters the device cannot display
+pub const PLACEHOLDER: u8 = b'?';
+
+/// Copy host-provided text keeping only printable
+/// ASCII, which is all the device fonts can draw.
+/// Each other character, including control
+/// characters and whole UTF-8 sequences, is
+/// replaced by one `PLACEHOLDER` so it can
+/// neither hide content nor break the layout.
+/// Returns `None` if `buf` is shorter than `input`.
+pub fn sanitize<'b>(input: &[u8], buf: &'b mut [u8]) -> Option<Sanitized<'b>> {
+    sanitize_with(input, PLACEHOLDER, buf)
+}
+
+/// `sanitize` with a custom placeholder, which
+/// must itself be printable ASCII
+pub fn sanitize_with<'b>(input: &[u8], placeholder: u8,
+    buf: &'b mut [u8]) -> Option<Sanitized<'b>> {
+    let printable = |b: u8| (0x20..=0x7e).contains(&b);
+    if !printable(placeholder) {
+        return None
+    }
+    let mut out = Cursor::new(buf);
+    let mut replaced = 0;
+    let mut in_sequence = false;
+    for &b in input {
+        if printable(b) {
+            out.push(&[b])?;
+            in_sequence = false;
+        } else if in_sequence && b & 0xc0 == 0x80 {
+            // Continuation of an already replaced
+            // UTF-8 sequence
+        } else {
+            out.push(&[placeholder])?;
+            replaced += 1;
+            in_sequence = b >= 0x80;
+        }
+    }
+    Some(Sanitized { text: out.into_str(), replaced })
+}
+
+/// Year, month (1-12) and day (1-31) of a
+/// count of days since 1970-01-01
+const fn civil_from_days(days: u64) -> (u64, u8, u8) {
+    // Proleptic Gregorian calendar, from
+    // http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = era * 400 + yoe + (month <= 2) as u64;
+    (year, month, day)
+}
+
+/// Push `n` as `width` digits, zero padded
+fn push_padded(out: &mut Cursor, n: u64, width: usize) -> Option<()> {
+    let mut digits = [0u8; U64_DIGITS];
+    let s = u64_to_str(n, &mut digits)?;
+    for _ in s.len()..width {
+        out.push(b"0")?;
+    }
+    out.push(s.as_bytes())
+}
+
+fn push_date(out: &mut Cursor, timestamp: u64) -> Option<()> {
+    let (year, month, day) = civil_from_days(timestamp / DAY);
+    push_padded(out, year, 4)?;
+    out.push(b"-")?;
+    push_padded(out, month as u64, 2)?;
+    out.push(b"-")?;
+    push_padded(out, day as u64, 2)
+}
+
+/// Format a UNIX timestamp as "2024-06-01 13:37 UTC"
+pub fn format_timestamp(timestamp: u64, buf: &mut [u8]) -> Option<&str> {
+    let mut out = Cursor::new(buf);
+    push_date(&mut out, timestamp)?;
+    out.push(b" ")?;
+    push_padded(&mut out, timestamp % DAY / HOUR, 2)?;
+    out.push(b":")?;
+    push_padded(&mut out, timestamp % HOUR / MINUTE, 2)?;
+    out.push(b" UTC")?;
+    Some(out.into_str())
+}
+
+/// Format the date of a UNIX timestamp
+/// as "2024-06-01"
+pub fn format_date(timestamp: u64, buf: &mut [u8]) -> Option<&str> {
+    let mut out = Cursor::new(buf);
+    push_date(&mut out, timestamp)?;
+    Some(out.into_str())
+}
+
+/// How the "0x" prefix of an address is shown
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HexPrefix {
+    None,
+    /// "0xAbC..."
+    Plain,
+    /// "0x AbC...", setting the prefix apart
+    /// from the checksummed digits
+    Spaced,
+}
+
+/// Render a 20-byte Ethereum address with its
+/// EIP-55 checksum casing. `hash` is the
+/// Keccak-256 of the lowercase hex address
+/// (without prefix), computed by the caller.
+/// Returns `None` if `hash` is shorter than
+/// 20 bytes or `buf` is too small.
+pub fn eip55<'b>(address: &[u8; 20], hash: &[u8], prefix: HexPrefix,
+    buf: &'b mut [u8]) -> Option<&'b str> {
+    let hash = hash.get(..20)?;
+    let mut out = Cursor::new(buf);
+    match prefix {
+        HexPrefix::None => (),
+        HexPrefix::Plain => out.push(b"0x")?,
+        HexPrefix::Spaced => out.push(b"0x ")?,
+    }
+    for (byte, h) in address.iter().zip(hash) {
+        for (nibble, h) in [(byte >> 4, h >> 4), (byte & 0xf, h & 0xf)] {
+            let case = if h >= 8 { Case::Upper } else { Case::Lower };
+            out.push(&[hex_digit(nibble, case)])?;
+        }
+    }
+    Some(out.into_str())
+}
+
+/// Values that can be displayed in a widget,
+/// so that typed values can be handed to the
+/// UI instead of pre-formatted strings
+pub trait ToUiStr {
+    /// Write the displayed form of `self` to
+    /// `buf`, or return `None` if it is too small
+    fn write_ui<'b>(&self, buf: &'b mut [u8]) -> Option<&'b str>;
+}
+
+macro_rules! impl_to_ui_str {
+    ($f:ident as $conv:ty: $($t:ty)*) => {
+        $(impl ToUiStr for $t {
+            fn write_ui<'b>(&self, buf: &'b mut [u8]) -> Option<&'b str> {
+                $f(*self as $conv, buf)
+            }
+        })*
+    };
+}
+
+impl_to_ui_str!(u64_to_str as u64: u8 u16 u32 u64 usize);
+impl_to_ui_str!(i64_to_str as i64: i8 i16 i32 i64 isize);
+
+impl ToUiStr for bool {
+    fn write_ui<'b>(&self, buf: &'b mut [u8]) -> Option<&'b str> {
+        str::write_ui(crate::i18n::yes_no().get(*self), buf)
+    }
+}
+
+impl ToUiStr for str {
+    fn write_ui<'b>(&self, buf: &'b mut [u8]) -> Option<&'b str> {
+        let mut out = Cursor::new(buf);
+        out.push(self.as_bytes())?;
+        Some(out.into_str())
+    }
+}
+
+/// Bytes are shown in lowercase hexadecimal
+impl ToUiStr for [u8] {
+    fn write_ui<'b>(&self, buf: &'b mut [u8]) -> Option<&'b str> {
+        hex(self, buf, Case::Lower)
+    }
+}
+
+impl<T: ToUiStr + ?Sized> ToUiStr for &T {
+    fn write_ui<'b>(&self, buf: &'b mut [u8]) -> Option<&'b str> {
+        (**self).write_ui(buf)
+    }
+}
+
+/// A fixed-point amount, see `format_amount_bytes`
+#[derive(Copy, Clone, Debug)]
+pub struct Amount<'a> {
+    /// Big-endian integer of up to 32 bytes
+    pub value: &'a [u8],
+    pub decimals: u8,
+    pub ticker: &'a str,
+}
+
+impl<'a> Amount<'a> {
+    pub const fn new(value: &'a [u8], decimals: u8, ticker: &'a str) -> Self {
+        Amount { value, decimals, ticker }
+    }
+}
+
+impl ToUiStr for Amount<'_> {
+    fn write_ui<'b>(&self, buf: &'b mut [u8]) -> Option<&'b str> {
+        format_amount_bytes(self.value, self.decimals, self.ticker, Grouping::None, buf)
+    }
+}
+
+/// A BIP32 derivation path, shown as
+/// "m/44'/0'/0'/0/1"
+#[derive(Copy, Clone, Debug)]
+pub struct Path<'a>(pub &'a [u32]);
+
+/// Bit set in hardened path components
+pub const HARDENED: u32 = 0x8000_0000;
+
+impl ToUiStr for Path<'_> {
+    fn write_ui<'b>(&self, buf: &'b mut [u8]) -> Option<&'b str> {
+        let mut out = Cursor::new(buf);
+        let mut digits = [0u8; U32_DIGITS];
+        out.push(b"m")?;
+        for c in self.0 {
+            out.push(b"/")?;
+            out.push(u32_to_str(c & !HARDENED, &mut digits)?.as_bytes())?;
+            if c & HARDENED != 0 {
+                out.push(b"'")?;
+            }
+        }
+        Some(out.into_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `f` writes `expected` into a buffer of its
+    /// exact length, and fails with one byte less
+    fn exact(expected: &str, f: fn(&mut [u8]) -> Option<&str>) {
+        let mut buf = [0u8; 320];
+        let n = expected.len();
+        assert_eq!(f(&mut buf[..n]), Some(expected));
+        assert_eq!(f(&mut buf[..n - 1]), None, "{:?} fits in {} bytes", expected, n - 1);
+    }
+
+    #[test]
+    fn unsigned_integers() {
+        exact("0", |b| u64_to_str(0, b));
+        exact("18446744073709551615", |b| u64_to_str(u64::MAX, b));
+        exact("4294967295", |b| u32_to_str(u32::MAX, b));
+        assert_eq!(u64_to_str(u64::MAX, &mut [0u8; U64_DIGITS]).map(str::len), Some(U64_DIGITS));
+        exact("3/12", |b| page_counter(3, 12, b));
+    }
+
+    #[test]
+    fn signed_integers() {
+        exact("-9223372036854775808", |b| i64_to_str(i64::MIN, b));
+        exact("9223372036854775807", |b| i64_to_str(i64::MAX, b));
+        exact("0", |b| i64_to_str(0, b));
+        exact("-1", |b| i32_to_str(-1, b));
+        exact("-2147483648", |b| i32_to_str(i32::MIN, b));
+        assert_eq!(i64_to_str(i64::MIN, &mut [0u8; I64_DIGITS]).map(str::len), Some(I64_DIGITS));
+    }
+
+    #[test]
+    fn amounts() {
+        exact("0.0015 BTC", |b| format_amount(150_000, 8, "BTC", b));
+        exact("0 BTC", |b| format_amount(0, 8, "BTC", b));
+        exact("0", |b| format_amount(0, 0, "", b));
+        exact("1.5", |b| format_amount(15, 1, "", b));
+        exact("100", |b| format_amount(100, 0, "", b));
+        exact("1", |b| format_amount(100, 2, "", b));
+        exact("18446744073709551615", |b| format_amount(u64::MAX, 0, "", b));
+        exact("184467440737.09551615", |b| format_amount(u64::MAX, 8, "", b));
+        exact("340282366920938463463.374607431768211455 ETH",
+            |b| format_amount_u128(u128::MAX, 18, "ETH", b));
+    }
+
+    #[test]
+    fn amount_with_255_decimals() {
+        let mut buf = [0u8; 300];
+        let one = format_amount(1, 255, "", &mut buf).unwrap();
+        // The point, 254 zeros, then the digit
+        assert_eq!(one.len(), 257);
+        assert!(one.starts_with("0.000") && one.ends_with("01"));
+        assert_eq!(one[2..256].bytes().filter(|d| *d == b'0').count(), 254);
+        assert_eq!(format_amount(1, 255, "", &mut buf[..256]), None);
+
+        let max = format_amount(u64::MAX, 255, "", &mut buf).unwrap();
+        assert_eq!(max.len(), 257);
+        assert!(max.ends_with("018446744073709551615"));
+        exact("0", |b| format_amount(0, 255, "", b));
+    }
+
+    #[test]
+    fn amount_from_raw_digits() {
+        exact("12.34 X", |b| amount_from_digits(b"1234", 2, "X", Grouping::None, b));
+        exact("0.01", |b| amount_from_digits(b"1", 2, "", Grouping::None, b));
+        exact("0", |b| amount_from_digits(b"0", 255, "", Grouping::None, b));
+    }
+
+    #[test]
+    fn grouped_integers() {
+        exact("1,234,567", |b| u64_to_str_grouped(1_234_567, Grouping::COMMA, b));
+        exact("999", |b| u64_to_str_grouped(999, Grouping::COMMA, b));
+        exact("1 000", |b| u64_to_str_grouped(1000, Grouping::SPACE, b));
+        exact("1234567", |b| u64_to_str_grouped(1_234_567, Grouping::None, b));
+        exact("18,446,744,073,709,551,615", |b| u64_to_str_grouped(u64::MAX, Grouping::COMMA, b));
+        exact("-9,223,372,036,854,775,808", |b| i64_to_str_grouped(i64::MIN, Grouping::COMMA, b));
+        exact("-1", |b| i64_to_str_grouped(-1, Grouping::COMMA, b));
+        // Multi-byte separators
+        exact("1\u{202f}000\u{202f}000", |b| u64_to_str_grouped(1_000_000, Grouping::With('\u{202f}'), b));
+    }
+
+    #[test]
+    fn grouped_amounts() {
+        exact("1 234 567.89 EUR", |b| format_amount_grouped(123_456_789, 2, "EUR", Grouping::SPACE, b));
+        exact("0.5", |b| format_amount_grouped(5, 1, "", Grouping::COMMA, b));
+        exact("1,000", |b| format_amount_grouped(100_000, 2, "", Grouping::COMMA, b));
+    }
+
+    const U256_MAX: &str =
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+    #[test]
+    fn big_endian_integers() {
+        exact(U256_MAX, |b| be_bytes_to_str(&[0xff; 32], b));
+        assert_eq!(U256_MAX.len(), U256_DIGITS);
+        exact("0", |b| be_bytes_to_str(&[], b));
+        exact("0", |b| be_bytes_to_str(&[0; 32], b));
+        exact("256", |b| be_bytes_to_str(&[1, 0], b));
+        exact("18446744073709551615", |b| be_bytes_to_str(&u64::MAX.to_be_bytes(), b));
+        // More than 256 bits
+        assert_eq!(be_bytes_to_str(&[0; 33], &mut [0u8; 100]), None);
+        assert_eq!(be_bytes_to_str(&[1; 33], &mut [0u8; 100]), None);
+    }
+
+    #[test]
+    fn big_endian_amounts() {
+        exact("1 ETH", |b| format_amount_bytes(&1_000_000_000_000_000_000u64.to_be_bytes(), 18,
+            "ETH", Grouping::None, b));
+        exact("115,792,089,237,316,195,423,570,985,008,687,907,853,269,984,665,640,564,039,457\
+            .584007913129639935 ETH", |b| format_amount_bytes(&[0xff; 32], 18, "ETH", Grouping::COMMA, b));
+        assert_eq!(format_amount_bytes(&[0; 33], 18, "ETH", Grouping::None, &mut [0u8; 100]), None);
+    }
+
+    fn fit(value: u32, max_chars: usize, abbreviation: Abbreviation,
+        buf: &mut [u8]) -> Option<&str> {
+        format_amount_fit(&value.to_be_bytes(), 0, "", Grouping::None, max_chars, abbreviation, buf)
+    }
+
+    #[test]
+    fn amounts_fit() {
+        let mut buf = [0u8; 32];
+        assert_eq!(fit(1_234_567, 7, Abbreviation::Suffix, &mut buf), Some("1234567"));
+        assert_eq!(fit(1_234_567, 6, Abbreviation::None, &mut buf), None);
+        assert_eq!(fit(1_234_567, 5, Abbreviation::Suffix, &mut buf), Some("~1.2M"));
+        assert_eq!(fit(1_234_567, 6, Abbreviation::Scientific, &mut buf), Some("~1.2e6"));
+        assert_eq!(fit(1_000_000, 3, Abbreviation::Suffix, &mut buf), Some("~1M"));
+        assert_eq!(fit(1_234_567, 2, Abbreviation::Suffix, &mut buf), None);
+        assert_eq!(format_amount_fit(&[0xff; 32], 18, "ETH", Grouping::None, 12,
+            Abbreviation::Suffix, &mut buf), Some("~1.15e59 ETH"));
+        // Zero is never abbreviated
+        assert_eq!(format_amount_fit(&[0], 0, "TICKER", Grouping::None, 3,
+            Abbreviation::Suffix, &mut buf), None);
+        assert_eq!(format_amount_fit(&[0; 33], 0, "", Grouping::None, 80,
+            Abbreviation::Suffix, &mut buf), None);
+    }
+
+    #[test]
+    fn amounts_fit_small_buffers() {
+        // The exact amount fits the screen, but not
+        // the buffer: only shorter forms are written
+        assert_eq!(fit(1_234_567, 7, Abbreviation::None, &mut [0u8; 7]), Some("1234567"));
+        assert_eq!(fit(1_234_567, 7, Abbreviation::None, &mut [0u8; 6]), None);
+        assert_eq!(fit(1_234_567, 7, Abbreviation::Suffix, &mut [0u8; 4]), Some("~1M"));
+        assert_eq!(fit(1_234_567, 7, Abbreviation::Suffix, &mut [0u8; 2]), None);
+    }
+
+    #[test]
+    fn percentages() {
+        exact("12.5%", |b| format_percent(1, 8, 2, b));
+        exact("33.33%", |b| format_percent(1, 3, 2, b));
+        exact("0%", |b| format_percent(0, 3, 2, b));
+        exact("100%", |b| format_percent(1, 1, 0, b));
+        exact("1844674407370955161500%", |b| format_percent(u64::MAX, 1, 16, b));
+        assert_eq!(format_percent(1, 0, 2, &mut [0u8; 8]), None);
+        assert_eq!(format_percent(1, 2, 17, &mut [0u8; 64]), None);
+        exact("12.5%", |b| format_bps(1250, b));
+        exact("0.01%", |b| format_bps(1, b));
+        exact("42949672.95%", |b| format_bps(u32::MAX, b));
+    }
+
+    #[test]
+    fn durations() {
+        exact("0s", |b| format_duration(0, b));
+        exact("59s", |b| format_duration(59, b));
+        exact("1m", |b| format_duration(60, b));
+        exact("5m 12s", |b| format_duration(312, b));
+        exact("1h 1m", |b| format_duration(3661, b));
+        exact("3d 4h", |b| format_duration(3 * DAY + 4 * HOUR + 59, b));
+        exact("2d", |b| format_duration(2 * DAY + 59 * MINUTE, b));
+        exact("213503982334601d 7h", |b| format_duration(u64::MAX, b));
+    }
+
+    #[test]
+    fn approximate_durations() {
+        exact("0 seconds", |b| format_duration_approx(0, b));
+        exact("1 second", |b| format_duration_approx(1, b));
+        exact("1 day", |b| format_duration_approx(DAY, b));
+        exact("~3 hours", |b| format_duration_approx(3 * HOUR + 1, b));
+        exact("2 weeks", |b| format_duration_approx(2 * WEEK, b));
+        exact("~1 year", |b| format_duration_approx(366 * DAY, b));
+        assert_eq!(blocks_to_secs(10, 600), 6000);
+        assert_eq!(blocks_to_secs(u64::MAX, 2), u64::MAX);
+    }
+
+    #[test]
+    fn hex_encoding() {
+        const BYTES: [u8; 4] = [0xde, 0xad, 0xbe, 0xef];
+        exact("deadbeef", |b| hex(&BYTES, b, Case::Lower));
+        exact("DEADBEEF", |b| hex(&BYTES, b, Case::Upper));
+        exact("00ff", |b| hex(&[0x00, 0xff], b, Case::Lower));
+        exact("DEAD BEEF", |b| hex_chunked(&BYTES, b, Case::Upper, 4, " "));
+        exact("de:ad:be:ef", |b| hex_chunked(&BYTES, b, Case::Lower, 2, ":"));
+        assert_eq!(hex(&[], &mut [], Case::Lower), Some(""));
+    }
+
+    #[test]
+    fn chunked_addresses() {
+        exact("1A1z P1eP 5QGe fi2D MPTf TL5S Lmv7 Divf Na", |b| chunk_address(
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa", AddressFormat::Base58, b));
+        exact("bc1 qar0 srrr 7xfk vy5l 643l ydnw 9re5 9gtz zwf5 mdq", |b| chunk_address(
+            "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq", AddressFormat::Bech32, b));
+        exact("0x 5aAe b605 3F3E", |b| chunk_address("0x5aAeb6053F3E", AddressFormat::Hex, b));
+        exact("5aAe b605", |b| chunk_address("5aAeb605", AddressFormat::Hex, b));
+        exact("éééé é", |b| chunk_str("ééééé", 4, " ", b));
+        exact("abc", |b| chunk_str("abc", 0, " ", b));
+    }
+
+    fn sanitized<'b>(input: &[u8], buf: &'b mut [u8]) -> Option<(&'b str, usize)> {
+        sanitize(input, buf).map(|s| (s.text, s.replaced))
+    }
+
+    #[test]
+    fn sanitized_text() {
+        let mut buf = [0u8; 32];
+        assert_eq!(sanitized(b"Pay\x00 to\x1b[2J", &mut buf), Some(("Pay? to?[2J", 2)));
+        // One placeholder per UTF-8 sequence
+        assert_eq!(sanitized("café 😀".as_bytes(), &mut buf), Some(("caf? ?", 2)));
+        assert_eq!(sanitized("éé".as_bytes(), &mut buf), Some(("??", 2)));
+        assert_eq!(sanitized(b"a\x80b\x7f", &mut buf), Some(("a?b?", 2)));
+        assert_eq!(sanitize(b"plain", &mut buf).map(|s| s.changed()), Some(false));
+        assert_eq!(sanitized(b"abc", &mut buf[..3]), Some(("abc", 0)));
+        assert_eq!(sanitized(b"abc", &mut buf[..2]), None);
+        assert_eq!(sanitize_with(b"\x01", b'_', &mut buf).map(|s| s.text), Some("_"));
+        assert_eq!(sanitize_with(b"a", b'\n', &mut buf), None);
+    }
+
+    #[test]
+    fn dates() {
+        exact("1970-01-01 00:00 UTC", |b| format_timestamp(0, b));
+        exact("1970-01-01", |b| format_date(DAY - 1, b));
+        exact("2000-02-29", |b| format_date(951_782_400, b));
+        exact("2000-02-29 23:59 UTC", |b| format_timestamp(951_868_799, b));
+        exact("2000-03-01 00:00 UTC", |b| format_timestamp(951_868_800, b));
+        exact("2038-01-19 03:14 UTC", |b| format_timestamp(i32::MAX as u64, b));
+        exact("2038-01-19 03:14 UTC", |b| format_timestamp(i32::MAX as u64 + 1, b));
+        // 2100 is not a leap year
+        exact("2100-02-28", |b| format_date(4_107_456_000, b));
+        exact("2100-03-01", |b| format_date(4_107_542_400, b));
+    }
+
+    /// Address from the EIP-55 reference, then the
+    /// first bytes of the Keccak-256 of its
+    /// lowercase hex digits
+    const EIP55: [(&str, [u8; 20]); 4] = [
+        ("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed", [0xd3, 0x85, 0x65, 0x0c, 0xe8, 0xfd, 0xc6,
+            0xdb, 0x7e, 0xe3, 0xa0, 0x91, 0xd3, 0x48, 0x14, 0xdb, 0xc4, 0xce, 0x18, 0x21]),
+        ("0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359", [0x5c, 0xfa, 0xc6, 0x63, 0xf4, 0x58, 0x37,
+            0xb4, 0x09, 0xc4, 0xd3, 0xdc, 0x1c, 0xef, 0x5f, 0x47, 0x59, 0x73, 0x4f, 0x49]),
+        ("0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB", [0x75, 0xcd, 0x39, 0x58, 0xe2, 0x51, 0xde,
+            0x0c, 0x49, 0xf5, 0x4d, 0xa9, 0x9b, 0x77, 0xf7, 0x9a, 0xdb, 0xef, 0x92, 0xca]),
+        ("0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb", [0xc8, 0xbc, 0x5d, 0x10, 0x24, 0x92, 0x38,
+            0xb9, 0x2a, 0xcb, 0x83, 0x8a, 0x86, 0xd8, 0x83, 0xbb, 0x92, 0x53, 0xc4, 0xb0]),
+    ];
+
+    /// Bytes of a hex address
+    fn address(hex: &str) -> [u8; 20] {
+        let mut bytes = [0u8; 20];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 + 2 * i..4 + 2 * i], 16).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn eip55_reference_addresses() {
+        let mut buf = [0u8; 43];
+        for (expected, hash) in EIP55.iter() {
+            let bytes = address(expected);
+            assert_eq!(eip55(&bytes, hash, HexPrefix::Plain, &mut buf[..42]), Some(*expected));
+            assert_eq!(eip55(&bytes, hash, HexPrefix::Plain, &mut buf[..41]), None);
+            assert_eq!(eip55(&bytes, hash, HexPrefix::None, &mut buf[..40]), Some(&expected[2..]));
+            let spaced = eip55(&bytes, hash, HexPrefix::Spaced, &mut buf).unwrap();
+            assert_eq!((&spaced[..3], &spaced[3..]), ("0x ", &expected[2..]));
+            // The hash must cover every digit
+            assert_eq!(eip55(&bytes, &hash[..19], HexPrefix::Plain, &mut buf), None);
+        }
+    }
+}
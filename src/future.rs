@@ -0,0 +1,100 @@
+//! Widgets as futures, for apps built around
+//! an async APDU dispatcher: awaiting a
+//! confirmation hands control back to the
+//! executor after each SEPH event, instead of
+//! blocking until the user is done.
+//!
+//! ```ignore
+//! async fn sign(comm: &mut Comm) {
+//!     if Validator::new("Sign?").ask_async().await == Outcome::Approved {
+//!         comm.reply_ok();
+//!     }
+//! }
+//! ```
+//!
+//! Apps without an executor of their own
+//! can use `block_on`.
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use crate::widget::{Session, Widget};
+
+/// Runs `widget` like `widget::run`, one event
+/// per poll. It owns the display from the first
+/// poll until it is done or dropped.
+pub struct WidgetFuture<'w, W: Widget> {
+    widget: &'w mut W,
+    session: Option<Session>,
+}
+
+impl<'w, W: Widget> WidgetFuture<'w, W> {
+    pub fn new(widget: &'w mut W) -> Self {
+        WidgetFuture { widget, session: None }
+    }
+}
+
+impl<W: Widget> Future for WidgetFuture<'_, W> {
+    type Output = W::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<W::Output> {
+        let this = self.get_mut();
+        let widget = &mut *this.widget;
+        let session = this.session.get_or_insert_with(|| Session::start(widget));
+        let output = match session.next_event(widget) {
+            Some(output) => Some(output),
+            None => session.check_idle(widget),
+        };
+        match output {
+            Some(output) => {
+                if let Some(session) = this.session.take() {
+                    session.end();
+                }
+                Poll::Ready(output)
+            }
+            // Events come from SEPH, which
+            // cannot wake us: poll again
+            None => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<W: Widget> Drop for WidgetFuture<'_, W> {
+    fn drop(&mut self) {
+        if let Some(session) = self.session.take() {
+            session.end();
+        }
+    }
+}
+
+/// `widget::run`, as a future
+pub fn run_async<W: Widget>(widget: &mut W) -> WidgetFuture<'_, W> {
+    WidgetFuture::new(widget)
+}
+
+fn noop_raw_waker() -> RawWaker {
+    fn clone(_: *const ()) -> RawWaker {
+        noop_raw_waker()
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+    RawWaker::new(core::ptr::null(), &VTABLE)
+}
+
+/// Poll `future` until it is ready. Widget
+/// futures make progress on every poll, so no
+/// waking is needed.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = unsafe { Waker::from_raw(noop_raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut future = core::pin::pin!(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output
+        }
+    }
+}
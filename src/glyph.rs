@@ -0,0 +1,97 @@
+//! Icons drawn from bitmaps provided by the
+//! app, e.g. its coin logo, rather than from
+//! the glyphs built into the firmware:
+//!
+//! ```ignore
+//! // 1 bpp rows, as in an XBM file
+//! const LOGO_ROWS: [u8; 28] = [0x00, 0x00, 0xf8, 0x07, ..];
+//! const LOGO_BITMAP: [u8; packed_len(14, 14, 1)] = pack_rows(&LOGO_ROWS, 14, 14);
+//! static LOGO: Glyph = Glyph::new(&LOGO_BITMAP, 14, 14);
+//!
+//! app_info!().home().icon(Icon::from_glyph(&LOGO)).show();
+//! ```
+
+use crate::seph;
+
+/// Colors of 1 bpp glyphs: black
+/// background, white pixels
+pub const MONOCHROME: [u32; 2] = [0x000000, 0xffffff];
+
+/// A bitmap in the BAGL glyph format
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Glyph {
+    pub width: u16,
+    pub height: u16,
+    /// Bits per pixel: 1, 2 or 4
+    pub bpp: u8,
+    /// Color of each pixel value, as 0xRRGGBB
+    pub colors: &'static [u32],
+    /// Pixels row after row, without padding
+    /// between rows, least significant bits first
+    pub bitmap: &'static [u8],
+}
+
+impl Glyph {
+    /// Monochrome glyph from a 1 bpp `bitmap`,
+    /// see `pack_rows`
+    pub const fn new(bitmap: &'static [u8], width: u16, height: u16) -> Self {
+        Glyph { width, height, bpp: 1, colors: &MONOCHROME, bitmap }
+    }
+
+    /// Use `bpp` bits per pixel, each pixel
+    /// value being an index in `colors`
+    pub const fn colors(self, bpp: u8, colors: &'static [u32]) -> Self {
+        Glyph { bpp, colors, ..self }
+    }
+
+    /// Bytes sent after the BAGL component
+    pub(crate) const fn payload_len(&self) -> usize {
+        1 + self.colors.len() * 4 + self.bitmap.len()
+    }
+
+    /// Send the bpp, color table and bitmap
+    /// following the BAGL component
+    pub(crate) fn send(&self) {
+        widget_debug_assert!(self.colors.len() == 1 << self.bpp, "one color per pixel value");
+        widget_debug_assert!(self.bitmap.len() == packed_len(self.width as usize,
+            self.height as usize, self.bpp as usize), "bitmap does not match the glyph size");
+        send_bitmap(self.bpp, self.colors, self.bitmap);
+    }
+}
+
+/// Send `bpp`, the color table and `bitmap`
+pub(crate) fn send_bitmap(bpp: u8, colors: &[u32], bitmap: &[u8]) {
+    seph::seph_send(&[bpp]);
+    for color in colors {
+        seph::seph_send(&color.to_le_bytes());
+    }
+    seph::seph_send(bitmap);
+}
+
+/// Bytes of a `width` x `height` bitmap
+/// with `bpp` bits per pixel
+pub const fn packed_len(width: usize, height: usize, bpp: usize) -> usize {
+    (width * height * bpp).div_ceil(8)
+}
+
+/// Pack 1 bpp `rows`, each starting on a new
+/// byte, least significant bit first (as in
+/// XBM files), into the BAGL format. `N` must
+/// be `packed_len(width, height, 1)`.
+pub const fn pack_rows<const N: usize>(rows: &[u8], width: usize, height: usize) -> [u8; N] {
+    let row_len = width.div_ceil(8);
+    let mut out = [0u8; N];
+    let mut y = 0;
+    while y < height {
+        let mut x = 0;
+        while x < width {
+            if rows[y * row_len + x / 8] & (1 << (x % 8)) != 0 {
+                let i = y * width + x;
+                out[i / 8] |= 1 << (i % 8);
+            }
+            x += 1;
+        }
+        y += 1;
+    }
+    out
+}
@@ -0,0 +1,136 @@
+//! Approval by holding both buttons, as the
+//! "Hold to sign" screens of newer devices:
+//! a bar shrinks while the buttons are held
+//! and the transaction is approved once it is
+//! empty. Releasing early starts over, and the
+//! left button alone rejects.
+//!
+//! ```ignore
+//! match HoldToApprove::new().ask() {
+//!     Outcome::Approved => sign(),
+//!     _ => return Err(SW_DENIED),
+//! }
+//! ```
+
+use crate::bagls::*;
+use crate::buttons::ButtonEvent;
+use crate::i18n::{tr, Text};
+use crate::input::{Action, Input};
+use crate::screen;
+use crate::truncate::{fit, LINE_BUF_LEN};
+use crate::ui::{Actions, WidgetState};
+use crate::widget::{Event, Outcome, Widget};
+
+/// Ticker events both buttons must be
+/// held for by default, about 2 seconds
+pub const HOLD_TICKS: u8 = 20;
+
+/// Position and width of the bar
+const BAR_X: i16 = 14;
+const BAR_Y: i16 = 20;
+const BAR_WIDTH: u16 = 100;
+
+#[derive(Copy, Clone)]
+pub struct HoldToApprove<'a> {
+    /// Translated "Hold to sign" if None
+    message: Option<&'a str>,
+    ticks: u8,
+    /// Ticks both buttons have been held for,
+    /// `None` while they are not
+    held: Option<u8>,
+}
+
+impl<'a> HoldToApprove<'a> {
+    pub const fn new() -> Self {
+        HoldToApprove { message: None, ticks: HOLD_TICKS, held: None }
+    }
+
+    pub const fn message(self, message: &'a str) -> Self {
+        HoldToApprove { message: Some(message), ..self }
+    }
+
+    /// Require holding for `ticks` ticker
+    /// events (10 per second), at least one
+    pub const fn ticks(self, ticks: u8) -> Self {
+        let ticks = if ticks == 0 { 1 } else { ticks };
+        HoldToApprove { ticks, ..self }
+    }
+
+    fn text(&self) -> &'a str {
+        self.message.unwrap_or_else(|| tr(Text::HoldToSign))
+    }
+
+    pub fn ask(&self) -> Outcome {
+        #[cfg(feature = "nbgl")]
+        return crate::nbgl::choice(self.text());
+        #[cfg(not(feature = "nbgl"))]
+        crate::widget::run(&mut { *self })
+    }
+
+    /// Width of the filled part of the bar
+    fn bar_width(&self) -> u16 {
+        let left = (self.ticks - self.held.unwrap_or(0).min(self.ticks)) as u32;
+        (left * BAR_WIDTH as u32 / self.ticks as u32) as u16
+    }
+
+    fn paint_bar(&self) {
+        let screen = screen::current();
+        let y = screen.y(BAR_Y);
+        Rect::new().pos(BAR_X, y).dims(BAR_WIDTH, 3).colors(0, 0xffffff).fill(true).paint();
+        Rect::new().pos(BAR_X, y).dims(BAR_WIDTH, 3).paint();
+        let width = self.bar_width();
+        if width > 0 {
+            Rect::new().pos(BAR_X, y).dims(width, 3).fill(true).paint();
+        }
+    }
+}
+
+impl Default for HoldToApprove<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Widget for HoldToApprove<'_> {
+    type Output = Outcome;
+
+    fn draw(&self) {
+        let screen = screen::current();
+        let mut buf = [0u8; LINE_BUF_LEN];
+        LabelLine::new().dims(104, 11).pos(12, screen.y(12))
+            .text(fit(self.text(), None, &mut buf)).display();
+        screen.place(Icon::new(Icons::Cross).pos(3, 12)).paint();
+        self.paint_bar();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Outcome> {
+        match event {
+            Event::Button(ButtonEvent::BothButtonsPress) => {
+                self.held = Some(0);
+                self.paint_bar();
+            }
+            Event::Ticker => {
+                let held = self.held?.saturating_add(1);
+                self.held = Some(held);
+                if held >= self.ticks {
+                    return Some(Outcome::Approved)
+                }
+                self.paint_bar();
+            }
+            // Released too early
+            Event::Button(ButtonEvent::BothButtonsRelease) => {
+                self.held = None;
+                self.paint_bar();
+            }
+            Event::Button(ButtonEvent::LeftButtonRelease)
+            | Event::Input(Input::Complete(Action::Reject)) => return Some(Outcome::Rejected),
+            _ => (),
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("HoldToApprove", self.held.unwrap_or(0) as usize, self.ticks as usize)
+            .actions(Actions::PREVIOUS.with(Actions::CONFIRM))
+    }
+}
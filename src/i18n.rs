@@ -0,0 +1,274 @@
+//! Localization of the fixed strings shown by
+//! widgets. Apps register one table per
+//! language, and the active one can be changed
+//! at runtime, e.g. from a settings menu:
+//!
+//! ```ignore
+//! static LANGUAGES: [Language; 2] = [i18n::ENGLISH, i18n::FRENCH];
+//! i18n::register(&LANGUAGES);
+//! i18n::language_menu();
+//! ```
+//!
+//! Apps shipping a single language pick it at
+//! compile time instead, with the `lang-fr` or
+//! `lang-de` feature: `DEFAULT` is then active
+//! without registering anything.
+
+use crate::fmt::BoolLabels;
+use crate::text::TRUNCATION_MARKER;
+use crate::ui::{Menu, MenuResult};
+
+/// Identifiers of the fixed strings
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Text {
+    Approve,
+    Reject,
+    Cancel,
+    Processing,
+    Yes,
+    No,
+    Back,
+    Language,
+    /// Step accepting a blind signing risk
+    AcceptRisk,
+    /// Message of `HoldToApprove`
+    HoldToSign,
+    /// Last entry of a `PickList`
+    Done,
+    /// Exits of a `Compare`
+    Match,
+    Mismatch,
+    /// Acknowledgement page of a `Warning`
+    Understood,
+    /// Placeholder of an empty `Menu`
+    NoEntries,
+}
+
+/// Number of `Text` variants, hence of
+/// strings in a `Language` table
+pub const TEXT_COUNT: usize = 15;
+
+/// String table of a language, indexed by `Text`
+#[derive(Copy, Clone, Debug)]
+pub struct Language {
+    /// ISO 639-1 code, e.g. "en"
+    pub code: &'static str,
+    /// Name of the language, in that language
+    pub name: &'static str,
+    pub strings: [&'static str; TEXT_COUNT],
+}
+
+impl Language {
+    pub const fn get(&self, id: Text) -> &'static str {
+        self.strings[id as usize]
+    }
+}
+
+pub const ENGLISH: Language = Language {
+    code: "en",
+    name: "English",
+    strings: ["Approve", "Reject", "Cancel", "Processing", "Yes", "No", "Back", "Language",
+              "Accept risk", "Hold to sign", "Done", "Match", "Mismatch", "I understand",
+              "No entries"],
+};
+
+pub const FRENCH: Language = Language {
+    code: "fr",
+    name: "Francais",
+    strings: ["Approuver", "Rejeter", "Annuler", "Traitement", "Oui", "Non", "Retour", "Langue",
+              "Accepter le risque", "Maintenir pour signer", "Termine", "Identiques",
+              "Differents", "J'ai compris", "Aucune entree"],
+};
+
+/// Umlauts are spelled out, the device
+/// fonts being ASCII only
+pub const GERMAN: Language = Language {
+    code: "de",
+    name: "Deutsch",
+    strings: ["Genehmigen", "Ablehnen", "Abbrechen", "Verarbeitung", "Ja", "Nein", "Zurueck",
+              "Sprache", "Risiko akzeptieren", "Halten zum Signieren", "Fertig", "Identisch",
+              "Abweichend", "Verstanden", "Keine Eintraege"],
+};
+
+/// Language active until `register` is called,
+/// selected by the `lang-*` features
+#[cfg(feature = "lang-fr")]
+pub const DEFAULT: Language = FRENCH;
+#[cfg(all(feature = "lang-de", not(feature = "lang-fr")))]
+pub const DEFAULT: Language = GERMAN;
+#[cfg(not(any(feature = "lang-fr", feature = "lang-de")))]
+pub const DEFAULT: Language = ENGLISH;
+
+/// Most languages shown by `language_menu`
+pub const MAX_LANGUAGES: usize = 8;
+
+static mut LANGUAGES: &[Language] = &[DEFAULT];
+static mut ACTIVE: usize = 0;
+
+/// Set the available languages and make the
+/// first one active
+pub fn register(languages: &'static [Language]) {
+    if languages.is_empty() {
+        return
+    }
+    unsafe {
+        LANGUAGES = languages;
+        ACTIVE = 0;
+    }
+}
+
+pub fn languages() -> &'static [Language] {
+    unsafe { LANGUAGES }
+}
+
+pub fn active() -> &'static Language {
+    &languages()[unsafe { ACTIVE }]
+}
+
+/// Make the language at `index` in the
+/// registered table active. Returns `false`
+/// if there is no such language.
+pub fn set_language(index: usize) -> bool {
+    if index >= languages().len() {
+        return false
+    }
+    unsafe { ACTIVE = index }
+    true
+}
+
+/// Make the language with the given code
+/// active. Returns `false` if it is unknown.
+pub fn set_language_code(code: &str) -> bool {
+    match languages().iter().position(|l| l.code == code) {
+        Some(i) => set_language(i),
+        None => false,
+    }
+}
+
+/// String `id` in the active language
+pub fn tr(id: Text) -> &'static str {
+    active().get(id)
+}
+
+/// Yes/No words in the active language
+pub fn yes_no() -> BoolLabels {
+    BoolLabels::new(tr(Text::Yes), tr(Text::No))
+}
+
+/// Let the user pick the active language
+/// among the first `MAX_LANGUAGES` ones.
+/// Does nothing with a single language.
+pub fn language_menu() {
+    let languages = languages();
+    let count = languages.len().min(MAX_LANGUAGES);
+    if count < 2 {
+        return
+    }
+    let mut names = [""; MAX_LANGUAGES];
+    for (name, l) in names.iter_mut().zip(languages) {
+        *name = l.name;
+    }
+    if let MenuResult::Selected(index) = Menu::new(&names[..count]).show() {
+        set_language(index);
+    }
+}
+
+/// Part of a parsed template
+enum Segment<'t> {
+    Text(&'t str),
+    Arg(&'t str),
+}
+
+/// Split `template` into literal text and
+/// argument values, or `None` if it names an
+/// argument missing from `args`
+fn segments<'t>(template: &'t str, args: &[(&str, &'t str)],
+    f: &mut dyn FnMut(Segment<'t>)) -> Option<()> {
+    let mut rest = template;
+    while let Some(open) = rest.find(&['{', '}'][..]) {
+        if rest[open + 1..].starts_with(&rest[open..open + 1]) {
+            f(Segment::Text(&rest[..open + 1]));
+            rest = &rest[open + 2..];
+            continue
+        }
+        if rest.as_bytes()[open] == b'}' {
+            f(Segment::Text(&rest[..open + 1]));
+            rest = &rest[open + 1..];
+            continue
+        }
+        f(Segment::Text(&rest[..open]));
+        let close = open + rest[open..].find('}')?;
+        let name = &rest[open + 1..close];
+        let (_, value) = args.iter().find(|(n, _)| *n == name)?;
+        f(Segment::Arg(value));
+        rest = &rest[close + 1..];
+    }
+    f(Segment::Text(rest));
+    Some(())
+}
+
+/// Fill the `{name}` placeholders of a localized
+/// sentence with `args`, `{{` and `}}` standing
+/// for literal braces:
+///
+/// ```ignore
+/// fill_template("Send {amount} to {dest}",
+///     &[("amount", amount), ("dest", address)], &mut buf)
+/// ```
+///
+/// When the result does not fit in `buf`, the
+/// longest argument values are shortened first and
+/// end with `TRUNCATION_MARKER`, while the template
+/// text is kept whole. Returns `None` if a
+/// placeholder has no value or the template text
+/// alone does not fit.
+pub fn fill_template<'b>(template: &str, args: &[(&str, &str)],
+    buf: &'b mut [u8]) -> Option<&'b str> {
+    let (mut fixed, mut longest) = (0, 0);
+    segments(template, args, &mut |s| match s {
+        Segment::Text(t) => fixed += t.len(),
+        Segment::Arg(v) => longest = longest.max(v.len()),
+    })?;
+
+    // Largest length every value can keep
+    let room = buf.len().checked_sub(fixed)?;
+    let mut limit = longest;
+    loop {
+        let mut len = 0;
+        segments(template, args, &mut |s| if let Segment::Arg(v) = s {
+            let (kept, marker) = shorten(v, limit);
+            len += kept.len() + marker.len();
+        })?;
+        if len <= room {
+            break
+        }
+        limit -= 1;
+    }
+
+    let mut out = crate::fmt::Cursor::new(buf);
+    let mut ok = Some(());
+    segments(template, args, &mut |s| {
+        let (text, marker) = match s {
+            Segment::Text(t) => (t, ""),
+            Segment::Arg(v) => shorten(v, limit),
+        };
+        ok = ok.and(out.push(text.as_bytes())).and(out.push(marker.as_bytes()));
+    })?;
+    ok?;
+    Some(out.into_str())
+}
+
+/// `value` cut to `limit` bytes including
+/// the truncation marker, which is returned
+/// separately
+fn shorten(value: &str, limit: usize) -> (&str, &'static str) {
+    if value.len() <= limit {
+        return (value, "")
+    }
+    let marker = if limit >= TRUNCATION_MARKER.len() { TRUNCATION_MARKER } else { "" };
+    let mut n = limit - marker.len();
+    while !value.is_char_boundary(n) {
+        n -= 1;
+    }
+    (&value[..n], marker)
+}
@@ -0,0 +1,389 @@
+//! Logical input consumed by the widgets, so
+//! they do not depend on the physical device.
+//!
+//! Widgets react to `Action`s: moving to the
+//! previous or next item, confirming, rejecting.
+//! An `InputMapper` turns device events into
+//! actions; `ButtonMapper` does it for the two
+//! buttons of the Nano S, and another mapper
+//! (e.g. for touch gestures) can be installed
+//! with `set_mapper`. A `KeyMap` changes which
+//! buttons give which action, for all widgets or
+//! only for the widgets it is given to.
+
+use crate::buttons::ButtonEvent;
+use crate::widget::Event;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    Previous,
+    Next,
+    Confirm,
+    Reject,
+}
+
+/// An action as seen by a widget
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Input {
+    /// The action started but is not done yet,
+    /// e.g. its button is held: widgets may
+    /// show feedback
+    Pending(Action),
+    /// The action is done
+    Complete(Action),
+}
+
+pub trait InputMapper {
+    fn map(&self, event: Event) -> Option<Input>;
+}
+
+/// Both buttons confirm; by default left goes
+/// back (and picks the top choice, the cancel
+/// one being at the bottom) and right forward.
+/// Buttons cannot reject.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ButtonMapper {
+    /// Exchange the roles of the left
+    /// and right buttons
+    pub swapped: bool,
+}
+
+impl ButtonMapper {
+    pub const DEFAULT: ButtonMapper = ButtonMapper { swapped: false };
+    /// For left-handed users, or apps mirroring
+    /// the conventions of a companion app
+    pub const SWAPPED: ButtonMapper = ButtonMapper { swapped: true };
+}
+
+impl InputMapper for ButtonMapper {
+    fn map(&self, event: Event) -> Option<Input> {
+        let button = match event {
+            Event::Button(b) => b,
+            Event::Input(input) => return Some(input),
+            _ => return None,
+        };
+        let (left, right) = match self.swapped {
+            false => (Action::Previous, Action::Next),
+            true => (Action::Next, Action::Previous),
+        };
+        Some(match button {
+            ButtonEvent::LeftButtonPress => Input::Pending(left),
+            ButtonEvent::RightButtonPress => Input::Pending(right),
+            ButtonEvent::BothButtonsPress => Input::Pending(Action::Confirm),
+            ButtonEvent::LeftButtonRelease => Input::Complete(left),
+            ButtonEvent::RightButtonRelease => Input::Complete(right),
+            ButtonEvent::BothButtonsRelease => Input::Complete(Action::Confirm),
+            // Widgets watching for long presses
+            // look at the button event itself
+            ButtonEvent::LeftButtonLongPress
+            | ButtonEvent::RightButtonLongPress
+            | ButtonEvent::BothButtonsLongPress => return None,
+        })
+    }
+}
+
+static mut MAPPER: &dyn InputMapper = &ButtonMapper::DEFAULT;
+
+/// Use `mapper` to turn events into
+/// actions for all widgets
+pub fn set_mapper(mapper: &'static dyn InputMapper) {
+    unsafe { MAPPER = mapper }
+}
+
+pub fn mapper() -> &'static dyn InputMapper {
+    unsafe { MAPPER }
+}
+
+/// Exchange the roles of the buttons
+/// for all widgets, or restore them
+pub fn swap_buttons(swapped: bool) {
+    set_mapper(match swapped {
+        false => &ButtonMapper::DEFAULT,
+        true => &ButtonMapper::SWAPPED,
+    })
+}
+
+/// What can be done with the buttons
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Gesture {
+    Left,
+    Right,
+    Both,
+    /// Held for `LONG_PRESS_TICKS`
+    LeftLong,
+    RightLong,
+    BothLong,
+}
+
+impl Gesture {
+    pub const fn is_long(self) -> bool {
+        matches!(self, Gesture::LeftLong | Gesture::RightLong | Gesture::BothLong)
+    }
+}
+
+/// Which gesture gives which action. Long
+/// press gestures give it only once held, not
+/// pending from the press. Given to a widget
+/// (see `Keys`), the release that follows them
+/// gives nothing: installed for all widgets, it
+/// gives the action of its buttons, if any.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyMap {
+    pub previous: Gesture,
+    pub next: Gesture,
+    pub confirm: Gesture,
+    /// Buttons cannot reject by default
+    pub cancel: Option<Gesture>,
+}
+
+impl KeyMap {
+    /// As `ButtonMapper::DEFAULT`
+    pub const DEFAULT: KeyMap = KeyMap { previous: Gesture::Left, next: Gesture::Right,
+        confirm: Gesture::Both, cancel: None };
+    /// As `ButtonMapper::SWAPPED`
+    pub const LEFT_HANDED: KeyMap = KeyMap { previous: Gesture::Right, next: Gesture::Left,
+        ..KeyMap::DEFAULT };
+    /// Confirm only once both buttons were held,
+    /// so that a slip does not confirm
+    pub const HOLD_TO_CONFIRM: KeyMap = KeyMap { confirm: Gesture::BothLong, ..KeyMap::DEFAULT };
+
+    pub const fn cancel(self, gesture: Gesture) -> Self {
+        KeyMap { cancel: Some(gesture), ..self }
+    }
+
+    fn action(&self, gesture: Gesture) -> Option<Action> {
+        match gesture {
+            g if g == self.previous => Some(Action::Previous),
+            g if g == self.next => Some(Action::Next),
+            g if g == self.confirm => Some(Action::Confirm),
+            g if Some(g) == self.cancel => Some(Action::Reject),
+            _ => None,
+        }
+    }
+
+    /// Input of `event`, `long_pressed` telling
+    /// whether the buttons held gave a long press
+    fn input(&self, event: Event, long_pressed: &mut bool) -> Option<Input> {
+        let button = match event {
+            Event::Button(b) => b,
+            Event::Input(input) => return Some(input),
+            _ => return None,
+        };
+        let (gesture, complete) = match button {
+            ButtonEvent::LeftButtonPress => (Gesture::Left, false),
+            ButtonEvent::RightButtonPress => (Gesture::Right, false),
+            ButtonEvent::BothButtonsPress => (Gesture::Both, false),
+            ButtonEvent::LeftButtonRelease => (Gesture::Left, true),
+            ButtonEvent::RightButtonRelease => (Gesture::Right, true),
+            ButtonEvent::BothButtonsRelease => (Gesture::Both, true),
+            ButtonEvent::LeftButtonLongPress => (Gesture::LeftLong, true),
+            ButtonEvent::RightButtonLongPress => (Gesture::RightLong, true),
+            ButtonEvent::BothButtonsLongPress => (Gesture::BothLong, true),
+        };
+        if gesture.is_long() {
+            let action = self.action(gesture)?;
+            *long_pressed = true;
+            return Some(Input::Complete(action))
+        }
+        if complete {
+            if *long_pressed {
+                *long_pressed = false;
+                return None
+            }
+            return self.action(gesture).map(Input::Complete)
+        }
+        *long_pressed = false;
+        self.action(gesture).map(Input::Pending)
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        KeyMap::DEFAULT
+    }
+}
+
+impl InputMapper for KeyMap {
+    fn map(&self, event: Event) -> Option<Input> {
+        // Nothing outlives a single event
+        self.input(event, &mut false)
+    }
+}
+
+/// A `KeyMap` given to a widget, with what
+/// it has seen of the buttons held
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Keys {
+    pub map: KeyMap,
+    /// The buttons held gave a long press:
+    /// their release is part of it
+    long_pressed: bool,
+}
+
+impl Keys {
+    pub const fn new(map: KeyMap) -> Self {
+        Keys { map, long_pressed: false }
+    }
+
+    /// Input of `event`, see `KeyMap`
+    pub fn input(&mut self, event: Event) -> Option<Input> {
+        self.map.input(event, &mut self.long_pressed)
+    }
+
+    /// Forget the buttons held, when the
+    /// widget is entered
+    pub fn reset(&mut self) {
+        self.long_pressed = false;
+    }
+}
+
+/// Input of `event`, from `keys` if
+/// given, else from the installed mapper
+pub(crate) fn input_of(keys: &mut Option<Keys>, event: Event) -> Option<Input> {
+    match keys {
+        Some(keys) => keys.input(event),
+        None => event.input(),
+    }
+}
+
+/// Ticker events Previous or Next must be
+/// held before they start repeating
+pub const REPEAT_DELAY_TICKS: u8 = 5;
+/// Ticker events between the first repeats
+const REPEAT_START_INTERVAL: u8 = 3;
+/// Repeats after which the interval
+/// shortens by one tick
+const REPEAT_SPEEDUP: u8 = 4;
+
+/// Fast scrolling: while Previous or Next is
+/// held, it completes again at an accelerating
+/// rate driven by ticker events. Releasing
+/// after a repeat does not move once more.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AutoRepeat {
+    /// Action held, if Previous or Next
+    held: Option<Action>,
+    /// Ticker events until the next repeat
+    wait: u8,
+    /// Repeats since the action was pressed
+    count: u8,
+}
+
+impl AutoRepeat {
+    pub const fn new() -> Self {
+        AutoRepeat { held: None, wait: 0, count: 0 }
+    }
+
+    /// Input of `event` for a widget scrolling
+    /// with Previous and Next, ticker events of
+    /// a held action completing it again
+    pub fn input(&mut self, event: Event) -> Option<Input> {
+        self.input_with(event, &mut None)
+    }
+
+    /// `input`, with the actions `keys`
+    /// gives if any
+    pub fn input_with(&mut self, event: Event, keys: &mut Option<Keys>) -> Option<Input> {
+        if event == Event::Ticker {
+            let action = self.held?;
+            self.wait = self.wait.saturating_sub(1);
+            if self.wait > 0 {
+                return None
+            }
+            self.count = self.count.saturating_add(1);
+            let speedup = self.count / REPEAT_SPEEDUP;
+            self.wait = REPEAT_START_INTERVAL.saturating_sub(speedup).max(1);
+            return Some(Input::Complete(action))
+        }
+        let input = match input_of(keys, event) {
+            Some(input) => input,
+            None => {
+                // Pressing buttons giving no action
+                // still lets go of the one held
+                if let Some(ButtonEvent::LeftButtonPress | ButtonEvent::RightButtonPress
+                    | ButtonEvent::BothButtonsPress) = event.button() {
+                    self.held = None;
+                }
+                return None
+            }
+        };
+        match input {
+            Input::Pending(action @ (Action::Previous | Action::Next)) => {
+                *self = AutoRepeat { held: Some(action), wait: REPEAT_DELAY_TICKS, count: 0 };
+            }
+            Input::Pending(_) => self.held = None,
+            Input::Complete(action) => {
+                let repeated = self.held == Some(action) && self.count > 0;
+                *self = AutoRepeat::new();
+                if repeated {
+                    return None
+                }
+            }
+        }
+        Some(input)
+    }
+
+    /// Repeats since the action held was
+    /// pressed, to take bigger steps
+    pub const fn repeats(&self) -> u8 {
+        self.count
+    }
+
+    /// Completed action of `event`, as `input`
+    pub fn action(&mut self, event: Event) -> Option<Action> {
+        match self.input(event)? {
+            Input::Complete(action) => Some(action),
+            Input::Pending(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ButtonEvent::*;
+
+    const CANCEL_HELD: KeyMap = KeyMap::DEFAULT.cancel(Gesture::LeftLong);
+
+    fn input(keys: &mut Keys, button: ButtonEvent) -> Option<Input> {
+        keys.input(Event::Button(button))
+    }
+
+    #[test]
+    fn long_press_swallows_its_release() {
+        let mut keys = Keys::new(CANCEL_HELD);
+        assert_eq!(input(&mut keys, LeftButtonPress), Some(Input::Pending(Action::Previous)));
+        assert_eq!(input(&mut keys, LeftButtonLongPress), Some(Input::Complete(Action::Reject)));
+        assert_eq!(input(&mut keys, LeftButtonRelease), None);
+        // The next press starts afresh
+        input(&mut keys, LeftButtonPress);
+        assert_eq!(input(&mut keys, LeftButtonRelease), Some(Input::Complete(Action::Previous)));
+    }
+
+    #[test]
+    fn long_press_latch_is_per_widget() {
+        let (mut first, mut second) = (Keys::new(CANCEL_HELD), Keys::new(CANCEL_HELD));
+        input(&mut first, LeftButtonPress);
+        input(&mut first, LeftButtonLongPress);
+        assert_eq!(input(&mut second, LeftButtonRelease), Some(Input::Complete(Action::Previous)));
+
+        // Entering the widget again forgets it
+        first.reset();
+        assert_eq!(input(&mut first, LeftButtonRelease), Some(Input::Complete(Action::Previous)));
+    }
+
+    #[test]
+    fn installed_key_map_keeps_no_latch() {
+        let map = CANCEL_HELD;
+        assert_eq!(map.map(Event::Button(LeftButtonLongPress)), Some(Input::Complete(Action::Reject)));
+        assert_eq!(map.map(Event::Button(LeftButtonRelease)), Some(Input::Complete(Action::Previous)));
+    }
+
+    #[test]
+    fn hold_to_confirm() {
+        let mut keys = Keys::new(KeyMap::HOLD_TO_CONFIRM);
+        assert_eq!(input(&mut keys, BothButtonsPress), None);
+        assert_eq!(input(&mut keys, BothButtonsLongPress), Some(Input::Complete(Action::Confirm)));
+        assert_eq!(input(&mut keys, BothButtonsRelease), None);
+    }
+}
@@ -0,0 +1,200 @@
+//! On-device text entry with the two buttons:
+//! left and right cycle through the choices,
+//! both buttons accept the one shown. Choices
+//! are the characters of the alphabet, then
+//! "Delete" and "Done".
+//!
+//! ```ignore
+//! let word = TextEntry::<8>::new("Word #1").words(BIP39_WORDS).ask();
+//! ```
+//!
+//! Given a word list, only characters leading
+//! to a listed word are offered, a prefix
+//! matching a single word completes to it, and
+//! "Done" needs a whole word.
+
+use crate::bagls::*;
+use crate::input::{Action, Input};
+use crate::text::UiString;
+use crate::truncate::LINE_MAX_CHARS;
+use crate::ui::{Actions, WidgetState};
+use crate::widget::{run, Event, Widget};
+
+/// Characters offered unless
+/// another alphabet is given
+pub const DEFAULT_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz";
+/// Labels of the choices after the alphabet
+pub const DELETE_LABEL: &str = "Delete";
+pub const DONE_LABEL: &str = "Done";
+
+/// Entry of the choice list
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Choice {
+    Char(char),
+    Delete,
+    Done,
+}
+
+/// Text of up to `N` bytes composed
+/// character by character
+#[derive(Copy, Clone)]
+pub struct TextEntry<'a, const N: usize> {
+    title: &'a str,
+    alphabet: &'a str,
+    /// Valid results, if restricted
+    words: Option<&'a [&'a str]>,
+    text: UiString<N>,
+    /// Choice currently shown
+    index: usize,
+}
+
+impl<'a, const N: usize> TextEntry<'a, N> {
+    pub const fn new(title: &'a str) -> Self {
+        TextEntry { title, alphabet: DEFAULT_ALPHABET, words: None, text: UiString::new(), index: 0 }
+    }
+
+    pub const fn alphabet(self, alphabet: &'a str) -> Self {
+        TextEntry { alphabet, ..self }
+    }
+
+    /// Only accept the given words, e.g.
+    /// the BIP39 English word list
+    pub const fn words(self, words: &'a [&'a str]) -> Self {
+        TextEntry { words: Some(words), ..self }
+    }
+
+    pub fn text(&self) -> &str {
+        self.text.as_str()
+    }
+
+    /// Words starting with the text so far
+    fn matches(&self, prefix: &str) -> usize {
+        match self.words {
+            Some(words) => words.iter().filter(|w| w.starts_with(prefix)).count(),
+            None => 1,
+        }
+    }
+
+    /// Whether `c` may follow the text
+    fn accepts(&self, c: char) -> bool {
+        if self.text.len() + c.len_utf8() > N {
+            return false
+        }
+        let mut prefix = self.text;
+        let mut buf = [0u8; 4];
+        prefix.push_str(c.encode_utf8(&mut buf));
+        self.matches(prefix.as_str()) > 0
+    }
+
+    fn can_finish(&self) -> bool {
+        match self.words {
+            Some(words) => words.contains(&self.text.as_str()),
+            None => true,
+        }
+    }
+
+    /// Choice `index`, in display order
+    fn choice(&self, index: usize) -> Option<Choice> {
+        let chars = self.alphabet.chars().filter(|&c| self.accepts(c)).map(Choice::Char);
+        let specials = [
+            (!self.text.is_empty()).then_some(Choice::Delete),
+            self.can_finish().then_some(Choice::Done),
+        ];
+        chars.chain(specials.iter().flatten().copied()).nth(index)
+    }
+
+    fn choice_count(&self) -> usize {
+        let chars = self.alphabet.chars().filter(|&c| self.accepts(c)).count();
+        chars + !self.text.is_empty() as usize + self.can_finish() as usize
+    }
+
+    /// Apply `choice`, returning the text once done
+    fn select(&mut self, choice: Choice) -> Option<UiString<N>> {
+        match choice {
+            Choice::Char(c) => {
+                let mut buf = [0u8; 4];
+                self.text.push_str(c.encode_utf8(&mut buf));
+                self.complete();
+            }
+            Choice::Delete => {
+                self.text.pop();
+            }
+            Choice::Done => return Some(self.text),
+        }
+        self.index = 0;
+        None
+    }
+
+    /// Replace the text by the only word
+    /// it starts, if there is one
+    fn complete(&mut self) {
+        if let Some(words) = self.words {
+            let mut matching = words.iter().filter(|w| w.starts_with(self.text.as_str()));
+            if let (Some(word), None) = (matching.next(), matching.next()) {
+                if word.len() <= N {
+                    self.text = UiString::from(*word);
+                }
+            }
+        }
+    }
+
+    pub fn ask(&self) -> UiString<N> {
+        run(&mut { *self })
+    }
+}
+
+impl<const N: usize> Widget for TextEntry<'_, N> {
+    type Output = UiString<N>;
+
+    fn draw(&self) {
+        BLANK.paint();
+        // The end of the text, where characters are added
+        let text = self.text.as_str();
+        let skip = text.chars().count().saturating_sub(LINE_MAX_CHARS - 1);
+        let start = text.char_indices().nth(skip).map_or(text.len(), |(i, _)| i);
+        let mut line = UiString::<64>::from(if text.is_empty() { self.title } else { &text[start..] });
+        if !text.is_empty() {
+            line.push_str("_");
+        }
+        LabelLine::new().text(line.as_str()).pos(0, 12).paint();
+        let mut buf = [0u8; 4];
+        let label = match self.choice(self.index) {
+            Some(Choice::Char(c)) => &*c.encode_utf8(&mut buf),
+            Some(Choice::Delete) => DELETE_LABEL,
+            Some(Choice::Done) => DONE_LABEL,
+            None => "",
+        };
+        LabelLine::new().text(label).pos(0, 26).bold().paint();
+        LEFT_ARROW.paint();
+        RIGHT_ARROW.paint();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<UiString<N>> {
+        let count = self.choice_count();
+        match event.input()? {
+            Input::Pending(Action::Previous) => LEFT_S_ARROW.paint(),
+            Input::Pending(Action::Next) => RIGHT_S_ARROW.paint(),
+            Input::Complete(Action::Previous) => {
+                self.index = self.index.checked_sub(1).unwrap_or(count.saturating_sub(1));
+                self.draw();
+            }
+            Input::Complete(Action::Next) => {
+                self.index = if self.index + 1 < count { self.index + 1 } else { 0 };
+                self.draw();
+            }
+            Input::Complete(Action::Confirm) => {
+                let output = self.select(self.choice(self.index)?);
+                if output.is_none() {
+                    self.draw();
+                }
+                return output
+            }
+            _ => ()
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("TextEntry", self.index, self.choice_count()).actions(Actions::ALL)
+    }
+}
@@ -1,4 +1,78 @@
 #![no_std]
 
+#[cfg(any(feature = "sim", feature = "speculos"))]
+extern crate std;
+
+mod seph;
+
+/// Like `debug_assert!`, but with the `state-dump`
+/// feature the failure location is saved first
+macro_rules! widget_debug_assert {
+    ($cond:expr, $($arg:tt)+) => {
+        if cfg!(debug_assertions) && !$cond {
+            #[cfg(feature = "state-dump")]
+            crate::debug::capture(file!(), line!());
+            panic!($($arg)+);
+        }
+    };
+}
+
+pub mod about;
 pub mod bagls;
-pub mod ui;
\ No newline at end of file
+pub mod batch;
+pub mod bidi;
+pub mod blind;
+pub mod breadcrumb;
+pub mod buttons;
+pub mod canvas;
+#[cfg(feature = "c-flow")]
+pub mod cflow;
+pub mod context;
+pub mod currency;
+#[cfg(feature = "state-dump")]
+pub mod debug;
+pub mod describe;
+pub mod flow;
+pub mod fmt;
+pub mod future;
+pub mod glyph;
+pub mod hold;
+pub mod i18n;
+pub mod input;
+pub mod keyboard;
+#[cfg(feature = "nbgl")]
+pub mod nbgl;
+#[cfg(feature = "ragger")]
+pub mod nav;
+pub mod navigator;
+pub mod number;
+pub mod owned;
+pub mod picklist;
+pub mod pin;
+pub mod progress;
+pub mod qr;
+pub mod resume;
+pub mod runner;
+pub mod screen;
+pub mod screensaver;
+pub mod settings;
+pub mod style;
+pub mod sign;
+pub mod status;
+#[cfg(feature = "event-tap")]
+pub mod tap;
+pub mod text;
+pub mod toast;
+pub mod touch;
+pub mod truncate;
+pub mod tx;
+pub mod ui;
+pub mod widget;
+
+#[cfg(feature = "sim")]
+pub mod sim;
+#[cfg(feature = "speculos")]
+pub mod speculos;
+
+#[cfg(feature = "derive")]
+pub use nanos_ui_derive::{include_glyph, UiScreen};
@@ -0,0 +1,56 @@
+//! Navigation metadata for ragger based tests,
+//! enabled with the `ragger` feature.
+//!
+//! The last state reported by a widget is kept
+//! so that the app can send it back, typically
+//! in reply to a test-only APDU. The JSON names
+//! the available moves after ragger's `NavInsID`
+//! so that test code can feed them straight to
+//! `navigator.navigate()`:
+//!
+//! `{"flow":"Menu","page":1,"pages":3,"actions":["LEFT_CLICK","RIGHT_CLICK","BOTH_CLICK"]}`
+
+use core::fmt::{self, Write};
+
+use crate::ui::{Actions, WidgetState};
+
+static mut CURRENT: Option<WidgetState> = None;
+
+pub(crate) fn record(state: WidgetState) {
+    unsafe { CURRENT = Some(state) }
+}
+
+/// Last state reported by a widget
+pub fn current() -> Option<WidgetState> {
+    unsafe { CURRENT }
+}
+
+/// Ragger `NavInsID` names of the given actions
+pub fn instructions(actions: Actions) -> impl Iterator<Item = &'static str> {
+    [
+        (Actions::PREVIOUS, "LEFT_CLICK"),
+        (Actions::NEXT, "RIGHT_CLICK"),
+        (Actions::CONFIRM, "BOTH_CLICK"),
+    ]
+    .iter()
+    .filter(move |(a, _)| actions.contains(*a))
+    .map(|(_, name)| *name)
+}
+
+/// Write the current state as JSON, or `null`
+/// if no widget reported anything yet
+pub fn write_json(out: &mut dyn Write) -> fmt::Result {
+    let s = match current() {
+        Some(s) => s,
+        None => return out.write_str("null"),
+    };
+    write!(out, "{{\"flow\":\"{}\",\"page\":{},\"pages\":{},\"actions\":[",
+        s.widget, s.index, s.count)?;
+    for (i, name) in instructions(s.actions).enumerate() {
+        if i > 0 {
+            out.write_char(',')?;
+        }
+        write!(out, "\"{}\"", name)?;
+    }
+    out.write_str("]}")
+}
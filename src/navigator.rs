@@ -0,0 +1,118 @@
+//! Blocking navigation through the screens of
+//! an app, e.g. home, settings menu, submenu,
+//! review. The app names its screens with a
+//! route type and shows the one asked for; the
+//! navigator keeps the stack of routes, so that
+//! leaving a screen shows the one below again:
+//!
+//! ```ignore
+//! #[derive(Copy, Clone)]
+//! enum Route { Home, Settings, Review }
+//!
+//! impl Routes for App {
+//!     type Route = Route;
+//!     fn show(&mut self, route: Route) -> Transition<Route> {
+//!         match route {
+//!             Route::Home => match self.home.show() {
+//!                 MenuResult::Selected(0) => Transition::Push(Route::Settings),
+//!                 _ => Transition::Exit,
+//!             },
+//!             Route::Settings => { self.settings.show(); Transition::Pop }
+//!             Route::Review => { self.review.ask(); Transition::Pop }
+//!         }
+//!     }
+//! }
+//!
+//! Navigator::new(Route::Home).chrome(paint_status_line).run(&mut app);
+//! ```
+//!
+//! The chrome is painted over every screen
+//! shown meanwhile, see `status::set_chrome`.
+
+use crate::status;
+
+/// Most routes stacked in a `Navigator`
+pub const NAV_MAX_DEPTH: usize = 8;
+
+/// Where to go once a screen is done
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Transition<R> {
+    /// Show `R` over the current screen;
+    /// ignored if the stack is full
+    Push(R),
+    /// Back to the screen below, or out
+    /// of the navigator from the first one
+    Pop,
+    /// Show `R` in place of the current screen
+    Replace(R),
+    /// Show the current screen again
+    Stay,
+    /// Leave the navigator
+    Exit,
+}
+
+/// The screens of an app
+pub trait Routes {
+    type Route: Copy;
+
+    /// Show the screen of `route`
+    /// until the user is done with it
+    fn show(&mut self, route: Self::Route) -> Transition<Self::Route>;
+}
+
+pub struct Navigator<R> {
+    stack: [Option<R>; NAV_MAX_DEPTH],
+    len: usize,
+    chrome: Option<fn()>,
+}
+
+impl<R: Copy> Navigator<R> {
+    pub const fn new(home: R) -> Self {
+        let mut stack = [None; NAV_MAX_DEPTH];
+        stack[0] = Some(home);
+        Navigator { stack, len: 1, chrome: None }
+    }
+
+    /// Paint `chrome` over every screen,
+    /// e.g. a status line
+    pub const fn chrome(self, chrome: fn()) -> Self {
+        Navigator { chrome: Some(chrome), ..self }
+    }
+
+    /// Number of routes stacked
+    pub const fn depth(&self) -> usize {
+        self.len
+    }
+
+    /// Route of the screen shown
+    pub fn current(&self) -> Option<R> {
+        self.len.checked_sub(1).and_then(|top| self.stack[top])
+    }
+
+    /// Show the screens of `routes` until one
+    /// exits or the first one is popped
+    pub fn run(&mut self, routes: &mut impl Routes<Route = R>) {
+        let previous = status::chrome();
+        if self.chrome.is_some() {
+            status::set_chrome(self.chrome);
+        }
+        while let Some(route) = self.current() {
+            match routes.show(route) {
+                Transition::Push(next) => {
+                    if self.len < NAV_MAX_DEPTH {
+                        self.stack[self.len] = Some(next);
+                        self.len += 1;
+                    }
+                }
+                Transition::Pop => {
+                    self.len -= 1;
+                    self.stack[self.len] = None;
+                }
+                Transition::Replace(next) => self.stack[self.len - 1] = Some(next),
+                Transition::Stay => (),
+                Transition::Exit => break,
+            }
+        }
+        status::set_chrome(previous);
+    }
+}
@@ -0,0 +1,310 @@
+//! Backend for the touch screen devices (Stax,
+//! Flex), enabled with the `nbgl` feature. The
+//! blocking methods of the widgets then show the
+//! matching NBGL use case of the C SDK instead
+//! of drawing BAGL elements:
+//!
+//! - `Validator::ask`: `nbgl_useCaseChoice`
+//! - `FieldsReview::ask`, `tx::review`: `nbgl_useCaseReview`
+//! - `HomeScreen::show`: `nbgl_useCaseHome`, its
+//!   settings button selecting the first entry
+//! - `Menu::show`: a list of touchable bars
+//!
+//! App code stays the same across the device
+//! line. The app must link the C SDK's NBGL
+//! library and provide `io_event`, as for
+//! `c-flow`. NBGL structures are declared as
+//! in the SDK, built with `-fshort-enums`.
+//!
+//! ```ignore
+//! unsafe { nbgl::set_app_icon(&C_app_icon_64px as *const _ as *const c_void) };
+//! let outcome = FieldsReview::new(&fields).ask();
+//! ```
+
+use core::ffi::{c_char, c_void};
+use core::ptr::null;
+
+use crate::context::UiContext;
+use crate::i18n::{tr, Text};
+use crate::seph;
+use crate::ui::MenuResult;
+use crate::widget::Outcome;
+
+/// Size of the C SDK's SEPH buffer
+const SPI_BUFFER_LEN: usize = 128;
+
+/// Most fields of an NBGL review
+pub const MAX_FIELDS: usize = 10;
+/// Most entries of an NBGL menu
+pub const MAX_ENTRIES: usize = 8;
+/// Longest field name, in bytes
+pub const NAME_LEN: usize = 32;
+/// Longest field value, in bytes
+pub const VALUE_LEN: usize = 128;
+/// Longest other text, in bytes
+const TEXT_LEN: usize = 64;
+
+/// `TYPE_TRANSACTION` of `nbgl_operationType_t`
+const TYPE_TRANSACTION: u8 = 0;
+
+#[repr(C)]
+struct TagValue {
+    item: *const c_char,
+    value: *const c_char,
+    value_icon: *const c_void,
+    /// `centeredInfo`, `aliasValue`... bits
+    flags: u8,
+}
+
+#[repr(C)]
+struct TagValueList {
+    pairs: *const TagValue,
+    callback: *const c_void,
+    nb_pairs: u8,
+    start_index: u8,
+    nb_max_lines_for_value: u8,
+    token: u8,
+    small_case_for_value: bool,
+    wrapping: bool,
+}
+
+#[repr(C)]
+struct TickerConfiguration {
+    callback: Option<extern "C" fn()>,
+    value: u32,
+    interval: u32,
+}
+
+#[repr(C)]
+struct LayoutDescription {
+    modal: bool,
+    with_left_border: bool,
+    tap_action_text: *const c_char,
+    tap_action_token: u8,
+    tap_tune_id: u8,
+    on_action_callback: Option<extern "C" fn(token: i32, index: u8)>,
+    ticker: TickerConfiguration,
+}
+
+#[repr(C)]
+struct LayoutBar {
+    icon_left: *const c_void,
+    text: *const c_char,
+    icon_right: *const c_void,
+    sub_text: *const c_char,
+    large: bool,
+    token: u8,
+    inactive: bool,
+    tune_id: u8,
+}
+
+extern "C" {
+    fn nbgl_useCaseChoice(icon: *const c_void, message: *const c_char, sub_message: *const c_char,
+        confirm_text: *const c_char, reject_text: *const c_char, callback: extern "C" fn(bool));
+    fn nbgl_useCaseReview(operation_type: u8, tag_value_list: *const TagValueList,
+        icon: *const c_void, review_title: *const c_char, review_sub_title: *const c_char,
+        finish_title: *const c_char, callback: extern "C" fn(bool));
+    fn nbgl_useCaseHome(app_name: *const c_char, app_icon: *const c_void, tagline: *const c_char,
+        with_settings: bool, top_right_callback: extern "C" fn(), quit_callback: extern "C" fn());
+    fn nbgl_layoutGet(description: *const LayoutDescription) -> *mut c_void;
+    fn nbgl_layoutAddTouchableBar(layout: *mut c_void, bar: *const LayoutBar) -> i32;
+    fn nbgl_layoutDraw(layout: *mut c_void) -> i32;
+    fn nbgl_layoutRelease(layout: *mut c_void) -> i32;
+    fn nbgl_refresh();
+    /// The app's SEPH event handler, which
+    /// dispatches events to NBGL
+    fn io_event(channel: u8) -> u8;
+    static mut G_io_seproxyhal_spi_buffer: [u8; SPI_BUFFER_LEN];
+}
+
+/// Icon of the app, as an `nbgl_icon_details_t`
+static mut APP_ICON: *const c_void = null();
+
+/// Value given by the use case callback
+/// once the user is done
+static mut DONE: Option<u32> = None;
+
+/// Use `icon`, an `nbgl_icon_details_t` of the
+/// app, on the home screen and in reviews
+///
+/// # Safety
+/// `icon` must be null or point to a valid
+/// icon that lives as long as the app.
+pub unsafe fn set_app_icon(icon: *const c_void) {
+    APP_ICON = icon;
+}
+
+fn app_icon() -> *const c_void {
+    unsafe { APP_ICON }
+}
+
+/// NUL terminated copy of a string, cut at
+/// a character boundary if too long
+struct CText<const N: usize> {
+    buf: [u8; N],
+}
+
+impl<const N: usize> CText<N> {
+    fn new(s: &str) -> Self {
+        let mut buf = [0u8; N];
+        let mut len = s.len().min(N - 1);
+        while !s.is_char_boundary(len) {
+            len -= 1;
+        }
+        buf[..len].copy_from_slice(&s.as_bytes()[..len]);
+        CText { buf }
+    }
+
+    fn as_ptr(&self) -> *const c_char {
+        self.buf.as_ptr() as *const c_char
+    }
+}
+
+extern "C" fn on_choice(confirm: bool) {
+    unsafe { DONE = Some(confirm as u32) }
+}
+
+extern "C" fn on_settings() {
+    unsafe { DONE = Some(1) }
+}
+
+extern "C" fn on_quit() {
+    unsafe { DONE = Some(0) }
+}
+
+extern "C" fn on_touch(token: i32, _index: u8) {
+    unsafe { DONE = Some(token as u32) }
+}
+
+/// Process events until the use case
+/// ends, then give the display back
+fn wait() -> u32 {
+    let result = loop {
+        if let Some(result) = unsafe { DONE } {
+            break result
+        }
+        if !seph::is_status_sent() {
+            seph::send_general_status();
+        }
+        unsafe {
+            let buffer = &mut *core::ptr::addr_of_mut!(G_io_seproxyhal_spi_buffer);
+            seph::seph_recv(buffer, 0);
+            io_event(0);
+        }
+    };
+    if let Some(widget) = UiContext::current() {
+        widget.redraw();
+    }
+    result
+}
+
+/// Ask to confirm `message`, as `Validator` does
+pub fn choice(message: &str) -> Outcome {
+    let message = CText::<TEXT_LEN>::new(message);
+    let confirm = CText::<TEXT_LEN>::new(tr(Text::Approve));
+    let reject = CText::<TEXT_LEN>::new(tr(Text::Cancel));
+    unsafe {
+        DONE = None;
+        nbgl_useCaseChoice(app_icon(), message.as_ptr(), null(), confirm.as_ptr(),
+            reject.as_ptr(), on_choice);
+    }
+    (wait() == 1).into()
+}
+
+/// Review `fields`, introduced by `intro`.
+/// Fields past `MAX_FIELDS` are not shown.
+pub fn review(fields: &[(&str, &str)], intro: [&str; 2]) -> Outcome {
+    let fields = &fields[..fields.len().min(MAX_FIELDS)];
+    let mut names = [(); MAX_FIELDS].map(|_| CText::<NAME_LEN>::new(""));
+    let mut values = [(); MAX_FIELDS].map(|_| CText::<VALUE_LEN>::new(""));
+    for (i, (name, value)) in fields.iter().enumerate() {
+        names[i] = CText::new(name);
+        values[i] = CText::new(value);
+    }
+    let mut i = 0;
+    let pairs = [(); MAX_FIELDS].map(|_| {
+        let pair = TagValue { item: names[i].as_ptr(), value: values[i].as_ptr(), value_icon: null(), flags: 0 };
+        i += 1;
+        pair
+    });
+    let list = TagValueList {
+        pairs: pairs.as_ptr(),
+        callback: null(),
+        nb_pairs: fields.len() as u8,
+        start_index: 0,
+        nb_max_lines_for_value: 0,
+        token: 0,
+        small_case_for_value: false,
+        wrapping: false,
+    };
+    let title = CText::<TEXT_LEN>::new(intro[0]);
+    let sub_title = CText::<TEXT_LEN>::new(intro[1]);
+    let finish = CText::<TEXT_LEN>::new(tr(Text::Approve));
+    unsafe {
+        DONE = None;
+        nbgl_useCaseReview(TYPE_TRANSACTION, &list, app_icon(), title.as_ptr(), sub_title.as_ptr(),
+            finish.as_ptr(), on_choice);
+    }
+    (wait() == 1).into()
+}
+
+/// Home screen of the app. Returns whether
+/// the settings button was touched; false
+/// means the user quit.
+pub fn home(name: &str, tagline: &str, with_settings: bool) -> bool {
+    let name = CText::<TEXT_LEN>::new(name);
+    let tagline = CText::<TEXT_LEN>::new(tagline);
+    unsafe {
+        DONE = None;
+        nbgl_useCaseHome(name.as_ptr(), app_icon(), tagline.as_ptr(), with_settings,
+            on_settings, on_quit);
+    }
+    wait() == 1
+}
+
+/// Menu of `entries`, one touchable bar each.
+/// `back` is the index of the "Back" entry,
+/// if any. Entries past `MAX_ENTRIES` are
+/// not shown.
+pub fn menu(entries: &[&str], back: Option<usize>) -> MenuResult {
+    let entries = &entries[..entries.len().min(MAX_ENTRIES)];
+    let description = LayoutDescription {
+        modal: false,
+        with_left_border: false,
+        tap_action_text: null(),
+        tap_action_token: 0,
+        tap_tune_id: 0,
+        on_action_callback: Some(on_touch),
+        ticker: TickerConfiguration { callback: None, value: 0, interval: 0 },
+    };
+    // The layout keeps pointers to the texts
+    let mut texts = [(); MAX_ENTRIES].map(|_| CText::<TEXT_LEN>::new(""));
+    for (text, entry) in texts.iter_mut().zip(entries) {
+        *text = CText::new(entry);
+    }
+    unsafe {
+        DONE = None;
+        let layout = nbgl_layoutGet(&description);
+        for (i, text) in texts[..entries.len()].iter().enumerate() {
+            let bar = LayoutBar {
+                icon_left: null(),
+                text: text.as_ptr(),
+                icon_right: null(),
+                sub_text: null(),
+                large: false,
+                token: i as u8,
+                inactive: false,
+                tune_id: 0,
+            };
+            nbgl_layoutAddTouchableBar(layout, &bar);
+        }
+        nbgl_layoutDraw(layout);
+        nbgl_refresh();
+        let selected = wait() as usize;
+        nbgl_layoutRelease(layout);
+        match back {
+            Some(back) if selected == back => MenuResult::Back,
+            _ => MenuResult::Selected(selected),
+        }
+    }
+}
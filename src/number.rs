@@ -0,0 +1,146 @@
+//! Editing a number on the device, e.g. a fee
+//! rate, a slippage or a timeout:
+//!
+//! ```ignore
+//! let slippage = NumberPicker::new(50, 0, 500).step(5).decimals(2).unit("%")
+//!     .title("Slippage").ask();
+//! ```
+//!
+//! Left and right decrease and increase the
+//! value, by larger steps while held, and
+//! both buttons confirm it.
+
+use crate::bagls::*;
+use crate::fmt::format_amount;
+use crate::input::{Action, AutoRepeat, Input};
+use crate::screen;
+use crate::ui::{Actions, WidgetState};
+use crate::widget::{run, Event, Widget};
+
+/// Repeats of a held button after which each
+/// one moves by 10 steps, then by 100 steps
+pub const NUMBER_FAST_REPEATS: u8 = 10;
+pub const NUMBER_FASTER_REPEATS: u8 = 20;
+
+#[derive(Copy, Clone)]
+pub struct NumberPicker<'a> {
+    value: u64,
+    min: u64,
+    max: u64,
+    step: u64,
+    /// Shown as a fixed-point amount
+    /// with this many decimals
+    decimals: u8,
+    unit: &'a str,
+    title: Option<&'a str>,
+    repeat: AutoRepeat,
+}
+
+impl<'a> NumberPicker<'a> {
+    /// Pick a value between `min` and `max`
+    /// included, starting from `value`
+    pub const fn new(value: u64, min: u64, max: u64) -> Self {
+        let max = if max < min { min } else { max };
+        let value = if value < min { min } else if value > max { max } else { value };
+        NumberPicker { value, min, max, step: 1, decimals: 0, unit: "", title: None,
+            repeat: AutoRepeat::new() }
+    }
+
+    /// Move by `step`, at least 1
+    pub const fn step(self, step: u64) -> Self {
+        let step = if step == 0 { 1 } else { step };
+        NumberPicker { step, ..self }
+    }
+
+    /// Show the value divided by 10^`decimals`,
+    /// e.g. 50 with 2 decimals as "0.5"
+    pub const fn decimals(self, decimals: u8) -> Self {
+        NumberPicker { decimals, ..self }
+    }
+
+    /// Shown after the value
+    pub const fn unit(self, unit: &'a str) -> Self {
+        NumberPicker { unit, ..self }
+    }
+
+    /// Bold line above the value
+    pub const fn title(self, title: &'a str) -> Self {
+        NumberPicker { title: Some(title), ..self }
+    }
+
+    pub const fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Let the user pick the value: None
+    /// if the input backend rejected it
+    pub fn ask(&self) -> Option<u64> {
+        run(&mut { *self })
+    }
+
+    /// Move the value by one step, or
+    /// more while the button is held
+    fn nudge(&mut self, up: bool) {
+        let steps = match self.repeat.repeats() {
+            n if n >= NUMBER_FASTER_REPEATS => 100,
+            n if n >= NUMBER_FAST_REPEATS => 10,
+            _ => 1,
+        };
+        let delta = self.step.saturating_mul(steps);
+        self.value = match up {
+            true => self.value.saturating_add(delta).min(self.max),
+            false => self.value.saturating_sub(delta).max(self.min),
+        };
+    }
+}
+
+impl Widget for NumberPicker<'_> {
+    type Output = Option<u64>;
+
+    fn draw(&self) {
+        let screen = screen::current();
+        let mut buf = [0u8; 40];
+        let text = format_amount(self.value, self.decimals, self.unit, &mut buf).unwrap_or("");
+        let y = match self.title {
+            Some(title) => {
+                LabelLine::new().text(title).pos(0, screen.y(12)).bold().display();
+                26
+            }
+            None => {
+                crate::screen::clear();
+                20
+            }
+        };
+        LabelLine::new().dims(104, 11).pos(12, screen.y(y)).text(text).paint();
+        if self.value > self.min {
+            screen.place(LEFT_ARROW).paint();
+        }
+        if self.value < self.max {
+            screen.place(RIGHT_ARROW).paint();
+        }
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Option<u64>> {
+        match self.repeat.input(event)? {
+            Input::Complete(Action::Previous) => {
+                self.nudge(false);
+                self.draw();
+            }
+            Input::Complete(Action::Next) => {
+                self.nudge(true);
+                self.draw();
+            }
+            Input::Complete(Action::Confirm) => return Some(Some(self.value)),
+            Input::Complete(Action::Reject) => return Some(None),
+            Input::Pending(_) => (),
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        let actions = Actions::PREVIOUS.when(self.value > self.min)
+            .with(Actions::NEXT.when(self.value < self.max)).with(Actions::CONFIRM);
+        WidgetState::new("NumberPicker", (self.value - self.min) as usize,
+            (self.max - self.min) as usize + 1).actions(actions)
+    }
+}
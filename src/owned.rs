@@ -0,0 +1,282 @@
+//! Widgets owning their text in fixed-capacity
+//! buffers rather than borrowing it, so screens
+//! built from APDU data can be kept in a `static`
+//! between iterations of the app's event loop:
+//!
+//! ```ignore
+//! static mut REVIEW: OwnedReview<4, 32> = OwnedReview::new();
+//! // when a chunk arrives
+//! unsafe { REVIEW.push(parsed_field) };
+//! // once the data is complete
+//! let outcome = unsafe { REVIEW.ask() };
+//! ```
+//!
+//! Text longer than a buffer is cut, ending
+//! with `TRUNCATION_MARKER`.
+
+use core::fmt::{self, Write};
+
+use crate::i18n::{tr, Text};
+use crate::input::AutoRepeat;
+use crate::text::UiString;
+use crate::ui::{Menu, MenuResult, MessageValidator, SingleMessage, WidgetState};
+use crate::widget::{run, Event, Outcome, Widget};
+
+/// `SingleMessage` holding up to `L` bytes
+#[derive(Copy, Clone, Default)]
+pub struct OwnedMessage<const L: usize> {
+    message: UiString<L>,
+}
+
+impl<const L: usize> OwnedMessage<L> {
+    pub const fn new() -> Self {
+        OwnedMessage { message: UiString::new() }
+    }
+
+    pub fn set(&mut self, message: &str) {
+        self.message = UiString::from(message);
+    }
+
+    pub fn message(&self) -> &str {
+        self.message.as_str()
+    }
+
+    fn widget(&self) -> SingleMessage<'_> {
+        SingleMessage::new(self.message.as_str())
+    }
+
+    pub fn show(&self) {
+        self.widget().show();
+    }
+
+    pub fn show_and_wait(&self) {
+        run(&mut { *self })
+    }
+}
+
+impl<const L: usize> Widget for OwnedMessage<L> {
+    type Output = ();
+
+    fn draw(&self) {
+        self.widget().draw();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<()> {
+        self.widget().handle(event)
+    }
+
+    fn state(&self) -> WidgetState {
+        self.widget().state()
+    }
+
+    fn on_idle(&mut self) -> Option<()> {
+        Some(())
+    }
+}
+
+/// Strings of `entries` as a slice
+fn refs<const N: usize, const L: usize>(entries: &[UiString<L>; N], len: usize) -> [&str; N] {
+    let mut refs = [""; N];
+    for (r, e) in refs.iter_mut().zip(&entries[..len]) {
+        *r = e.as_str();
+    }
+    refs
+}
+
+/// `Menu` of up to `N` entries of up to `L` bytes
+#[derive(Copy, Clone)]
+pub struct OwnedMenu<const N: usize, const L: usize> {
+    entries: [UiString<L>; N],
+    len: usize,
+    /// Selected entry
+    index: usize,
+    /// Ticks both buttons have been held for
+    held: Option<u8>,
+    repeat: AutoRepeat,
+}
+
+impl<const N: usize, const L: usize> OwnedMenu<N, L> {
+    pub const fn new() -> Self {
+        OwnedMenu { entries: [UiString::new(); N], len: 0, index: 0, held: None,
+            repeat: AutoRepeat::new() }
+    }
+
+    /// Append an entry. Returns false if
+    /// the menu is full.
+    pub fn push(&mut self, entry: &str) -> bool {
+        if self.len == N {
+            return false
+        }
+        self.entries[self.len] = UiString::from(entry);
+        self.len += 1;
+        true
+    }
+
+    /// Append an entry computed at runtime:
+    /// `menu.push_fmt(format_args!("Account {}", i))`.
+    /// Returns false if the menu is full.
+    pub fn push_fmt(&mut self, args: fmt::Arguments) -> bool {
+        if self.len == N {
+            return false
+        }
+        let mut entry = UiString::new();
+        // Overflow only truncates
+        let _ = entry.write_fmt(args);
+        self.entries[self.len] = entry;
+        self.len += 1;
+        true
+    }
+
+    /// Replace entry `index`, if it exists
+    pub fn set(&mut self, index: usize, entry: &str) {
+        if index < self.len {
+            self.entries[index] = UiString::from(entry);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.index = 0;
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Show the menu, returning the selected entry
+    pub fn show(&self) -> MenuResult {
+        run(&mut { *self })
+    }
+}
+
+impl<const N: usize, const L: usize> Default for OwnedMenu<N, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const L: usize> Widget for OwnedMenu<N, L> {
+    type Output = MenuResult;
+
+    fn draw(&self) {
+        let refs = refs(&self.entries, self.len);
+        Menu { index: self.index, ..Menu::new(&refs[..self.len]) }.draw();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<MenuResult> {
+        let refs = refs(&self.entries, self.len);
+        let mut menu = Menu { index: self.index, held: self.held, repeat: self.repeat,
+            ..Menu::new(&refs[..self.len]) };
+        let output = menu.handle(event);
+        self.index = menu.index;
+        self.held = menu.held;
+        self.repeat = menu.repeat;
+        output
+    }
+
+    fn state(&self) -> WidgetState {
+        let refs = refs(&self.entries, self.len);
+        Menu { index: self.index, ..Menu::new(&refs[..self.len]) }.state()
+    }
+}
+
+/// Longest entry of a `StaticMenu`, in bytes
+pub const MENU_ENTRY_LEN: usize = 24;
+
+/// Menu of up to `N` short entries, for labels
+/// computed at runtime such as account names:
+///
+/// ```ignore
+/// let mut accounts = StaticMenu::<5>::new();
+/// for i in 0..5 {
+///     accounts.push_fmt(format_args!("Account {}", i));
+/// }
+/// if let MenuResult::Selected(account) = accounts.show() { .. }
+/// ```
+pub type StaticMenu<const N: usize> = OwnedMenu<N, MENU_ENTRY_LEN>;
+
+/// `MessageValidator` over up to `N` pages of
+/// up to `L` bytes, ending with the translated
+/// "Approve" and "Reject" pages
+#[derive(Copy, Clone)]
+pub struct OwnedReview<const N: usize, const L: usize> {
+    pages: [UiString<L>; N],
+    len: usize,
+    /// Page currently shown
+    page: usize,
+}
+
+impl<const N: usize, const L: usize> OwnedReview<N, L> {
+    pub const fn new() -> Self {
+        OwnedReview { pages: [UiString::new(); N], len: 0, page: 0 }
+    }
+
+    /// Append a page. Returns false if
+    /// the review is full.
+    pub fn push(&mut self, page: &str) -> bool {
+        if self.len == N {
+            return false
+        }
+        self.pages[self.len] = UiString::from(page);
+        self.len += 1;
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.page = 0;
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn ask(&self) -> Outcome {
+        run(&mut { *self })
+    }
+
+    fn with_validator<R>(&self, f: impl FnOnce(&mut MessageValidator) -> R) -> R {
+        let refs = refs(&self.pages, self.len);
+        let (confirm, cancel) = ([tr(Text::Approve)], [tr(Text::Reject)]);
+        let mut validator = MessageValidator {
+            page: self.page,
+            ..MessageValidator::new(&refs[..self.len], &confirm, &cancel)
+        };
+        f(&mut validator)
+    }
+}
+
+impl<const N: usize, const L: usize> Default for OwnedReview<N, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize, const L: usize> Widget for OwnedReview<N, L> {
+    type Output = Outcome;
+
+    fn draw(&self) {
+        self.with_validator(|v| v.draw());
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Outcome> {
+        let (output, page) = self.with_validator(|v| {
+            let output = v.handle(event);
+            (output, v.page)
+        });
+        self.page = page;
+        output
+    }
+
+    fn state(&self) -> WidgetState {
+        self.with_validator(|v| v.state())
+    }
+}
@@ -0,0 +1,145 @@
+//! A list of entries to check, e.g. the
+//! networks or tokens shown by the app:
+//!
+//! ```ignore
+//! let shown = PickList::new(&["Mainnet", "Testnet", "Regtest"]).selected(0b001).ask();
+//! if shown & 0b010 != 0 { .. }
+//! ```
+//!
+//! Entries are shown one at a time with their
+//! marker, "[x]" or "[ ]", left and right move
+//! between them and both buttons toggle the one
+//! shown. The last entry, "Done", gives the
+//! checked entries as a bitmap. In radio mode,
+//! checking an entry unchecks the others.
+
+use crate::bagls::*;
+use crate::flow::Pager;
+use crate::i18n::{tr, Text};
+use crate::input::{Action, AutoRepeat, Input};
+use crate::screen;
+use crate::text::UiString;
+use crate::truncate::{fit, LINE_BUF_LEN};
+use crate::ui::{Actions, WidgetState};
+use crate::widget::{run, Event, Widget};
+
+/// Most entries of a `PickList`,
+/// one bit each in the selection
+pub const PICK_MAX_ENTRIES: usize = 32;
+
+#[derive(Copy, Clone)]
+pub struct PickList<'a> {
+    entries: &'a [&'a str],
+    /// Bit `i` set if entry `i` is checked
+    selection: u32,
+    /// At most one entry checked
+    radio: bool,
+    /// Entry shown, `len` for "Done"
+    index: usize,
+    repeat: AutoRepeat,
+}
+
+impl<'a> PickList<'a> {
+    /// Entries past `PICK_MAX_ENTRIES`
+    /// are left out
+    pub const fn new(entries: &'a [&'a str]) -> Self {
+        PickList { entries, selection: 0, radio: false, index: 0, repeat: AutoRepeat::new() }
+    }
+
+    /// Entries checked at first
+    pub const fn selected(self, selection: u32) -> Self {
+        PickList { selection, ..self }
+    }
+
+    /// Check at most one entry,
+    /// marked "(*)" or "( )"
+    pub const fn radio(self) -> Self {
+        PickList { radio: true, ..self }
+    }
+
+    /// Number of entries, "Done" excluded
+    pub const fn len(&self) -> usize {
+        if self.entries.len() > PICK_MAX_ENTRIES { PICK_MAX_ENTRIES } else { self.entries.len() }
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub const fn is_checked(&self, index: usize) -> bool {
+        index < PICK_MAX_ENTRIES && self.selection & (1 << index) != 0
+    }
+
+    /// Let the user check entries until "Done",
+    /// returning the checked ones
+    pub fn ask(&self) -> u32 {
+        run(&mut { *self })
+    }
+
+    fn toggle(&mut self, index: usize) {
+        let bit = 1 << index;
+        self.selection = match self.radio {
+            true => bit,
+            false => self.selection ^ bit,
+        };
+    }
+
+    fn pager(&self) -> Pager {
+        Pager::new(self.index, self.len() + 1)
+    }
+}
+
+impl Widget for PickList<'_> {
+    type Output = u32;
+
+    fn draw(&self) {
+        let screen = screen::current();
+        let mut line = UiString::<LINE_BUF_LEN>::new();
+        let mut buf = [0u8; LINE_BUF_LEN];
+        let text = match self.entries.get(self.index).filter(|_| self.index < self.len()) {
+            Some(entry) => {
+                let marker = match (self.radio, self.is_checked(self.index)) {
+                    (false, true) => "[x] ",
+                    (false, false) => "[ ] ",
+                    (true, true) => "(*) ",
+                    (true, false) => "( ) ",
+                };
+                line.push_str(marker);
+                line.push_str(entry);
+                fit(line.as_str(), None, &mut buf)
+            }
+            None => tr(Text::Done),
+        };
+        LabelLine::new().dims(104, 11).pos(12, screen.y(20)).text(text).display();
+        self.pager().paint_arrows();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<u32> {
+        match self.repeat.input(event)? {
+            Input::Pending(Action::Previous) => {
+                screen::current().place(LEFT_S_ARROW).paint();
+            }
+            Input::Pending(Action::Next) => {
+                screen::current().place(RIGHT_S_ARROW).paint();
+            }
+            Input::Complete(action @ (Action::Previous | Action::Next)) => {
+                if let Some(index) = self.pager().turn(action) {
+                    self.index = index;
+                }
+                self.draw();
+            }
+            Input::Complete(Action::Confirm) if self.index < self.len() => {
+                self.toggle(self.index);
+                self.draw();
+            }
+            Input::Complete(Action::Confirm | Action::Reject) => return Some(self.selection),
+            Input::Pending(_) => (),
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("PickList", self.index, self.len() + 1)
+            .actions(self.pager().actions().with(Actions::CONFIRM))
+    }
+}
@@ -0,0 +1,40 @@
+//! Re-validation of the device PIN before
+//! high-risk screens, e.g. a flow step marked
+//! with `Step::require_pin`.
+//!
+//! The crate does not call the OS itself: the
+//! app installs a validator wrapping whatever
+//! its SDK exposes (`os_ux` with
+//! `BOLOS_UX_VALIDATE_PIN` in the C SDK):
+//!
+//! ```ignore
+//! pin::set_validator(|| unsafe { validate_pin_with_os() });
+//! ```
+
+static mut VALIDATOR: Option<fn() -> bool> = None;
+
+/// Use `validator` to ask for the PIN. It
+/// returns whether the user entered it.
+pub fn set_validator(validator: fn() -> bool) {
+    unsafe { VALIDATOR = Some(validator) }
+}
+
+fn validator() -> Option<fn() -> bool> {
+    unsafe { VALIDATOR }
+}
+
+/// Whether a validator was installed
+pub fn available() -> bool {
+    validator().is_some()
+}
+
+/// Ask for the PIN. Without a validator this
+/// fails, so gated screens stay out of reach
+/// rather than silently ungated.
+pub fn validate() -> bool {
+    widget_debug_assert!(available(), "a screen requires the PIN but no validator is set");
+    match validator() {
+        Some(validator) => validator(),
+        None => false,
+    }
+}
@@ -0,0 +1,274 @@
+//! Progress page for long operations such as
+//! hashing a payload streamed over many APDUs.
+//!
+//! The app reports how many bytes it processed;
+//! the page repaints only when the bar moves, so
+//! reporting after every chunk is cheap:
+//!
+//! ```ignore
+//! let mut progress = Progress::new(total_len);
+//! progress.display();
+//! // for each chunk
+//! hasher.update(chunk);
+//! if !progress.advance(chunk.len()) {
+//!     return Err(StatusWords::UserCancelled)
+//! }
+//! // for each button event received meanwhile
+//! progress.handle(event);
+//! ```
+//!
+//! Apps tracking their own percentage paint
+//! a `ProgressBar` instead, and those that
+//! cannot tell their progress a `Spinner`.
+
+use crate::bagls::*;
+use crate::fmt::format_percent;
+use crate::i18n::{tr, Text};
+use crate::input::Action;
+use crate::widget::Event;
+
+/// Position and width of the bar
+const BAR_X: i16 = 14;
+const BAR_Y: i16 = 18;
+const BAR_WIDTH: u16 = 100;
+
+pub struct Progress<'a> {
+    title: Option<&'a str>,
+    total: usize,
+    done: usize,
+    /// Bar width last painted
+    shown: u16,
+    cancelled: bool,
+}
+
+impl<'a> Progress<'a> {
+    /// Progress over `total` bytes, titled
+    /// with the translated "Processing"
+    pub const fn new(total: usize) -> Self {
+        Progress { title: None, total, done: 0, shown: 0, cancelled: false }
+    }
+
+    pub const fn title(self, title: &'a str) -> Self {
+        Progress { title: Some(title), ..self }
+    }
+
+    /// Bytes processed so far
+    pub const fn done(&self) -> usize {
+        self.done
+    }
+
+    /// Whether the user cancelled
+    pub const fn cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    /// Record that `done` bytes out of the total
+    /// were processed. Returns false once the user
+    /// cancelled, so the app can stop hashing.
+    pub fn update(&mut self, done: usize) -> bool {
+        self.done = done.min(self.total);
+        let width = self.bar_width();
+        if width != self.shown {
+            self.shown = width;
+            self.paint();
+        }
+        !self.cancelled
+    }
+
+    /// Record `n` more processed bytes
+    pub fn advance(&mut self, n: usize) -> bool {
+        self.update(self.done.saturating_add(n))
+    }
+
+    /// Feed an event received while processing:
+    /// confirming or rejecting cancels
+    pub fn handle(&mut self, event: Event) {
+        if let Some(Action::Confirm | Action::Reject) = event.action() {
+            self.cancelled = true;
+        }
+    }
+
+    fn bar_width(&self) -> u16 {
+        if self.total == 0 {
+            return BAR_WIDTH
+        }
+        (self.done as u64 * BAR_WIDTH as u64 / self.total as u64) as u16
+    }
+}
+
+impl Displayable for Progress<'_> {
+    fn paint(&self) {
+        let title = self.title.unwrap_or_else(|| tr(Text::Processing));
+        let percent = (self.bar_width() as u32 * 100 / BAR_WIDTH as u32) as u8;
+        ProgressBar { label: Some(title), percent }.paint();
+        let mut buf = [0u8; 8];
+        if let Some(percent) = format_percent(self.done as u64, self.total.max(1) as u64, 0, &mut buf) {
+            LabelLine::new().text(percent).pos(0, 30).paint();
+        }
+    }
+}
+
+/// Bar filled to a percentage, under an
+/// optional label. It paints right away, without
+/// an event loop, e.g. between two APDU chunks:
+///
+/// ```ignore
+/// let mut bar = ProgressBar::new().label("Hashing");
+/// bar.paint();
+/// // after each chunk
+/// bar.set(percent);
+/// ```
+#[derive(Copy, Clone)]
+pub struct ProgressBar<'a> {
+    label: Option<&'a str>,
+    percent: u8,
+}
+
+impl<'a> ProgressBar<'a> {
+    pub const fn new() -> Self {
+        ProgressBar { label: None, percent: 0 }
+    }
+
+    pub const fn label(self, label: &'a str) -> Self {
+        ProgressBar { label: Some(label), ..self }
+    }
+
+    pub const fn percent(&self) -> u8 {
+        self.percent
+    }
+
+    /// Fill the bar to `percent`, at most 100,
+    /// repainting only if it changed
+    pub fn set(&mut self, percent: u8) {
+        let percent = percent.min(100);
+        if percent != self.percent {
+            self.percent = percent;
+            self.paint();
+        }
+    }
+}
+
+impl Default for ProgressBar<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Displayable for ProgressBar<'_> {
+    fn paint(&self) {
+        BLANK.paint();
+        if let Some(label) = self.label {
+            LabelLine::new().text(label).pos(0, 12).paint();
+        }
+        // Outline, then filled part
+        Rect::new().pos(BAR_X, BAR_Y).dims(BAR_WIDTH, 3).paint();
+        let width = (self.percent as u32 * BAR_WIDTH as u32 / 100) as u16;
+        if width > 0 {
+            Rect::new().pos(BAR_X, BAR_Y).dims(width, 3).fill(true).paint();
+        }
+    }
+}
+
+/// Frames of a `Spinner` drawing dots
+pub const DOTS: &[&str] = &[".", "..", "...", ""];
+/// Frames of a `Spinner` drawing a rotating dash
+pub const DASHES: &[&str] = &["-", "\\", "|", "/"];
+/// Ticker events each frame stays for
+pub const SPINNER_TICKS: u8 = 3;
+
+/// Activity indicator for long computations:
+/// it changes frame on ticker events, without
+/// an event loop of its own.
+///
+/// ```ignore
+/// let mut spinner = Spinner::new();
+/// spinner.start();
+/// // for each event received while signing
+/// spinner.handle(event);
+/// // once done
+/// spinner.stop();
+/// ```
+#[derive(Copy, Clone)]
+pub struct Spinner<'a> {
+    label: Option<&'a str>,
+    frames: &'a [&'a str],
+    /// Frame currently shown
+    frame: usize,
+    /// Ticks until the next frame
+    wait: u8,
+    running: bool,
+}
+
+impl<'a> Spinner<'a> {
+    /// Dots under the translated "Processing"
+    pub const fn new() -> Self {
+        Spinner { label: None, frames: DOTS, frame: 0, wait: SPINNER_TICKS, running: false }
+    }
+
+    pub const fn label(self, label: &'a str) -> Self {
+        Spinner { label: Some(label), ..self }
+    }
+
+    /// Cycle through `frames`, e.g. `DASHES`
+    pub const fn frames(self, frames: &'a [&'a str]) -> Self {
+        Spinner { frames, ..self }
+    }
+
+    pub const fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Show the first frame and
+    /// animate on the next ticks
+    pub fn start(&mut self) {
+        self.running = true;
+        self.frame = 0;
+        self.wait = SPINNER_TICKS;
+        self.paint();
+    }
+
+    /// Stop animating and clear the screen
+    pub fn stop(&mut self) {
+        self.running = false;
+        BLANK.paint();
+    }
+
+    /// Advance by one tick, repainting
+    /// when the frame changes
+    pub fn tick(&mut self) {
+        if !self.running || self.frames.is_empty() {
+            return
+        }
+        self.wait = self.wait.saturating_sub(1);
+        if self.wait == 0 {
+            self.wait = SPINNER_TICKS;
+            self.frame = (self.frame + 1) % self.frames.len();
+            self.paint();
+        }
+    }
+
+    /// Feed an event received while
+    /// running: ticks animate the spinner
+    pub fn handle(&mut self, event: Event) {
+        if event == Event::Ticker {
+            self.tick();
+        }
+    }
+}
+
+impl Default for Spinner<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Displayable for Spinner<'_> {
+    fn paint(&self) {
+        BLANK.paint();
+        let label = self.label.unwrap_or_else(|| tr(Text::Processing));
+        LabelLine::new().text(label).pos(0, 12).paint();
+        if let Some(frame) = self.frames.get(self.frame) {
+            LabelLine::new().text(frame).pos(0, 26).bold().paint();
+        }
+    }
+}
@@ -0,0 +1,439 @@
+//! QR codes of short payloads, e.g. a receive
+//! address, so that a wallet can scan it from
+//! the device:
+//!
+//! ```ignore
+//! let outcome = QrCode::new(address).ask();
+//! ```
+//!
+//! The QR code is shown first, then the address
+//! as text, as `AddressConfirm` does. Codes use
+//! byte mode with low error correction, in
+//! versions 1 to 5 (up to 106 bytes).
+//!
+//! Scanning needs 2 pixels per module, so the
+//! code is meant for the 64 pixel high screens
+//! of the Nano S Plus and Nano X: on the Nano S
+//! only single pixel modules fit.
+
+use crate::bagls::*;
+use crate::input::Action;
+use crate::screen;
+use crate::tx::AddressConfirm;
+use crate::ui::{Actions, WidgetState};
+use crate::widget::{run, Event, Outcome, Widget};
+
+/// Highest version encoded
+pub const QR_MAX_VERSION: usize = 5;
+/// Modules per side of the largest code
+pub const QR_MAX_SIZE: usize = 17 + 4 * QR_MAX_VERSION;
+/// Most payload bytes encoded
+pub const QR_MAX_BYTES: usize = 106;
+
+/// Data and error correction codewords of
+/// versions 1 to 5 at the low level, each
+/// a single block
+const CODEWORDS: [(usize, usize); QR_MAX_VERSION] = [(19, 7), (34, 10), (55, 15), (80, 20), (108, 26)];
+const MAX_CODEWORDS: usize = 134;
+/// Light modules kept around the code,
+/// at most
+const QUIET_ZONE: usize = 4;
+
+/// Modules of a QR code, a bit per module
+/// (set: dark), row after row
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct QrMatrix {
+    pub size: usize,
+    rows: [u64; QR_MAX_SIZE],
+    /// Finder, timing, alignment and
+    /// format modules
+    function: [u64; QR_MAX_SIZE],
+}
+
+impl QrMatrix {
+    /// Smallest code of `data`, None if
+    /// longer than `QR_MAX_BYTES`
+    pub fn encode(data: &[u8]) -> Option<Self> {
+        let version = (1..=QR_MAX_VERSION).find(|&v| data.len() + 2 <= CODEWORDS[v - 1].0)?;
+        let (data_len, ecc_len) = CODEWORDS[version - 1];
+        let mut codewords = [0u8; MAX_CODEWORDS];
+        let mut bits = Bits { buf: &mut codewords[..data_len], len: 0 };
+        bits.push(0b0100, 4);
+        bits.push(data.len() as u32, 8);
+        for &b in data {
+            bits.push(b as u32, 8);
+        }
+        // Terminator, then padding to a byte
+        let capacity = data_len * 8;
+        bits.push(0, (capacity - bits.len).min(4));
+        bits.push(0, (8 - bits.len % 8) % 8);
+        for pad in [0xec, 0x11].iter().cycle().take(data_len - bits.len / 8) {
+            bits.push(*pad, 8);
+        }
+        let (data_cw, ecc_cw) = codewords.split_at_mut(data_len);
+        reed_solomon(data_cw, &mut ecc_cw[..ecc_len]);
+
+        let mut qr = QrMatrix { size: 17 + 4 * version, rows: [0; QR_MAX_SIZE], function: [0; QR_MAX_SIZE] };
+        qr.draw_function_patterns(version);
+        qr.draw_codewords(&codewords[..data_len + ecc_len]);
+        // Keep the mask giving the lowest penalty
+        let mut best = (u32::MAX, 0);
+        for mask in 0..8 {
+            qr.apply_mask(mask);
+            qr.draw_format(mask);
+            let penalty = qr.penalty();
+            if penalty < best.0 {
+                best = (penalty, mask);
+            }
+            qr.apply_mask(mask);
+        }
+        qr.apply_mask(best.1);
+        qr.draw_format(best.1);
+        Some(qr)
+    }
+
+    /// Whether the module in column
+    /// `x` of row `y` is dark
+    pub const fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.rows[y] >> x & 1 == 1
+    }
+
+    fn set(&mut self, x: usize, y: usize, dark: bool) {
+        self.rows[y] = self.rows[y] & !(1 << x) | (dark as u64) << x;
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.set(x, y, dark);
+        self.function[y] |= 1 << x;
+    }
+
+    const fn is_function(&self, x: usize, y: usize) -> bool {
+        self.function[y] >> x & 1 == 1
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                    let dist = dx.abs().max(dy.abs());
+                    if (0..size as i32).contains(&x) && (0..size as i32).contains(&y) {
+                        self.set_function(x as usize, y as usize, dist != 2 && dist != 4);
+                    }
+                }
+            }
+        }
+        // Versions 2 to 6 have one alignment
+        // pattern, near the bottom right corner
+        if version > 1 {
+            let c = size - 7;
+            for dy in -2i32..=2 {
+                for dx in -2i32..=2 {
+                    let (x, y) = ((c as i32 + dx) as usize, (c as i32 + dy) as usize);
+                    self.set_function(x, y, dx.abs().max(dy.abs()) != 1);
+                }
+            }
+        }
+        // Reserve the format modules
+        self.draw_format(0);
+    }
+
+    /// Format bits of the low error
+    /// correction level and `mask`
+    fn draw_format(&mut self, mask: usize) {
+        let data = (0b01 << 3 | mask) as u32;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = (data << 10 | rem) ^ 0x5412;
+        let bit = |i: usize| bits >> i & 1 == 1;
+        let size = self.size;
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Place `codewords` in the zigzag order,
+    /// two columns at a time from the right
+    fn draw_codewords(&mut self, codewords: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size as i32 - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..size {
+                for j in 0..2 {
+                    let x = (right - j) as usize;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { size - 1 - vert } else { vert };
+                    if !self.is_function(x, y) && i < codewords.len() * 8 {
+                        self.set(x, y, codewords[i / 8] >> (7 - i % 8) & 1 == 1);
+                        i += 1;
+                    }
+                }
+            }
+            right -= 2;
+        }
+    }
+
+    /// Flip the data modules selected by `mask`,
+    /// applying it a second time undoes it
+    fn apply_mask(&mut self, mask: usize) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let flip = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if flip && !self.is_function(x, y) {
+                    self.set(x, y, !self.is_dark(x, y));
+                }
+            }
+        }
+    }
+
+    /// Penalty of the current modules, from
+    /// the four rules of the standard
+    fn penalty(&self) -> u32 {
+        let size = self.size;
+        let mut penalty = 0;
+        for transpose in [false, true] {
+            let dark = |a: usize, b: usize| if transpose { self.is_dark(b, a) } else { self.is_dark(a, b) };
+            for line in 0..size {
+                // Runs of 5 or more modules
+                let mut run = 1;
+                for k in 1..size {
+                    if dark(k, line) == dark(k - 1, line) {
+                        run += 1;
+                        if run == 5 {
+                            penalty += 3;
+                        } else if run > 5 {
+                            penalty += 1;
+                        }
+                    } else {
+                        run = 1;
+                    }
+                }
+                // 1:1:3:1:1 finder-like patterns
+                // with 4 light modules on a side
+                for k in 0..size.saturating_sub(10) {
+                    let m = |i: usize| dark(k + i, line);
+                    let core = m(0) && !m(1) && m(2) && m(3) && m(4) && !m(5) && m(6);
+                    let light = |from: usize| (from..from + 4).all(|i| !m(i));
+                    if core && light(7) {
+                        penalty += 40;
+                    }
+                    let core = m(4) && !m(5) && m(6) && m(7) && m(8) && !m(9) && m(10);
+                    if core && light(0) {
+                        penalty += 40;
+                    }
+                }
+            }
+        }
+        // 2x2 blocks of a color
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let c = self.is_dark(x, y);
+                if c == self.is_dark(x + 1, y) && c == self.is_dark(x, y + 1) && c == self.is_dark(x + 1, y + 1) {
+                    penalty += 3;
+                }
+            }
+        }
+        // Balance of dark and light modules
+        let dark: u32 = self.rows[..size].iter().map(|r| r.count_ones()).sum();
+        let total = (size * size) as u32;
+        let k = (dark * 20).abs_diff(total * 10).div_ceil(total).saturating_sub(1);
+        penalty + k * 10
+    }
+}
+
+/// Appends bits to codewords, most
+/// significant first
+struct Bits<'b> {
+    buf: &'b mut [u8],
+    len: usize,
+}
+
+impl Bits<'_> {
+    fn push(&mut self, value: u32, count: usize) {
+        for i in (0..count).rev() {
+            if value >> i & 1 == 1 {
+                self.buf[self.len / 8] |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+}
+
+/// Product in GF(256), modulo
+/// x^8 + x^4 + x^3 + x^2 + 1
+fn gf_mul(x: u8, y: u8) -> u8 {
+    let mut z: u8 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x1d);
+        z ^= ((y >> i) & 1) * x;
+    }
+    z
+}
+
+/// Reed-Solomon error correction
+/// codewords of `data` into `ecc`
+fn reed_solomon(data: &[u8], ecc: &mut [u8]) {
+    let n = ecc.len();
+    let mut divisor = [0u8; 32];
+    divisor[n - 1] = 1;
+    let mut root = 1;
+    for _ in 0..n {
+        for j in 0..n {
+            divisor[j] = gf_mul(divisor[j], root);
+            if j + 1 < n {
+                divisor[j] ^= divisor[j + 1];
+            }
+        }
+        root = gf_mul(root, 0x02);
+    }
+    ecc.fill(0);
+    for &b in data {
+        let factor = b ^ ecc[0];
+        ecc.copy_within(1.., 0);
+        ecc[n - 1] = 0;
+        for (e, d) in ecc.iter_mut().zip(&divisor[..n]) {
+            *e ^= gf_mul(*d, factor);
+        }
+    }
+}
+
+/// A QR code of an address, then the
+/// address as text with approve and
+/// reject pages
+#[derive(Copy, Clone)]
+pub struct QrCode<'a> {
+    address: AddressConfirm<'a>,
+    /// Whether the QR code is shown
+    qr: bool,
+}
+
+impl<'a> QrCode<'a> {
+    pub const fn new(address: &'a str) -> Self {
+        QrCode { address: AddressConfirm::new(address), qr: true }
+    }
+
+    /// Show the text pages of `address`
+    /// instead of the default ones
+    pub const fn text(self, address: AddressConfirm<'a>) -> Self {
+        QrCode { address, ..self }
+    }
+
+    pub fn ask(&self) -> Outcome {
+        run(&mut { *self })
+    }
+
+    /// Draw the code of the address, the
+    /// largest fitting, in the middle
+    fn draw_qr(&self) {
+        let screen = screen::current();
+        screen.blank().paint();
+        let qr = match QrMatrix::encode(self.address.address().as_bytes()) {
+            Some(qr) => qr,
+            None => return,
+        };
+        let scale = (screen.height as usize / (qr.size + 2)).max(1);
+        let quiet = ((screen.height as usize / scale).saturating_sub(qr.size) / 2).min(QUIET_ZONE);
+        let side = (qr.size + 2 * quiet) * scale;
+        // Codes taller than the screen are cut
+        let x0 = (screen.width as usize).saturating_sub(side) / 2;
+        let y0 = (screen.height as usize).saturating_sub(side) / 2;
+        Rect::new().pos(x0 as i16, y0 as i16).dims(side as u16, side as u16).fill(true).paint();
+        let (x0, y0) = (x0 + quiet * scale, y0 + quiet * scale);
+        // One rectangle per run of dark modules
+        for y in 0..qr.size {
+            let mut x = 0;
+            while x < qr.size {
+                if !qr.is_dark(x, y) {
+                    x += 1;
+                    continue
+                }
+                let start = x;
+                while x < qr.size && qr.is_dark(x, y) {
+                    x += 1;
+                }
+                Rect::new().pos((x0 + start * scale) as i16, (y0 + y * scale) as i16)
+                    .dims(((x - start) * scale) as u16, scale as u16)
+                    .colors(0, 0xffffff).fill(true).paint();
+            }
+        }
+        screen.place(RIGHT_ARROW).paint();
+    }
+}
+
+impl Widget for QrCode<'_> {
+    type Output = Outcome;
+
+    fn draw(&self) {
+        if self.qr {
+            self.draw_qr();
+        } else {
+            self.address.draw();
+            screen::current().place(LEFT_ARROW).paint();
+        }
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Outcome> {
+        match (self.qr, event.action()?) {
+            (true, Action::Next) => {
+                self.qr = false;
+                self.draw();
+            }
+            (true, Action::Reject) => return Some(Outcome::Rejected),
+            (true, _) => (),
+            (false, Action::Previous) if self.address.page() == 0 => {
+                self.qr = true;
+                self.draw();
+            }
+            (false, _) => {
+                let output = self.address.handle(event);
+                if output.is_none() && self.address.page() == 0 {
+                    screen::current().place(LEFT_ARROW).paint();
+                }
+                return output
+            }
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        let address = self.address.state();
+        if self.qr {
+            return WidgetState::new("QrCode", 0, address.count + 1).actions(Actions::NEXT)
+        }
+        WidgetState::new("QrCode", address.index + 1, address.count + 1)
+            .actions(address.actions.with(Actions::PREVIOUS))
+    }
+}
@@ -0,0 +1,192 @@
+//! Review progress kept in non-volatile memory,
+//! so a long review interrupted by a reboot can
+//! resume where the user left it.
+//!
+//! The app provides the storage, typically an
+//! `nvm` region of its own:
+//!
+//! ```ignore
+//! let mut review = Resumable::new(validator, tx_id, &mut store);
+//! let outcome = widget::run(&mut review);
+//! ```
+//!
+//! `tx_id` identifies the reviewed data (e.g. the
+//! first bytes of its hash): saved progress only
+//! applies to a review of the same data.
+
+use crate::flow::Flow;
+use crate::ui::{HScroller, MessageScroller, MessageValidator, WidgetState};
+use crate::widget::{Event, Widget};
+
+/// Size of a serialized `ReviewProgress`
+pub const PROGRESS_LEN: usize = 12;
+
+const MAGIC: u8 = 0xa5;
+
+/// Where the review stands
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReviewProgress {
+    /// Identifies the reviewed data
+    pub id: u32,
+    /// Page currently shown
+    pub page: u16,
+    /// Number of pages seen so far
+    pub visited: u16,
+    /// Whether the review still waits for a decision
+    pub pending: bool,
+}
+
+impl ReviewProgress {
+    pub fn to_bytes(&self) -> [u8; PROGRESS_LEN] {
+        let mut out = [0u8; PROGRESS_LEN];
+        out[0] = MAGIC;
+        out[1] = self.pending as u8;
+        out[2..6].copy_from_slice(&self.id.to_be_bytes());
+        out[6..8].copy_from_slice(&self.page.to_be_bytes());
+        out[8..10].copy_from_slice(&self.visited.to_be_bytes());
+        let sum = checksum(&out[..10]);
+        out[10..].copy_from_slice(&sum.to_be_bytes());
+        out
+    }
+
+    /// None if `bytes` do not hold a valid progress,
+    /// e.g. blank or partially written storage
+    pub fn from_bytes(bytes: &[u8; PROGRESS_LEN]) -> Option<Self> {
+        let sum = u16::from_be_bytes([bytes[10], bytes[11]]);
+        if bytes[0] != MAGIC || bytes[1] > 1 || sum != checksum(&bytes[..10]) {
+            return None
+        }
+        Some(ReviewProgress {
+            id: u32::from_be_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]),
+            page: u16::from_be_bytes([bytes[6], bytes[7]]),
+            visited: u16::from_be_bytes([bytes[8], bytes[9]]),
+            pending: bytes[1] == 1,
+        })
+    }
+}
+
+/// Fletcher-16
+fn checksum(data: &[u8]) -> u16 {
+    let (a, b) = data.iter().fold((0u16, 0u16), |(a, b), &x| {
+        let a = (a + x as u16) % 255;
+        (a, (b + a) % 255)
+    });
+    (b << 8) | a
+}
+
+/// App-provided storage for the progress
+pub trait ProgressStore {
+    fn load(&self) -> [u8; PROGRESS_LEN];
+    fn store(&mut self, bytes: &[u8; PROGRESS_LEN]);
+}
+
+/// Widgets whose current page can be set
+pub trait Seek {
+    fn seek(&mut self, page: usize);
+}
+
+impl Seek for MessageValidator<'_> {
+    fn seek(&mut self, page: usize) {
+        self.page = page.min(self.page_count() - 1);
+    }
+}
+
+impl Seek for MessageScroller<'_> {
+    fn seek(&mut self, page: usize) {
+        self.page = page.min(self.page_count().saturating_sub(1));
+    }
+}
+
+impl Seek for HScroller<'_> {
+    fn seek(&mut self, page: usize) {
+        self.index = page.min(self.len().saturating_sub(1));
+    }
+}
+
+impl Seek for Flow<'_> {
+    /// Stops before the first step requiring
+    /// the PIN, so resuming cannot skip it
+    fn seek(&mut self, page: usize) {
+        if self.steps.is_empty() {
+            return
+        }
+        let page = page.min(self.steps.len() - 1);
+        self.index = self.steps[..=page].iter().position(|s| s.pin)
+            .map_or(page, |gated| gated.saturating_sub(1));
+    }
+}
+
+/// Wraps a review widget, saving its progress
+/// each time the page changes and resuming from
+/// the saved progress when it matches `id`
+pub struct Resumable<'s, W> {
+    widget: W,
+    store: &'s mut dyn ProgressStore,
+    progress: ReviewProgress,
+    resumed: bool,
+}
+
+impl<'s, W: Widget + Seek> Resumable<'s, W> {
+    pub fn new(mut widget: W, id: u32, store: &'s mut dyn ProgressStore) -> Self {
+        let (progress, resumed) = match ReviewProgress::from_bytes(&store.load()) {
+            Some(saved) if saved.id == id && saved.pending => {
+                widget.seek(saved.page as usize);
+                (saved, true)
+            }
+            _ => (ReviewProgress { id, page: 0, visited: 1, pending: true }, false),
+        };
+        let mut resumable = Resumable { widget, store, progress, resumed };
+        resumable.save();
+        resumable
+    }
+
+    /// Progress of the review, as last saved
+    pub fn progress(&self) -> ReviewProgress {
+        self.progress
+    }
+
+    /// Whether the review resumed an earlier one
+    pub fn resumed(&self) -> bool {
+        self.resumed
+    }
+
+    fn save(&mut self) {
+        self.store.store(&self.progress.to_bytes());
+    }
+}
+
+impl<W: Widget + Seek> Widget for Resumable<'_, W> {
+    type Output = W::Output;
+
+    fn enter(&mut self) {
+        self.widget.enter();
+    }
+
+    fn draw(&self) {
+        self.widget.draw();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<W::Output> {
+        let output = self.widget.handle(event);
+        if output.is_some() {
+            self.progress.pending = false;
+            self.save();
+            return output
+        }
+        let page = self.widget.state().index as u16;
+        if page != self.progress.page {
+            self.progress.page = page;
+            self.progress.visited = self.progress.visited.max(page + 1);
+            self.save();
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        self.widget.state()
+    }
+
+    fn on_idle(&mut self) -> Option<W::Output> {
+        self.widget.on_idle()
+    }
+}
@@ -0,0 +1,193 @@
+//! Non-blocking alternative to `widget::run`:
+//! the app keeps its own event loop, hands the
+//! UI the events meant for it, and gets each
+//! screen's output through a callback. APDUs keep
+//! being processed while a screen is shown.
+//!
+//! ```ignore
+//! let mut review = Screen::new(validator, |outcome| approved = outcome.is_approved());
+//! let mut ui = UiRunner::new();
+//! ui.show(&mut review);
+//! loop {
+//!     match comm.next_event::<Ins>() {
+//!         io::Event::Button(button) => { ui.step(Event::Button(button.into())); }
+//!         io::Event::Ticker => { ui.step(Event::Ticker); }
+//!         io::Event::Command(ins) => handle_apdu(&mut comm, ins),
+//!     }
+//! }
+//! ```
+//!
+//! Screens stack: showing one over another
+//! covers it until the new one is done.
+
+use crate::context::{ActiveWidget, UiContext};
+use crate::screensaver;
+use crate::ui::{report, WidgetState};
+use crate::widget::{idle_timeout, Event, Widget};
+
+/// Most screens stacked in a `UiRunner`
+pub const MAX_SCREENS: usize = 4;
+
+/// A widget and the callback
+/// receiving its output
+pub struct Screen<W, F> {
+    pub widget: W,
+    on_done: F,
+}
+
+impl<W: Widget, F: FnMut(W::Output)> Screen<W, F> {
+    pub const fn new(widget: W, on_done: F) -> Self {
+        Screen { widget, on_done }
+    }
+}
+
+/// Screen with its types erased
+trait Step {
+    fn enter(&mut self) -> Option<ActiveWidget>;
+    fn draw(&self);
+    fn state(&self) -> WidgetState;
+    /// Feed `event`, calling back once
+    /// done. Returns whether done.
+    fn handle(&mut self, event: Event) -> bool;
+    fn on_idle(&mut self) -> bool;
+}
+
+impl<W: Widget, F: FnMut(W::Output)> Step for Screen<W, F> {
+    fn enter(&mut self) -> Option<ActiveWidget> {
+        self.widget.enter();
+        UiContext::enter(&self.widget)
+    }
+
+    fn draw(&self) {
+        UiContext::exclusive(|| self.widget.draw());
+    }
+
+    fn state(&self) -> WidgetState {
+        self.widget.state()
+    }
+
+    fn handle(&mut self, event: Event) -> bool {
+        match self.widget.handle(event) {
+            Some(output) => {
+                (self.on_done)(output);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn on_idle(&mut self) -> bool {
+        match self.widget.on_idle() {
+            Some(output) => {
+                (self.on_done)(output);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Stack of screens driven by the app's
+/// events, one `step` at a time
+pub struct UiRunner<'a> {
+    screens: [Option<&'a mut dyn Step>; MAX_SCREENS],
+    /// Display owner before each screen
+    previous: [Option<ActiveWidget>; MAX_SCREENS],
+    len: usize,
+    /// Ticker events since the last button event
+    idle_ticks: u32,
+}
+
+impl<'a> UiRunner<'a> {
+    pub const fn new() -> Self {
+        UiRunner {
+            screens: [None, None, None, None],
+            previous: [None; MAX_SCREENS],
+            len: 0,
+            idle_ticks: 0,
+        }
+    }
+
+    /// Show `screen` over the current one.
+    /// Returns false if the stack is full.
+    pub fn show<W: Widget, F: FnMut(W::Output)>(&mut self, screen: &'a mut Screen<W, F>) -> bool {
+        if self.len == MAX_SCREENS {
+            return false
+        }
+        self.previous[self.len] = screen.enter();
+        screen.draw();
+        self.screens[self.len] = Some(screen);
+        self.len += 1;
+        self.idle_ticks = 0;
+        screensaver::reset();
+        true
+    }
+
+    /// Number of screens stacked
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no screen is shown, so
+    /// events are not meant for the UI
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Drop every screen without calling
+    /// back, e.g. when an APDU cancels them
+    pub fn clear(&mut self) {
+        if self.len > 0 {
+            UiContext::leave(self.previous[0]);
+        }
+        self.screens = [None, None, None, None];
+        self.len = 0;
+    }
+
+    /// Give `event` to the top screen. Once it is
+    /// done, its callback runs and the screen below
+    /// is drawn again. Returns whether a screen
+    /// is still shown.
+    pub fn step(&mut self, event: Event) -> bool {
+        let top = match self.len.checked_sub(1) {
+            Some(top) => top,
+            None => return false,
+        };
+        // Not for the screens while blanked
+        if screensaver::filter(event) {
+            self.idle_ticks = 0;
+            return true
+        }
+        match event {
+            Event::Ticker => self.idle_ticks = self.idle_ticks.saturating_add(1),
+            _ => self.idle_ticks = 0,
+        }
+        let timeout = idle_timeout();
+        let expired = timeout > 0 && self.idle_ticks >= timeout;
+        let done = match &mut self.screens[top] {
+            Some(screen) => screen.handle(event) || (expired && screen.on_idle()),
+            None => false,
+        };
+        if expired {
+            self.idle_ticks = 0;
+        }
+        if done {
+            self.screens[top] = None;
+            self.len = top;
+            UiContext::leave(self.previous[top]);
+            if let Some(screen) = top.checked_sub(1).and_then(|i| self.screens[i].as_ref()) {
+                screen.draw();
+            }
+        }
+        if let Some(screen) = self.len.checked_sub(1).and_then(|i| self.screens[i].as_ref()) {
+            report(screen.state());
+        }
+        !self.is_empty()
+    }
+}
+
+impl Default for UiRunner<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
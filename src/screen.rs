@@ -0,0 +1,146 @@
+//! Screen geometry of the target device,
+//! selected by the `nanosplus` and `nanox`
+//! features (Nano S without either).
+//!
+//! Widgets are laid out for the 128x32 Nano S
+//! screen; on the 128x64 devices the layout is
+//! centered vertically:
+//!
+//! ```ignore
+//! let screen = screen::current();
+//! LabelLine::new().text("Review").pos(0, screen.y(12)).paint();
+//! screen.place(LEFT_ARROW).paint();
+//! ```
+//!
+//! With the `sim` feature, the geometry follows
+//! `sim::set_screen_size` instead.
+
+use crate::bagls::{Icon, Rect};
+
+/// Height of the layouts drawn by the widgets
+pub const LAYOUT_HEIGHT: u16 = 32;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Screen {
+    pub width: u16,
+    pub height: u16,
+    /// Lines of regular text fitting on the screen
+    pub lines: usize,
+    /// Distance between the baselines of
+    /// two lines of regular text
+    pub line_height: i16,
+}
+
+impl Screen {
+    pub const NANO_S: Screen = Screen { width: 128, height: 32, lines: 2, line_height: 14 };
+    pub const NANO_SP: Screen = Screen { width: 128, height: 64, lines: 4, line_height: 14 };
+    pub const NANO_X: Screen = Screen { width: 128, height: 64, lines: 4, line_height: 14 };
+
+    /// Vertical offset of the widget layouts
+    pub const fn y_offset(&self) -> i16 {
+        (self.height as i16 - LAYOUT_HEIGHT as i16) / 2
+    }
+
+    /// y on this screen of `y` in the layout
+    pub const fn y(&self, y: i16) -> i16 {
+        y + self.y_offset()
+    }
+
+    /// `icon`, moved from the layout to this screen
+    pub const fn place(&self, icon: Icon) -> Icon {
+        icon.pos(icon.pos.0, self.y(icon.pos.1))
+    }
+
+    /// Rectangle clearing the whole screen,
+    /// `BLANK` on the Nano S
+    pub const fn blank(&self) -> Rect {
+        Rect::new().pos(0, 0).dims(self.width, self.height).colors(0, 0xffffff).fill(true)
+    }
+}
+
+/// Screen of the device the app is built for
+#[cfg(feature = "nanox")]
+pub const TARGET: Screen = Screen::NANO_X;
+#[cfg(all(feature = "nanosplus", not(feature = "nanox")))]
+pub const TARGET: Screen = Screen::NANO_SP;
+#[cfg(not(any(feature = "nanosplus", feature = "nanox")))]
+pub const TARGET: Screen = Screen::NANO_S;
+
+/// Screen the widgets are drawn on
+#[cfg(not(feature = "sim"))]
+pub fn current() -> Screen {
+    TARGET
+}
+
+#[cfg(feature = "sim")]
+pub fn current() -> Screen {
+    let size = crate::sim::screen_size();
+    match size.height as u16 {
+        h if h == Screen::NANO_S.height => Screen::NANO_S,
+        h => Screen { width: size.width as u16, height: h, ..Screen::NANO_X },
+    }
+}
+
+/// Clear the whole screen
+pub fn clear() {
+    use crate::bagls::Displayable;
+    if crate::canvas::divert(|c| c.clear()) {
+        return
+    }
+    current().blank().paint();
+    crate::canvas::cleared();
+}
+
+/// Most bitmap bytes `draw_bitmap` sends at once
+pub const BITMAP_CHUNK: usize = 128;
+
+/// Draw a 1 bpp bitmap of `w`x`h` pixels at
+/// (x, y), lit pixels in white. `data` holds
+/// the rows one after the other, without padding,
+/// least significant bits first, as glyphs do.
+/// Large bitmaps are sent in bands of rows.
+pub fn draw_bitmap(x: i16, y: i16, w: u16, h: u16, data: &[u8]) {
+    use crate::bagls::{Bitmap, Displayable};
+    let w_bits = w as usize;
+    if w_bits == 0 || h == 0 {
+        return
+    }
+    let band = (BITMAP_CHUNK * 8 / w_bits).clamp(1, h as usize);
+    let mut buf = [0u8; BITMAP_CHUNK];
+    let mut row = 0;
+    while row < h as usize {
+        let rows = band.min(h as usize - row);
+        let bits = rows * w_bits;
+        let len = bits.div_ceil(8).min(BITMAP_CHUNK);
+        buf[..len].fill(0);
+        let start = row * w_bits;
+        for i in 0..bits.min(BITMAP_CHUNK * 8) {
+            let src = start + i;
+            let lit = data.get(src / 8).is_some_and(|b| b >> (src % 8) & 1 != 0);
+            if lit {
+                buf[i / 8] |= 1 << (i % 8);
+            }
+        }
+        Bitmap::new(&buf[..len], w, rows as u16).pos(x, y + row as i16).paint();
+        row += rows;
+    }
+}
+
+/// Draw a single row of `w` pixels from
+/// `bits`, least significant bits first
+pub fn draw_row(x: i16, y: i16, w: u16, bits: &[u8]) {
+    draw_bitmap(x, y, w, 1, bits)
+}
+
+/// Fill a `w`x`h` rectangle, lit in white
+/// or cleared to black
+pub fn fill_rect(x: i16, y: i16, w: u16, h: u16, lit: bool) {
+    use crate::bagls::Displayable;
+    let colors = if lit { (0xffffff, 0) } else { (0, 0xffffff) };
+    Rect::new().pos(x, y).dims(w, h).colors(colors.0, colors.1).fill(true).paint();
+}
+
+/// Light or clear the pixel at (x, y)
+pub fn set_pixel(x: i16, y: i16, lit: bool) {
+    fill_rect(x, y, 1, 1, lit)
+}
@@ -0,0 +1,131 @@
+//! Screen blanking after a period without
+//! button activity, so that an address left
+//! on the screen neither burns into the OLED
+//! nor stays readable to passers-by:
+//!
+//! ```ignore
+//! // Blank after 2 minutes, forgetting the
+//! // cached key when it does
+//! screensaver::set_timeout(1200);
+//! screensaver::set_lock(Some(forget_key));
+//! ```
+//!
+//! Widgets run by `widget::run` or `UiRunner`
+//! are not given the events received while the
+//! screen is blank. The first button press shows
+//! the widget again; it is swallowed, along
+//! with its release.
+//!
+//! BAGL has no brightness control, so the
+//! screen is blanked rather than dimmed.
+
+use crate::buttons::ButtonEvent;
+use crate::context::UiContext;
+use crate::screen;
+use crate::status;
+use crate::widget::Event;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    Awake,
+    Blank,
+    /// Shown again, until the buttons
+    /// pressed to wake it are released
+    Waking,
+}
+
+struct Saver {
+    /// Ticker events before blanking, 0: never
+    timeout: u32,
+    lock: Option<fn()>,
+    idle_ticks: u32,
+    state: State,
+}
+
+static mut SAVER: Saver = Saver { timeout: 0, lock: None, idle_ticks: 0, state: State::Awake };
+
+fn saver() -> &'static mut Saver {
+    unsafe { &mut *core::ptr::addr_of_mut!(SAVER) }
+}
+
+/// Blank the screen after `ticks` ticker
+/// events (10 per second) without button
+/// activity, or never if 0
+pub fn set_timeout(ticks: u32) {
+    saver().timeout = ticks;
+}
+
+pub fn timeout() -> u32 {
+    saver().timeout
+}
+
+/// Call `lock` each time the screen is
+/// blanked, e.g. to forget cached secrets.
+/// It must not draw.
+pub fn set_lock(lock: Option<fn()>) {
+    saver().lock = lock;
+}
+
+/// Whether the screen is blanked
+pub fn is_blank() -> bool {
+    saver().state == State::Blank
+}
+
+/// Restart the inactivity count, e.g. when
+/// a new screen is shown
+pub fn reset() {
+    let s = saver();
+    s.idle_ticks = 0;
+    if s.state == State::Blank {
+        s.state = State::Awake;
+    }
+}
+
+/// Update the inactivity count from `event`,
+/// blanking or showing the screen again.
+/// Returns true when the event is taken by
+/// the screensaver and not meant for widgets.
+pub(crate) fn filter(event: Event) -> bool {
+    let s = saver();
+    match (s.state, event) {
+        (State::Awake, Event::Ticker) => {
+            s.idle_ticks = s.idle_ticks.saturating_add(1);
+            if s.timeout > 0 && s.idle_ticks >= s.timeout {
+                s.state = State::Blank;
+                screen::clear();
+                if let Some(lock) = s.lock {
+                    lock();
+                }
+            }
+            false
+        }
+        (State::Awake, Event::Button(_) | Event::Input(_) | Event::Touch(_)) => {
+            s.idle_ticks = 0;
+            false
+        }
+        (State::Awake, _) => false,
+        (State::Blank, Event::Button(_) | Event::Input(_) | Event::Touch(_)) => {
+            s.idle_ticks = 0;
+            s.state = State::Waking;
+            if let Some(active) = UiContext::current() {
+                active.redraw();
+                status::paint_overlay();
+            }
+            true
+        }
+        (State::Blank, _) => true,
+        (State::Waking, Event::Button(b)) => {
+            if matches!(b, ButtonEvent::LeftButtonRelease | ButtonEvent::RightButtonRelease
+                | ButtonEvent::BothButtonsRelease)
+            {
+                s.state = State::Awake;
+            }
+            true
+        }
+        (State::Waking, Event::Input(_) | Event::Touch(_)) => {
+            s.state = State::Awake;
+            true
+        }
+        (State::Waking, _) => false,
+    }
+}
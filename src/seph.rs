@@ -0,0 +1,8 @@
+//! Indirection over the SEPH transport so
+//! that the `sim` feature can swap it out
+
+#[cfg(not(feature = "sim"))]
+pub use nanos_sdk::seph::*;
+
+#[cfg(feature = "sim")]
+pub use crate::sim::seph::*;
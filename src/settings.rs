@@ -0,0 +1,186 @@
+//! On/off app settings kept in NVM, declared
+//! once with `settings!` for both storage and
+//! the settings menu:
+//!
+//! ```ignore
+//! settings!(pub SETTINGS = [
+//!     BLIND_SIGNING: "Blind signing" = false @ 0,
+//!     DEBUG_DATA: "Debug data" = false @ 1,
+//! ]);
+//!
+//! if BLIND_SIGNING.get(&store) { .. }
+//! settings::menu(SETTINGS, &mut store);
+//! ```
+//!
+//! Each setting uses the NVM byte at its offset.
+//! Blank storage reads as the default value.
+//!
+//! Settings with more than two values list
+//! them, selecting one cycles to the next:
+//!
+//! ```ignore
+//! const DISPLAY: Setting = Setting::choice("Display", &["Short", "Full", "Hex"], 0, 2);
+//!
+//! static mut STORE: Pic<AtomicStorage<[u8; 4]>> = Pic::new(AtomicStorage::new(&[0; 4]));
+//! let store = unsafe { STORE.get_mut() };
+//! settings::menu(&[BLIND_SIGNING, DISPLAY], store);
+//! match DISPLAY.option(store) { .. }
+//! ```
+
+use core::fmt::Write;
+
+use crate::fmt::ON_OFF;
+use crate::i18n::{tr, Text};
+use crate::owned::{OwnedMenu, MENU_ENTRY_LEN};
+use crate::widget::run;
+
+/// Most settings shown by `menu`
+pub const MAX_SETTINGS: usize = 8;
+
+/// App-provided storage, typically
+/// an `nvm` region of the app
+pub trait SettingsStore {
+    fn read(&self, offset: usize) -> u8;
+    fn write(&mut self, offset: usize, value: u8);
+}
+
+/// Settings kept in an `AtomicStorage` of the
+/// app, one byte each
+#[cfg(not(feature = "sim"))]
+impl<const N: usize> SettingsStore for nanos_sdk::nvm::AtomicStorage<[u8; N]> {
+    fn read(&self, offset: usize) -> u8 {
+        self.get_ref().get(offset).copied().unwrap_or(0)
+    }
+
+    fn write(&mut self, offset: usize, value: u8) {
+        let mut bytes = *self.get_ref();
+        if let Some(byte) = bytes.get_mut(offset) {
+            if *byte != value {
+                *byte = value;
+                self.update(&bytes);
+            }
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Setting {
+    pub name: &'static str,
+    pub default: bool,
+    /// NVM byte holding the setting
+    pub offset: usize,
+    /// Values of a multiple choice
+    /// setting, empty for on/off ones
+    pub options: &'static [&'static str],
+    /// Index of the default option
+    pub default_option: usize,
+}
+
+impl Setting {
+    pub const fn new(name: &'static str, default: bool, offset: usize) -> Self {
+        Setting { name, default, offset, options: &[], default_option: 0 }
+    }
+
+    /// Setting taking one of `options`
+    pub const fn choice(name: &'static str, options: &'static [&'static str], default: usize,
+        offset: usize) -> Self {
+        Setting { options, default_option: default, ..Setting::new(name, false, offset) }
+    }
+
+    /// Index of the option chosen. The byte
+    /// holds it plus one, 0 being the default.
+    pub fn option(&self, store: &dyn SettingsStore) -> usize {
+        match store.read(self.offset) as usize {
+            0 => self.default_option,
+            n if n <= self.options.len() => n - 1,
+            _ => self.default_option,
+        }
+    }
+
+    pub fn set_option(&self, store: &mut dyn SettingsStore, index: usize) {
+        widget_debug_assert!(index < self.options.len(), "no such option");
+        store.write(self.offset, (index + 1) as u8);
+    }
+
+    /// Label of the current value
+    pub fn label(&self, store: &dyn SettingsStore) -> &'static str {
+        match self.options {
+            [] => ON_OFF.get(self.get(store)),
+            options => options.get(self.option(store)).copied().unwrap_or(""),
+        }
+    }
+
+    /// Toggle an on/off setting, or move
+    /// to the next option of a choice
+    pub fn cycle(&self, store: &mut dyn SettingsStore) {
+        match self.options.len() {
+            0 => {
+                self.toggle(store);
+            }
+            n => {
+                let next = (self.option(store) + 1) % n;
+                self.set_option(store, next);
+            }
+        }
+    }
+
+    /// The byte tells whether the value
+    /// differs from the default
+    pub fn get(&self, store: &dyn SettingsStore) -> bool {
+        self.default ^ (store.read(self.offset) == 1)
+    }
+
+    pub fn set(&self, store: &mut dyn SettingsStore, value: bool) {
+        store.write(self.offset, (value != self.default) as u8);
+    }
+
+    pub fn toggle(&self, store: &mut dyn SettingsStore) -> bool {
+        let value = !self.get(store);
+        self.set(store, value);
+        value
+    }
+}
+
+/// "Name: On" menu entry of `setting`
+fn entry<const N: usize>(menu: &mut OwnedMenu<N, MENU_ENTRY_LEN>, index: usize,
+    setting: &Setting, store: &dyn SettingsStore) {
+    let mut label = crate::text::UiString::<MENU_ENTRY_LEN>::new();
+    let _ = write!(label, "{}: {}", setting.name, setting.label(store));
+    menu.set(index, &label);
+}
+
+/// Menu of `settings` followed by "Back":
+/// selecting a setting cycles it, until
+/// "Back" is selected or the menu is left
+pub fn menu(settings: &[Setting], store: &mut dyn SettingsStore) {
+    widget_debug_assert!(settings.len() <= MAX_SETTINGS, "too many settings for the menu");
+    let settings = &settings[..settings.len().min(MAX_SETTINGS)];
+    let mut menu = OwnedMenu::<{ MAX_SETTINGS + 1 }, MENU_ENTRY_LEN>::new();
+    for (i, setting) in settings.iter().enumerate() {
+        menu.push("");
+        entry(&mut menu, i, setting, store);
+    }
+    menu.push(tr(Text::Back));
+    loop {
+        let selected = run(&mut menu).selected().unwrap_or(settings.len());
+        match settings.get(selected) {
+            Some(setting) => {
+                setting.cycle(store);
+                entry(&mut menu, selected, setting, store);
+            }
+            None => return,
+        }
+    }
+}
+
+/// Declare settings as `Setting` constants, and
+/// a list of them for `settings::menu`:
+/// `NAME: "Label" = default @ nvm_offset`
+#[macro_export]
+macro_rules! settings {
+    ($vis:vis $list:ident = [$($name:ident: $label:literal = $default:literal @ $offset:literal),+ $(,)?]) => {
+        $($vis const $name: $crate::settings::Setting =
+            $crate::settings::Setting::new($label, $default, $offset);)+
+        $vis const $list: &[$crate::settings::Setting] = &[$($name),+];
+    };
+}
@@ -0,0 +1,265 @@
+//! Reviews that keep the transport alive: while
+//! the user reviews, APDUs (keep-alives, remaining
+//! chunks of the data to sign...) still reach the
+//! app instead of waiting behind the UI.
+//!
+//! ```ignore
+//! let report = review_and_sign(&mut validator, &mut comm, |comm, ins| match ins {
+//!     INS_CHUNK => { store_chunk(comm.get_data()); comm.reply_ok() }
+//!     _ => comm.reply(StatusWords::Unknown),
+//! });
+//! if report.outcome.is_approved() { sign() }
+//! ```
+//!
+//! With `review_interruptible`, an APDU can also
+//! end the review, e.g. a cancel command. Each
+//! outcome has its status word:
+//!
+//! ```ignore
+//! let report = review_interruptible(&mut validator, &mut comm, |comm, ins| {
+//!     comm.reply_ok();
+//!     ins == INS_CANCEL
+//! });
+//! comm.reply(report.outcome);
+//! ```
+//!
+//! Any other widget can run the same way with
+//! `run_with_comm`, e.g. refusing APDUs until
+//! the user is done: `run_with_comm(&mut menu, &mut comm, reject_busy)`.
+//!
+//! Failures are shown with `ErrorScreen`, which
+//! explains status words from the app's table:
+//!
+//! ```ignore
+//! const REASONS: &[(u16, &str)] = &[(SW_DENIED, "Denied by user"), (0x6a80, "Invalid data")];
+//! ErrorScreen::new(sw).reasons(REASONS).show();
+//! ```
+
+use nanos_sdk::io::{self, Comm, Reply};
+
+use crate::bagls::{Displayable, Icons};
+use crate::context::UiContext;
+use crate::fmt::{hex, Case};
+use crate::screen;
+use crate::text::PageText;
+use crate::ui::{report, Actions, Page, WidgetState};
+use crate::widget::{run, AbortReason, Event, Outcome, Widget};
+
+/// How a review ended and what
+/// happened on the transport meanwhile
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SignReport {
+    pub outcome: Outcome,
+    /// APDUs handled during the review
+    pub commands: usize,
+    /// Ticker events received during the review
+    pub ticks: usize,
+}
+
+impl SignReport {
+    /// Whether any APDU came in during the review
+    pub const fn interrupted(&self) -> bool {
+        self.commands > 0
+    }
+}
+
+/// Status word replied by `reject_busy`:
+/// conditions of use not satisfied
+pub const SW_BUSY: u16 = 0x6985;
+
+/// Status word of a rejected review:
+/// denied by the user
+pub const SW_DENIED: u16 = 0x6985;
+/// Status word of a review aborted because
+/// the device was locked
+pub const SW_LOCKED: u16 = 0x5515;
+
+impl Outcome {
+    /// Status word to reply with: `0x9000` if
+    /// approved, `SW_LOCKED` if aborted for the
+    /// PIN, `SW_DENIED` otherwise
+    pub const fn status_word(self) -> u16 {
+        match self {
+            Outcome::Approved => 0x9000,
+            Outcome::Aborted(AbortReason::Pin) => SW_LOCKED,
+            Outcome::Rejected | Outcome::TimedOut | Outcome::Aborted(_) => SW_DENIED,
+        }
+    }
+}
+
+impl From<Outcome> for Reply {
+    fn from(outcome: Outcome) -> Reply {
+        Reply(outcome.status_word())
+    }
+}
+
+/// `on_command` refusing every APDU
+/// while a screen is shown
+pub fn reject_busy(comm: &mut Comm, _ins: u8) {
+    comm.reply(Reply(SW_BUSY));
+}
+
+/// Run `widget` on events from `comm` rather than
+/// `widget::run`, so APDUs received meanwhile are
+/// not lost. Each one is given to the widget as
+/// `Event::Command`, then to `on_command` with its
+/// instruction byte. `on_command` must reply to
+/// it, processing it or with `reject_busy`.
+pub fn run_with_comm<W, F>(widget: &mut W, comm: &mut Comm, mut on_command: F) -> W::Output
+where
+    W: Widget,
+    F: FnMut(&mut Comm, u8),
+{
+    match drive(widget, comm, |comm, ins| { on_command(comm, ins); false }).0 {
+        Some(output) => output,
+        None => unreachable!(),
+    }
+}
+
+/// Run the review `widget` on events from `comm`,
+/// handing every APDU received meanwhile to
+/// `on_command` along with its instruction byte.
+/// `on_command` must reply to each of them.
+pub fn review_and_sign<W, F>(widget: &mut W, comm: &mut Comm, mut on_command: F) -> SignReport
+where
+    W: Widget<Output = Outcome>,
+    F: FnMut(&mut Comm, u8),
+{
+    review_interruptible(widget, comm, |comm, ins| { on_command(comm, ins); false })
+}
+
+/// `review_and_sign`, where `on_command` also
+/// returns whether the APDU ends the review,
+/// e.g. a cancel command. The review then ends
+/// as `Outcome::Aborted(AbortReason::Command)`.
+pub fn review_interruptible<W, F>(widget: &mut W, comm: &mut Comm, on_command: F) -> SignReport
+where
+    W: Widget<Output = Outcome>,
+    F: FnMut(&mut Comm, u8) -> bool,
+{
+    let (outcome, commands, ticks) = drive(widget, comm, on_command);
+    let outcome = outcome.unwrap_or(Outcome::Aborted(AbortReason::Command));
+    SignReport { outcome, commands, ticks }
+}
+
+/// Output of `widget`, None if `on_command`
+/// ended it, with the number of APDUs and
+/// ticker events received
+fn drive<W, F>(widget: &mut W, comm: &mut Comm, mut on_command: F) -> (Option<W::Output>, usize, usize)
+where
+    W: Widget,
+    F: FnMut(&mut Comm, u8) -> bool,
+{
+    let (mut commands, mut ticks) = (0, 0);
+    widget.enter();
+    let previous = UiContext::enter(widget);
+    UiContext::exclusive(|| widget.draw());
+    let output = loop {
+        report(widget.state());
+        let event = match comm.next_event::<u8>() {
+            io::Event::Button(button) => Event::Button(button.into()),
+            io::Event::Command(ins) => {
+                commands += 1;
+                let output = widget.handle(Event::Command(ins));
+                if on_command(comm, ins) {
+                    break None
+                }
+                match output {
+                    Some(output) => break Some(output),
+                    // The handler may have drawn over the widget
+                    None => {
+                        UiContext::exclusive(|| widget.draw());
+                    }
+                }
+                continue
+            }
+            io::Event::Ticker => {
+                ticks += 1;
+                Event::Ticker
+            }
+        };
+        if let Some(output) = widget.handle(event) {
+            break Some(output)
+        }
+    };
+    UiContext::leave(previous);
+    (output, commands, ticks)
+}
+
+/// First line of an `ErrorScreen` for
+/// status words missing from the table
+pub const ERROR_TITLE: &str = "Error";
+
+/// Cross badge and the reason of a failure,
+/// looked up by status word in the app's table,
+/// or the status word itself. Any button
+/// release dismisses it.
+#[derive(Copy, Clone)]
+pub struct ErrorScreen<'a> {
+    code: u16,
+    reasons: &'a [(u16, &'a str)],
+}
+
+impl<'a> ErrorScreen<'a> {
+    pub const fn new(code: u16) -> Self {
+        ErrorScreen { code, reasons: &[] }
+    }
+
+    pub const fn from_reply(reply: Reply) -> Self {
+        Self::new(reply.0)
+    }
+
+    /// For the status word of `outcome`
+    pub const fn from_outcome(outcome: Outcome) -> Self {
+        Self::new(outcome.status_word())
+    }
+
+    /// Status words and their reasons
+    pub const fn reasons(self, reasons: &'a [(u16, &'a str)]) -> Self {
+        ErrorScreen { reasons, ..self }
+    }
+
+    pub const fn code(&self) -> u16 {
+        self.code
+    }
+
+    /// Reason of the status word, if in the table
+    pub fn reason(&self) -> Option<&'a str> {
+        self.reasons.iter().find(|(code, _)| *code == self.code).map(|(_, reason)| *reason)
+    }
+
+    pub fn show(&self) {
+        run(&mut { *self })
+    }
+}
+
+impl Widget for ErrorScreen<'_> {
+    type Output = ();
+
+    fn draw(&self) {
+        let mut digits = [0u8; 4];
+        let mut code = PageText::<6>::from("0x");
+        code.push_str(hex(&self.code.to_be_bytes(), &mut digits, Case::Lower).unwrap_or(""));
+        let (reason, unknown);
+        let lines: &[&str] = match self.reason() {
+            Some(r) => {
+                reason = [r];
+                &reason
+            }
+            None => {
+                unknown = [ERROR_TITLE, code.as_str()];
+                &unknown
+            }
+        };
+        screen::clear();
+        Page::new(lines).icon(Icons::CrossBadge).paint();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<()> {
+        event.action().map(|_| ())
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("ErrorScreen", 0, 1).actions(Actions::ALL)
+    }
+}
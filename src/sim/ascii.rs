@@ -0,0 +1,100 @@
+//! ASCII-art rendering of the screen, for
+//! snapshot tests and documentation.
+//!
+//! Each cell covers 4x8 pixels. Labels are
+//! written one character per cell, centered like
+//! on the device, with bold text between `*`.
+//! Arrows are drawn as `<` `>` `^` `v` and
+//! other icons as a short tag such as `(V)`.
+
+use std::format;
+use std::string::String;
+use std::vec;
+use std::vec::Vec;
+
+use super::screen::{Element, ScreenSize};
+use crate::bagls::{BaglTypes, Font};
+
+const CELL_WIDTH: usize = 4;
+const CELL_HEIGHT: usize = 8;
+
+fn icon_tag(glyph_id: u8) -> &'static str {
+    match glyph_id {
+        6 => "V",
+        7 => "X",
+        8 => "(V)",
+        9 => "<",
+        10 => ">",
+        11 => "^",
+        12 => "v",
+        14 => "(X)",
+        24 => "($)",
+        27 => "(o)",
+        _ => "(?)",
+    }
+}
+
+struct Grid {
+    cols: usize,
+    rows: Vec<Vec<char>>,
+}
+
+impl Grid {
+    fn put(&mut self, col: i32, row: i32, s: &str) {
+        if row < 0 || row as usize >= self.rows.len() {
+            return
+        }
+        for (i, c) in s.chars().enumerate() {
+            let x = col + i as i32;
+            if x >= 0 && (x as usize) < self.cols {
+                self.rows[row as usize][x as usize] = c;
+            }
+        }
+    }
+}
+
+/// Render a list of elements, in paint order
+pub fn render(elements: &[Element], size: ScreenSize) -> String {
+    let cols = size.width / CELL_WIDTH;
+    let mut grid = Grid { cols, rows: vec![vec![' '; cols]; size.height / CELL_HEIGHT] };
+
+    for e in elements {
+        let (x, y) = (e.x as i32, e.y as i32);
+        let (w, h) = (e.width as i32, e.height as i32);
+        if e.kind == BaglTypes::Icon as u8 {
+            let tag = icon_tag(e.icon_id);
+            let col = (x + w / 2) / CELL_WIDTH as i32 - tag.len() as i32 / 2;
+            grid.put(col, (y + h / 2) / CELL_HEIGHT as i32, tag);
+        } else if let Some(text) = &e.text {
+            let mut s = String::new();
+            let bold = e.font_id & 0xff == Font::OpenSansExtrabold11px as u16;
+            if bold {
+                s.push('*');
+            }
+            s.push_str(text);
+            if bold {
+                s.push('*');
+            }
+            let len = s.chars().count() as i32;
+            let col = (x + w / 2) / CELL_WIDTH as i32 - len / 2;
+            grid.put(col, (y - CELL_HEIGHT as i32 / 2) / CELL_HEIGHT as i32, &s);
+        } else if e.kind == BaglTypes::Rectangle as u8 && e.fill {
+            let c = if e.fgcolor != 0 { '#' } else { ' ' };
+            for row in y / CELL_HEIGHT as i32..(y + h + CELL_HEIGHT as i32 - 1) / CELL_HEIGHT as i32 {
+                for col in x / CELL_WIDTH as i32..(x + w + CELL_WIDTH as i32 - 1) / CELL_WIDTH as i32 {
+                    grid.put(col, row, c.encode_utf8(&mut [0; 4]));
+                }
+            }
+        }
+    }
+
+    let border = format!("+{}+\n", "-".repeat(cols));
+    let mut out = border.clone();
+    for row in grid.rows {
+        out.push('|');
+        out.extend(row);
+        out.push_str("|\n");
+    }
+    out.push_str(&border);
+    out
+}
@@ -0,0 +1,56 @@
+//! Virtual time for ticker-dependent tests:
+//! timeouts, auto-repeat and animations.
+
+use super::push_tick;
+
+/// Where the ticker events received by
+/// the app come from
+pub trait TickSource {
+    /// Deliver `ticks` ticker events
+    fn advance(&mut self, ticks: u32);
+    /// Ticks delivered so far
+    fn now(&self) -> u32;
+}
+
+/// Deterministic stand-in for the device ticker.
+/// Advancing it queues ticker events, which
+/// the app receives through the same SEPH path
+/// as the 100ms ticker on hardware.
+pub struct FakeClock {
+    now: u32,
+}
+
+impl FakeClock {
+    pub const fn new() -> Self {
+        FakeClock { now: 0 }
+    }
+
+    /// Advance virtual time by `ticks` ticker events
+    pub fn advance(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            push_tick();
+        }
+        self.now += ticks;
+    }
+
+    /// Virtual ticks elapsed on this clock
+    pub fn now(&self) -> u32 {
+        self.now
+    }
+}
+
+impl TickSource for FakeClock {
+    fn advance(&mut self, ticks: u32) {
+        FakeClock::advance(self, ticks)
+    }
+
+    fn now(&self) -> u32 {
+        FakeClock::now(self)
+    }
+}
+
+impl Default for FakeClock {
+    fn default() -> Self {
+        FakeClock::new()
+    }
+}
@@ -0,0 +1,81 @@
+//! High-level drivers for the built-in widgets:
+//! each helper queues the events a user would
+//! produce, runs the widget and returns its result.
+
+use std::string::String;
+
+use super::push_keys;
+use crate::flow::Flow;
+use crate::ui::*;
+use crate::widget::Outcome;
+
+fn rights(n: usize) -> String {
+    "r".repeat(n)
+}
+
+/// Validate the message of a `Validator`
+pub fn approve(validator: &Validator) -> Outcome {
+    push_keys("b");
+    validator.ask()
+}
+
+/// Select "Cancel" on a `Validator`
+pub fn reject(validator: &Validator) -> Outcome {
+    push_keys("rb");
+    validator.ask()
+}
+
+/// Go through every page of a
+/// `MessageValidator`, then confirm
+pub fn approve_review(review: &MessageValidator) -> Outcome {
+    push_keys(&rights(review.page_count() - 2));
+    push_keys("b");
+    review.ask()
+}
+
+/// Go through every page of a
+/// `MessageValidator`, then reject
+pub fn reject_review(review: &MessageValidator) -> Outcome {
+    push_keys(&rights(review.page_count() - 1));
+    push_keys("b");
+    review.ask()
+}
+
+/// Scroll down to entry `index` of a `Menu`
+/// and select it
+pub fn select(menu: &Menu, index: usize) -> MenuResult {
+    push_keys(&rights(index));
+    push_keys("b");
+    menu.show()
+}
+
+/// Go forward to step `index` of a `Flow`
+/// and confirm it, returning the index of
+/// the step that ended the flow
+pub fn confirm_step(flow: &Flow, index: usize) -> usize {
+    push_keys(&rights(index));
+    push_keys("b");
+    flow.run()
+}
+
+/// Scroll to the last page of a
+/// `MessageScroller`, then exit
+pub fn read_to_end(scroller: &MessageScroller) {
+    push_keys(&rights(scroller.page_count().saturating_sub(1)));
+    push_keys("b");
+    scroller.event_loop();
+}
+
+/// Scroll to the last screen of a
+/// `HScroller`, then exit
+pub fn browse_to_end(scroller: &HScroller) {
+    push_keys(&rights(scroller.len().saturating_sub(1)));
+    push_keys("b");
+    scroller.event_loop();
+}
+
+/// Dismiss a `SingleMessage` with a right click
+pub fn dismiss(message: &SingleMessage) {
+    push_keys("r");
+    message.show_and_wait();
+}
@@ -0,0 +1,148 @@
+//! Expected-screens assertions, see `expect_flow!`
+
+use std::string::String;
+use std::vec::Vec;
+
+use super::{checkpoint, pending_events, push_buttons, push_checkpoint, push_tick};
+
+/// Physical buttons, as named in `expect_flow!`
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Button {
+    Left,
+    Right,
+    Both,
+}
+
+impl Button {
+    const fn mask(self) -> u8 {
+        match self {
+            Button::Left => 1,
+            Button::Right => 2,
+            Button::Both => 3,
+        }
+    }
+}
+
+/// Scripted interaction with a widget:
+/// events are queued up front, then `verify`
+/// checks what the widget displayed.
+pub struct ExpectFlow {
+    /// Checkpoint ids and the text expected there
+    expected: Vec<(usize, String)>,
+}
+
+impl ExpectFlow {
+    pub fn new() -> Self {
+        ExpectFlow { expected: Vec::new() }
+    }
+
+    /// Press and release a button. Both buttons
+    /// are pressed one after the other, as users do.
+    pub fn press(&mut self, button: Button) {
+        if button == Button::Both {
+            push_buttons(1);
+        }
+        self.hold(button);
+        self.release();
+    }
+
+    /// Press a button without releasing it
+    pub fn hold(&mut self, button: Button) {
+        push_buttons(button.mask());
+    }
+
+    /// Release all buttons
+    pub fn release(&mut self) {
+        push_buttons(0);
+    }
+
+    /// Let `n` ticker events elapse
+    pub fn tick(&mut self, n: u32) {
+        for _ in 0..n {
+            push_tick();
+        }
+    }
+
+    /// Expect a label containing `text` on
+    /// screen once the previous events are handled
+    pub fn see(&mut self, text: &str) {
+        self.expected.push((push_checkpoint(), String::from(text)));
+    }
+
+    /// Check the captured screens, and that the
+    /// widget consumed all the scripted events
+    pub fn verify(&self) {
+        for (n, (id, text)) in self.expected.iter().enumerate() {
+            let seen = checkpoint(*id).unwrap_or_else(|| {
+                panic!("expect_flow: widget returned before `see {:?}` (#{})", text, n)
+            });
+            assert!(seen.iter().any(|t| t.contains(text.as_str())),
+                "expect_flow: expected {:?} on screen, found {:?}", text, seen);
+        }
+        let left = pending_events();
+        assert!(left == 0, "expect_flow: widget returned with {} event(s) left", left);
+    }
+}
+
+impl Default for ExpectFlow {
+    fn default() -> Self {
+        ExpectFlow::new()
+    }
+}
+
+/// Drive a widget with scripted events and
+/// assert what it shows and returns:
+///
+/// ```ignore
+/// expect_flow!(Validator::new("Sign?").ask(), [
+///     see "Sign?",
+///     press Right,
+///     see "Cancel",
+///     press Both,
+///     returns Outcome::Rejected
+/// ]);
+/// ```
+///
+/// Steps are `press B`, `hold B`, `release`,
+/// `tick N` and `see "text"`, where `see` checks
+/// the screen right before the next event is
+/// read. An optional final `returns v` compares
+/// the widget's result. The result is returned.
+#[macro_export]
+macro_rules! expect_flow {
+    (@step $f:ident, $run:expr; press $b:ident $(, $($rest:tt)*)?) => {{
+        $f.press($crate::sim::Button::$b);
+        $crate::expect_flow!(@step $f, $run; $($($rest)*)?)
+    }};
+    (@step $f:ident, $run:expr; hold $b:ident $(, $($rest:tt)*)?) => {{
+        $f.hold($crate::sim::Button::$b);
+        $crate::expect_flow!(@step $f, $run; $($($rest)*)?)
+    }};
+    (@step $f:ident, $run:expr; release $(, $($rest:tt)*)?) => {{
+        $f.release();
+        $crate::expect_flow!(@step $f, $run; $($($rest)*)?)
+    }};
+    (@step $f:ident, $run:expr; tick $n:expr $(, $($rest:tt)*)?) => {{
+        $f.tick($n);
+        $crate::expect_flow!(@step $f, $run; $($($rest)*)?)
+    }};
+    (@step $f:ident, $run:expr; see $text:expr $(, $($rest:tt)*)?) => {{
+        $f.see($text);
+        $crate::expect_flow!(@step $f, $run; $($($rest)*)?)
+    }};
+    (@step $f:ident, $run:expr; returns $v:expr $(,)?) => {{
+        let result = $run;
+        $f.verify();
+        assert_eq!(result, $v, "expect_flow: unexpected return value");
+        result
+    }};
+    (@step $f:ident, $run:expr;) => {{
+        let result = $run;
+        $f.verify();
+        result
+    }};
+    ($run:expr, [$($steps:tt)*]) => {{
+        let mut flow = $crate::sim::ExpectFlow::new();
+        $crate::expect_flow!(@step flow, $run; $($steps)*)
+    }};
+}
@@ -0,0 +1,85 @@
+//! Keyboard to button mapping, for scripting
+//! events concisely and for manual testing
+//! from a terminal.
+//!
+//! | key                        | action              |
+//! |----------------------------|---------------------|
+//! | `l` `h` `a` `<`, left/up   | left click          |
+//! | `r` `d` `>`, right/down    | right click         |
+//! | `b` `s` `x`, empty line    | both buttons click  |
+//! | `L` `R` `B`                | press and hold      |
+//! | `.`                        | release all buttons |
+//!
+//! Other characters, such as whitespace, are ignored.
+
+use std::io::{self, BufRead, Write};
+use std::print;
+use std::string::String;
+
+use super::push_buttons;
+
+/// Button masks sent for a single key,
+/// or `None` if the key is not mapped
+pub fn key_events(key: char) -> Option<&'static [u8]> {
+    match key {
+        'l' | 'h' | 'a' | '<' => Some(&[1, 0]),
+        'r' | 'd' | '>' => Some(&[2, 0]),
+        'b' | 's' | 'x' => Some(&[1, 3, 0]),
+        'L' => Some(&[1]),
+        'R' => Some(&[2]),
+        'B' => Some(&[3]),
+        '.' => Some(&[0]),
+        _ => None,
+    }
+}
+
+/// Queue the button events for a string of
+/// keys. ANSI arrow key sequences are accepted.
+pub fn push_keys(keys: &str) {
+    let mut chars = keys.chars().peekable();
+    while let Some(c) = chars.next() {
+        let c = match (c, chars.peek()) {
+            ('\x1b', Some('[')) => {
+                chars.next();
+                match chars.next() {
+                    Some('A') | Some('D') => 'l',
+                    Some('B') | Some('C') => 'r',
+                    _ => continue,
+                }
+            }
+            _ => c,
+        };
+        if let Some(masks) = key_events(c) {
+            for m in masks.iter() {
+                push_buttons(*m);
+            }
+        }
+    }
+}
+
+/// Print the screen, then read one line from
+/// stdin and queue its events. An empty line
+/// is a both buttons click. Returns `false`
+/// once stdin is closed.
+pub(crate) fn prompt() -> bool {
+    print!("[");
+    for t in super::texts() {
+        print!(" {} |", t);
+    }
+    print!(" ] > ");
+    let _ = io::stdout().flush();
+
+    let mut line = String::new();
+    match io::stdin().lock().read_line(&mut line) {
+        Ok(0) | Err(_) => false,
+        Ok(_) => {
+            let line = line.trim_end_matches(&['\r', '\n'][..]);
+            if line.is_empty() {
+                push_keys("b");
+            } else {
+                push_keys(line);
+            }
+            true
+        }
+    }
+}
@@ -0,0 +1,201 @@
+//! Host-side backend for testing widgets,
+//! enabled with the `sim` feature.
+//!
+//! SEPH traffic is redirected to in-memory
+//! state: events are taken from a scripted
+//! queue filled by the test, which lets the
+//! blocking widgets run under `cargo test`.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::ui::WidgetState;
+
+mod ascii;
+mod clock;
+pub mod driver;
+mod expect;
+mod keys;
+mod screen;
+pub(crate) mod seph;
+#[cfg(test)]
+mod tests;
+pub use clock::{FakeClock, TickSource};
+pub use expect::{Button, ExpectFlow};
+pub use keys::{key_events, push_keys};
+pub use screen::*;
+
+/// SEPH event tags the simulator knows about
+pub const BUTTON_PUSH_EVENT: u8 = 0x05;
+pub const DISPLAY_PROCESSED_EVENT: u8 = 0x0d;
+pub const TICKER_EVENT: u8 = 0x0e;
+
+/// An entry of the scripted event queue
+enum Scripted {
+    Packet(Vec<u8>),
+    /// Capture the screen texts when reached
+    Checkpoint(usize),
+}
+
+struct State {
+    /// Events waiting to be received by the app
+    events: VecDeque<Scripted>,
+    /// Whether the app sent a status and
+    /// now waits for the next event
+    status_sent: bool,
+    /// A display status was sent, so the next
+    /// event is a display processed one
+    display_pending: bool,
+    /// Packet currently being sent by the app
+    packet: Vec<u8>,
+    /// Bytes still expected for `packet`
+    remaining: usize,
+    /// Elements currently visible, in paint order
+    elements: Vec<Element>,
+    /// Ticker events received by the app so far
+    ticks: u32,
+    /// Distinct states reported by widgets
+    states: Vec<WidgetState>,
+    /// Read events from stdin once the
+    /// scripted queue is exhausted
+    interactive: bool,
+    /// Screen texts captured at each checkpoint
+    checkpoints: Vec<Option<Vec<String>>>,
+    size: ScreenSize,
+}
+
+impl State {
+    const fn new() -> Self {
+        State {
+            events: VecDeque::new(),
+            status_sent: false,
+            display_pending: false,
+            packet: Vec::new(),
+            remaining: 0,
+            elements: Vec::new(),
+            ticks: 0,
+            states: Vec::new(),
+            interactive: false,
+            checkpoints: Vec::new(),
+            size: ScreenSize::NANO_S,
+        }
+    }
+}
+
+std::thread_local! {
+    static STATE: RefCell<State> = const { RefCell::new(State::new()) };
+}
+
+fn with_state<R>(f: impl FnOnce(&mut State) -> R) -> R {
+    STATE.with(|s| f(&mut s.borrow_mut()))
+}
+
+/// Clear all simulator state
+pub fn reset() {
+    with_state(|s| *s = State::new());
+}
+
+/// Queue a raw SEPH event packet
+pub fn push_packet(packet: &[u8]) {
+    with_state(|s| s.events.push_back(Scripted::Packet(packet.to_vec())));
+}
+
+/// Queue a button push event reporting
+/// `mask` as the pressed buttons
+/// (1: left, 2: right, 3: both, 0: none)
+pub fn push_buttons(mask: u8) {
+    push_packet(&[BUTTON_PUSH_EVENT, 0, 1, mask << 1]);
+}
+
+/// Queue a single ticker event
+pub fn push_tick() {
+    push_packet(&[TICKER_EVENT, 0, 0]);
+}
+
+/// Simulate a screen of the given size, e.g.
+/// `ScreenSize::NANO_X`. Defaults to the Nano S.
+pub fn set_screen_size(size: ScreenSize) {
+    with_state(|s| s.size = size);
+}
+
+pub fn screen_size() -> ScreenSize {
+    with_state(|s| s.size)
+}
+
+/// When enabled, running out of scripted events
+/// prints the screen and reads keys from stdin
+/// (see `keys` for the mapping) instead of panicking
+pub fn set_interactive(on: bool) {
+    with_state(|s| s.interactive = on);
+}
+
+/// Number of ticker events received by the app
+pub fn ticks_received() -> u32 {
+    with_state(|s| s.ticks)
+}
+
+/// Number of scripted events not yet received
+pub fn pending_events() -> usize {
+    with_state(|s| {
+        s.events.iter().filter(|e| matches!(e, Scripted::Packet(_))).count()
+    })
+}
+
+/// Queue a checkpoint: the screen texts are
+/// captured when the app asks for the event
+/// following it. Returns the checkpoint id.
+pub fn push_checkpoint() -> usize {
+    with_state(|s| {
+        let id = s.checkpoints.len();
+        s.checkpoints.push(None);
+        s.events.push_back(Scripted::Checkpoint(id));
+        id
+    })
+}
+
+/// Screen texts captured at a checkpoint,
+/// or `None` if it was not reached
+pub fn checkpoint(id: usize) -> Option<Vec<String>> {
+    with_state(|s| s.checkpoints.get(id).cloned().flatten())
+}
+
+/// Elements currently on screen, in paint order
+pub fn elements() -> Vec<Element> {
+    with_state(|s| s.elements.clone())
+}
+
+/// Strings of the labels currently on screen
+pub fn texts() -> Vec<String> {
+    with_state(|s| s.elements.iter().filter_map(|e| e.text.clone()).collect())
+}
+
+/// Last state reported by a widget
+pub fn widget_state() -> Option<WidgetState> {
+    with_state(|s| s.states.last().copied())
+}
+
+/// Every distinct state reported by widgets,
+/// oldest first
+pub fn state_history() -> Vec<WidgetState> {
+    with_state(|s| s.states.clone())
+}
+
+pub(crate) fn record_state(state: WidgetState) {
+    with_state(|s| {
+        if s.states.last() != Some(&state) {
+            s.states.push(state);
+        }
+    });
+}
+
+/// ASCII-art rendering of the current screen
+pub fn ascii() -> String {
+    with_state(|s| ascii::render(&s.elements, s.size))
+}
+
+/// Rasterized image of the current screen
+pub fn screenshot() -> Screenshot {
+    with_state(|s| Screenshot::render(&s.elements, s.size))
+}
@@ -0,0 +1,375 @@
+//! Screen model of the simulator: BAGL
+//! elements painted by the app, and a coarse
+//! 1-bpp rasterization used for golden images
+
+use std::fmt::Write as _;
+use std::format;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::vec;
+use std::vec::Vec;
+
+use crate::bagls::{BaglComponent, BaglTypes};
+
+/// Dimensions of the simulated screen
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ScreenSize {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl ScreenSize {
+    pub const NANO_S: ScreenSize = ScreenSize { width: 128, height: 32 };
+    pub const NANO_SP: ScreenSize = ScreenSize { width: 128, height: 64 };
+    pub const NANO_X: ScreenSize = ScreenSize { width: 128, height: 64 };
+}
+
+/// Approximate advance of a character,
+/// used to rasterize text as blocks
+const CHAR_WIDTH: usize = 6;
+const CHAR_HEIGHT: usize = 8;
+
+/// A BAGL element painted by the app
+#[derive(Clone, Debug, PartialEq)]
+pub struct Element {
+    pub kind: u8,
+    pub x: i16,
+    pub y: i16,
+    pub width: u16,
+    pub height: u16,
+    pub fill: bool,
+    pub fgcolor: u32,
+    pub bgcolor: u32,
+    pub font_id: u16,
+    pub icon_id: u8,
+    pub text: Option<String>,
+}
+
+impl Element {
+    /// Decode the payload of a display status
+    pub(crate) fn decode(payload: &[u8]) -> Option<Element> {
+        let size = core::mem::size_of::<BaglComponent>();
+        if payload.len() < size {
+            return None
+        }
+        let c = unsafe {
+            core::ptr::read_unaligned(payload.as_ptr() as *const BaglComponent)
+        };
+        let text = match c.type_ == BaglTypes::LabelLine as u8 {
+            true => Some(String::from_utf8_lossy(&payload[size..]).into_owned()),
+            false => None,
+        };
+        Some(Element {
+            kind: c.type_,
+            x: c.x,
+            y: c.y,
+            width: c.width,
+            height: c.height,
+            fill: c.fill != 0,
+            fgcolor: c.fgcolor,
+            bgcolor: c.bgcolor,
+            font_id: c.font_id,
+            icon_id: c.icon_id,
+            text,
+        })
+    }
+
+    /// Whether painting this element erases
+    /// what lies below it
+    pub fn erases(&self) -> bool {
+        self.kind == BaglTypes::Rectangle as u8 && self.fill && self.fgcolor == 0
+    }
+
+    /// Area covered on screen, as (x, y, width, height).
+    /// Labels are positioned by their baseline.
+    pub fn bounds(&self) -> (i32, i32, i32, i32) {
+        match self.text {
+            Some(_) => (self.x as i32, self.y as i32 - CHAR_HEIGHT as i32,
+                self.width as i32, CHAR_HEIGHT as i32),
+            None => (self.x as i32, self.y as i32, self.width as i32, self.height as i32),
+        }
+    }
+
+    /// Whether `other` lies entirely within this element
+    pub fn covers(&self, other: &Element) -> bool {
+        let (x, y, w, h) = self.bounds();
+        let (ox, oy, ow, oh) = other.bounds();
+        ox >= x && oy >= y && ox + ow <= x + w && oy + oh <= y + h
+    }
+}
+
+/// A 1-bpp image of the screen. Rectangles are
+/// exact, icons are drawn as their outline and
+/// text as one block per character: enough to
+/// catch layout regressions without font data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Screenshot {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<bool>,
+}
+
+impl Screenshot {
+    pub fn new(width: usize, height: usize) -> Self {
+        Screenshot { width, height, pixels: vec![false; width * height] }
+    }
+
+    /// Rasterize a list of elements, in paint order
+    pub fn render(elements: &[Element], size: ScreenSize) -> Self {
+        let mut shot = Screenshot::new(size.width, size.height);
+        for e in elements {
+            shot.draw(e);
+        }
+        shot
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, on: bool) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.pixels[y as usize * self.width + x as usize] = on;
+        }
+    }
+
+    fn fill_rect(&mut self, x: i32, y: i32, w: i32, h: i32, on: bool) {
+        for j in y..y + h {
+            for i in x..x + w {
+                self.set(i, j, on);
+            }
+        }
+    }
+
+    fn draw(&mut self, e: &Element) {
+        let (x, y) = (e.x as i32, e.y as i32);
+        let (w, h) = (e.width as i32, e.height as i32);
+        if e.kind == BaglTypes::Rectangle as u8 {
+            if e.fill {
+                self.fill_rect(x, y, w, h, e.fgcolor != 0);
+            }
+        } else if e.kind == BaglTypes::Icon as u8 {
+            self.fill_rect(x, y, w, 1, true);
+            self.fill_rect(x, y + h - 1, w, 1, true);
+            self.fill_rect(x, y, 1, h, true);
+            self.fill_rect(x + w - 1, y, 1, h, true);
+        } else if let Some(text) = &e.text {
+            let n = text.chars().count() as i32;
+            let start = x + (w - n * CHAR_WIDTH as i32) / 2;
+            let top = y - CHAR_HEIGHT as i32;
+            for (i, c) in text.chars().enumerate() {
+                if !c.is_whitespace() {
+                    let cx = start + i as i32 * CHAR_WIDTH as i32;
+                    self.fill_rect(cx, top, CHAR_WIDTH as i32 - 1, CHAR_HEIGHT as i32 - 1, true);
+                }
+            }
+        }
+    }
+
+    /// Encode as a plain PBM (P1) image
+    pub fn to_pbm(&self) -> String {
+        let mut out = format!("P1\n{} {}\n", self.width, self.height);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                out.push(if self.get(x, y) { '1' } else { '0' });
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Decode a plain PBM (P1) image
+    pub fn from_pbm(data: &str) -> Option<Self> {
+        let mut tokens = data.lines()
+            .filter(|l| !l.starts_with('#'))
+            .flat_map(|l| l.split_whitespace());
+        if tokens.next()? != "P1" {
+            return None
+        }
+        let width = tokens.next()?.parse().ok()?;
+        let height = tokens.next()?.parse().ok()?;
+        let mut shot = Screenshot::new(width, height);
+        let mut bits = tokens.flat_map(|t| t.chars());
+        for i in 0..width * height {
+            shot.pixels[i] = bits.next()? == '1';
+        }
+        Some(shot)
+    }
+
+    /// Compare against an expected image.
+    /// Returns `None` if both are identical.
+    pub fn diff(&self, expected: &Screenshot) -> Option<ScreenDiff> {
+        if self == expected {
+            return None
+        }
+        let width = self.width.max(expected.width);
+        let height = self.height.max(expected.height);
+        let pixel = |s: &Screenshot, x: usize, y: usize| {
+            x < s.width && y < s.height && s.get(x, y)
+        };
+        let mut xor = Screenshot::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let d = pixel(self, x, y) != pixel(expected, x, y);
+                xor.set(x as i32, y as i32, d);
+            }
+        }
+        let regions = xor.regions();
+        Some(ScreenDiff { expected: expected.clone(), actual: self.clone(), xor, regions })
+    }
+
+    /// Bounding boxes of the connected
+    /// groups of set pixels
+    fn regions(&self) -> Vec<Region> {
+        let mut seen = vec![false; self.pixels.len()];
+        let mut regions = Vec::new();
+        for start in 0..self.pixels.len() {
+            if !self.pixels[start] || seen[start] {
+                continue
+            }
+            let (x0, y0) = (start % self.width, start / self.width);
+            let mut r = Region { x: x0, y: y0, width: 1, height: 1, pixels: 0 };
+            let (mut x1, mut y1) = (x0, y0);
+            let mut stack = vec![start];
+            seen[start] = true;
+            while let Some(i) = stack.pop() {
+                let (x, y) = (i % self.width, i / self.width);
+                r.pixels += 1;
+                r.x = r.x.min(x);
+                r.y = r.y.min(y);
+                x1 = x1.max(x);
+                y1 = y1.max(y);
+                for (dx, dy) in [(-1i32, 0i32), (1, 0), (0, -1), (0, 1)].iter() {
+                    let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                    if nx < 0 || ny < 0 || nx as usize >= self.width || ny as usize >= self.height {
+                        continue
+                    }
+                    let n = ny as usize * self.width + nx as usize;
+                    if self.pixels[n] && !seen[n] {
+                        seen[n] = true;
+                        stack.push(n);
+                    }
+                }
+            }
+            r.width = x1 - r.x + 1;
+            r.height = y1 - r.y + 1;
+            regions.push(r);
+        }
+        regions
+    }
+}
+
+/// A rectangular area where two screenshots differ
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Region {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    /// Number of differing pixels in the area
+    pub pixels: usize,
+}
+
+/// Result of comparing a screenshot
+/// against its golden image
+#[derive(Clone, Debug)]
+pub struct ScreenDiff {
+    pub expected: Screenshot,
+    pub actual: Screenshot,
+    /// Pixels that differ between both images
+    pub xor: Screenshot,
+    pub regions: Vec<Region>,
+}
+
+impl ScreenDiff {
+    /// Human readable list of differing regions
+    pub fn summary(&self) -> String {
+        let total: usize = self.regions.iter().map(|r| r.pixels).sum();
+        let mut out = format!("{} pixel(s) differ in {} region(s)\n",
+            total, self.regions.len());
+        for r in self.regions.iter() {
+            let _ = writeln!(out, "  {}x{} at ({}, {}): {} pixel(s)",
+                r.width, r.height, r.x, r.y, r.pixels);
+        }
+        if self.expected.width != self.actual.width
+            || self.expected.height != self.actual.height {
+            let _ = writeln!(out, "  size mismatch: expected {}x{}, got {}x{}",
+                self.expected.width, self.expected.height,
+                self.actual.width, self.actual.height);
+        }
+        out
+    }
+
+    /// Expected, actual and XOR images side by
+    /// side, separated by a 2 pixel wide bar
+    pub fn side_by_side(&self) -> Screenshot {
+        const GAP: usize = 2;
+        let panels = [&self.expected, &self.actual, &self.xor];
+        let width = panels.iter().map(|p| p.width).sum::<usize>() + 2 * GAP;
+        let height = panels.iter().map(|p| p.height).max().unwrap_or(0);
+        let mut out = Screenshot::new(width, height);
+        let mut left = 0;
+        for (n, p) in panels.iter().enumerate() {
+            for y in 0..p.height {
+                for x in 0..p.width {
+                    out.set((left + x) as i32, y as i32, p.get(x, y));
+                }
+            }
+            left += p.width;
+            if n < panels.len() - 1 {
+                out.fill_rect(left as i32, 0, GAP as i32, height as i32, true);
+                left += GAP;
+            }
+        }
+        out
+    }
+
+    /// Store `<name>.diff.pbm` and `<name>.diff.txt`
+    /// in `dir`, returning the path of the image
+    pub fn save(&self, dir: &Path, name: &str) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let image = dir.join(format!("{}.diff.pbm", name));
+        fs::write(&image, self.side_by_side().to_pbm())?;
+        fs::write(dir.join(format!("{}.diff.txt", name)), self.summary())?;
+        Ok(image)
+    }
+}
+
+/// Directory where diff reports are stored:
+/// `$SIM_DIFF_DIR`, or `target/sim-diffs`
+pub fn diff_dir() -> PathBuf {
+    match std::env::var_os("SIM_DIFF_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from("target").join("sim-diffs"),
+    }
+}
+
+/// Compare the current screen with the golden
+/// image at `golden`. On mismatch, a diff report
+/// is stored in `diff_dir()` and the test panics.
+/// With `SIM_UPDATE_GOLDEN` set, the golden image
+/// is (re)written instead.
+pub fn assert_golden(golden: &Path) {
+    let actual = super::screenshot();
+    if std::env::var_os("SIM_UPDATE_GOLDEN").is_some() {
+        if let Some(dir) = golden.parent() {
+            fs::create_dir_all(dir).expect("sim: cannot create golden directory");
+        }
+        fs::write(golden, actual.to_pbm()).expect("sim: cannot write golden image");
+        return
+    }
+    let data = fs::read_to_string(golden).unwrap_or_else(|e| {
+        panic!("sim: cannot read golden image {}: {}", golden.display(), e)
+    });
+    let expected = Screenshot::from_pbm(&data).unwrap_or_else(|| {
+        panic!("sim: {} is not a plain PBM image", golden.display())
+    });
+    if let Some(diff) = actual.diff(&expected) {
+        let name = golden.file_stem().and_then(|s| s.to_str()).unwrap_or("screen");
+        let report = diff.save(&diff_dir(), name);
+        panic!("sim: screen differs from {}\n{}report: {:?}",
+            golden.display(), diff.summary(), report);
+    }
+}
@@ -0,0 +1,89 @@
+//! In-memory replacement for `nanos_sdk::seph`:
+//! status packets update the simulated screen,
+//! and received events come from the scripted queue.
+
+use super::*;
+
+pub use nanos_sdk::seph::SephTags;
+
+pub fn seph_send(buffer: &[u8]) {
+    with_state(|s| {
+        if s.remaining == 0 {
+            if buffer.len() < 3 {
+                return
+            }
+            let len = u16::from_be_bytes([buffer[1], buffer[2]]) as usize;
+            s.packet.clear();
+            s.remaining = len + 3;
+            s.status_sent = true;
+        }
+        let n = buffer.len().min(s.remaining);
+        s.packet.extend_from_slice(&buffer[..n]);
+        s.remaining -= n;
+        if s.remaining == 0 {
+            complete_packet(s);
+        }
+    });
+}
+
+fn complete_packet(s: &mut State) {
+    if s.packet[0] != SephTags::ScreenDisplayStatus as u8 {
+        return
+    }
+    s.display_pending = true;
+    if let Some(e) = Element::decode(&s.packet[3..]) {
+        if e.erases() {
+            s.elements.retain(|o| !e.covers(o));
+        }
+        s.elements.push(e);
+    }
+}
+
+fn next_packet(s: &mut State) -> Option<Vec<u8>> {
+    loop {
+        match s.events.pop_front()? {
+            Scripted::Packet(p) => return Some(p),
+            Scripted::Checkpoint(id) => {
+                let texts = s.elements.iter().filter_map(|e| e.text.clone()).collect();
+                s.checkpoints[id] = Some(texts);
+            }
+        }
+    }
+}
+
+pub fn seph_recv(buffer: &mut [u8], _flags: u32) -> usize {
+    let starved = |s: &mut State| {
+        !s.display_pending && !s.events.iter().any(|e| matches!(e, Scripted::Packet(_)))
+    };
+    while with_state(|s| s.interactive && starved(s)) {
+        if !keys::prompt() {
+            panic!("sim: stdin closed");
+        }
+    }
+    with_state(|s| {
+        let packet = if s.display_pending {
+            s.display_pending = false;
+            std::vec![DISPLAY_PROCESSED_EVENT, 0, 0]
+        } else {
+            match next_packet(s) {
+                Some(p) => p,
+                None => panic!("sim: scripted event queue exhausted"),
+            }
+        };
+        if packet[0] == TICKER_EVENT {
+            s.ticks += 1;
+        }
+        s.status_sent = false;
+        let n = packet.len().min(buffer.len());
+        buffer[..n].copy_from_slice(&packet[..n]);
+        n
+    })
+}
+
+pub fn is_status_sent() -> bool {
+    with_state(|s| s.status_sent)
+}
+
+pub fn send_general_status() {
+    seph_send(&[SephTags::GeneralStatus as u8, 0, 0]);
+}
@@ -0,0 +1,187 @@
+//! Widgets driven through the simulator
+
+use super::driver::*;
+use super::*;
+use crate::expect_flow;
+use crate::flow::{Flow, Step};
+use crate::i18n::{tr, Text};
+use crate::ui::*;
+use crate::widget::{Outcome, Widget};
+
+#[test]
+fn validator_timeout() {
+    reset();
+    let validator = Validator::new("Sign?");
+    let mut clock = FakeClock::new();
+    clock.advance(30);
+    assert_eq!(validator.ask_with_timeout(30), None);
+    assert_eq!(ticks_received(), clock.now());
+
+    // Confirmed on the last tick before the timeout
+    clock.advance(29);
+    push_keys("b");
+    assert_eq!(validator.ask_with_timeout(30), Some(true));
+    assert_eq!(ticks_received(), clock.now());
+    assert_eq!(pending_events(), 0);
+}
+
+#[test]
+fn tick_source() {
+    reset();
+    let clock: &mut dyn TickSource = &mut FakeClock::new();
+    clock.advance(3);
+    assert_eq!((clock.now(), pending_events()), (3, 3));
+}
+
+#[test]
+fn screenshot_diff() {
+    reset();
+    approve(&Validator::new("Sign?"));
+    let sign = screenshot();
+    assert_eq!(sign.diff(&sign).map(|d| d.summary()), None);
+    assert_eq!(Screenshot::from_pbm(&sign.to_pbm()), Some(sign.clone()));
+
+    approve(&Validator::new("Sign this?"));
+    let diff = screenshot().diff(&sign).expect("screens should differ");
+    assert!(!diff.regions.is_empty());
+    assert!(diff.summary().contains("region"));
+    assert!(!diff.side_by_side().get(0, 0));
+}
+
+#[test]
+fn validator_screens() {
+    reset();
+    expect_flow!(Validator::new("Sign?").ask(), [
+        see "Sign?",
+        press Right,
+        see "Cancel",
+        press Left,
+        see "Sign?",
+        press Both,
+        returns Outcome::Approved
+    ]);
+}
+
+#[test]
+fn validator_confirm() {
+    reset();
+    let validator = Validator::new("Sign?");
+    assert_eq!(approve(&validator), Outcome::Approved);
+    assert_eq!(reject(&validator), Outcome::Rejected);
+    assert_eq!(pending_events(), 0);
+}
+
+#[test]
+fn menu_select() {
+    reset();
+    let menu = Menu::new(&["First", "Second", "Third"]);
+    assert_eq!(select(&menu, 2), MenuResult::Selected(2));
+    assert_eq!(widget_state().map(|s| (s.index, s.count)), Some((2, 3)));
+}
+
+#[test]
+fn review_drivers() {
+    reset();
+    let review = MessageValidator::new(&["Send", "1 BTC"], &["Confirm"], &["Reject"]);
+    assert_eq!(approve_review(&review), Outcome::Approved);
+    assert_eq!(reject_review(&review), Outcome::Rejected);
+    dismiss(&SingleMessage::new("Done"));
+    assert_eq!(pending_events(), 0);
+}
+
+#[test]
+fn empty_menu() {
+    reset();
+    expect_flow!(Menu::new(&[]).show(), [
+        see tr(Text::NoEntries),
+        press Right,
+        see tr(Text::NoEntries),
+        press Both,
+        returns MenuResult::Back
+    ]);
+}
+
+#[test]
+fn single_entry_menu() {
+    reset();
+    let menu = Menu::new(&["Only"]);
+    expect_flow!(menu.show(), [
+        see "Only",
+        press Right,
+        see "Only",
+        press Left,
+        see "Only",
+        press Both,
+        returns MenuResult::Selected(0)
+    ]);
+    assert_eq!(select(&menu, 0), MenuResult::Selected(0));
+}
+
+#[test]
+fn flow_confirm_step() {
+    reset();
+    let steps = [
+        Step::review_field("Amount", "1 BTC"),
+        Step::confirm("Approve"),
+        Step::reject("Reject"),
+    ];
+    let flow = Flow::new(&steps);
+    assert_eq!(confirm_step(&flow, 1), 1);
+    assert_eq!(confirm_step(&flow, 2), 2);
+    expect_flow!(flow.run(), [
+        see "Amount",
+        press Right,
+        see "Approve",
+        press Both,
+        returns 1
+    ]);
+}
+
+#[test]
+fn message_scroller_paging() {
+    reset();
+    expect_flow!(MessageScroller::new("0123456789abcdefGHIJ").event_loop(), [
+        see "0123456789abcdef",
+        press Right,
+        see "GHIJ",
+        press Left,
+        see "0123456789abcdef",
+        press Right,
+        press Both,
+    ]);
+    let pages = state_history().iter().map(|s| s.index).collect::<Vec<_>>();
+    assert_eq!(pages, [0, 1, 0, 1]);
+
+    read_to_end(&MessageScroller::new("0123456789abcdefGHIJ"));
+    assert_eq!(texts().iter().filter(|t| t.as_str() == "GHIJ").count(), 1);
+    assert_eq!(pending_events(), 0);
+}
+
+#[test]
+fn empty_hscroller() {
+    reset();
+    expect_flow!(HScroller::new(&[]).select(), [
+        see tr(Text::NoEntries),
+        press Right,
+        see tr(Text::NoEntries),
+        press Both,
+        returns MenuResult::Back
+    ]);
+    expect_flow!(HScroller::composite(&[]).pages(&[]).event_loop(), [
+        see tr(Text::NoEntries),
+        press Both,
+    ]);
+}
+
+#[test]
+fn centered_on_nano_x() {
+    reset();
+    set_screen_size(ScreenSize::NANO_X);
+    let label_ys = || {
+        elements().iter().filter(|e| e.text.is_some()).map(|e| e.y).collect::<Vec<_>>()
+    };
+    AmountPage::new("Amount", "1 BTC").draw();
+    assert_eq!(label_ys(), [26, 45]);
+    ScrollingLabel::new("0x1234").title("Address").draw();
+    assert_eq!(label_ys(), [28, 42]);
+}
@@ -0,0 +1,269 @@
+//! End to end tests against an app running in
+//! the Speculos emulator, through its REST API.
+//! Enabled with the `speculos` feature, on the
+//! host side of the app's CI:
+//!
+//! ```ignore
+//! let device = Speculos::new("127.0.0.1:5000");
+//! device.wait_for("Review")?;
+//! device.expect_page(&["Amount", "0.1 BTC"])?;
+//! device.approve_review()?;
+//! ```
+//!
+//! Screens are read back as the text Speculos
+//! recognizes on them. Helpers moving through
+//! reviews rely on the pages the widgets show,
+//! e.g. the "Approve" page of `MessageValidator`.
+
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::string::{String, ToString};
+use std::thread;
+use std::time::{Duration, Instant};
+use std::vec::Vec;
+use std::{fmt, format};
+
+use crate::i18n::{tr, Text};
+
+/// Time `wait_for` waits for a text by default
+pub const SPECULOS_TIMEOUT: Duration = Duration::from_secs(5);
+/// Pages `navigate_to` goes through at most
+pub const SPECULOS_MAX_PAGES: usize = 64;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Button {
+    Left,
+    Right,
+    Both,
+}
+
+impl Button {
+    const fn path(self) -> &'static str {
+        match self {
+            Button::Left => "/button/left",
+            Button::Right => "/button/right",
+            Button::Both => "/button/both",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// Speculos answered with this HTTP status
+    Http(u16),
+    /// `expected` was not shown in time;
+    /// `screen` is what was shown instead
+    NotShown { expected: String, screen: Vec<String> },
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "speculos: {}", e),
+            Error::Http(status) => write!(f, "speculos: HTTP status {}", status),
+            Error::NotShown { expected, screen } => {
+                write!(f, "speculos: expected {:?}, screen shows {:?}", expected, screen)
+            }
+        }
+    }
+}
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// An emulated device, reached at the address
+/// of the Speculos API (`--api-port`)
+pub struct Speculos {
+    addr: String,
+    timeout: Duration,
+}
+
+impl Speculos {
+    pub fn new(addr: &str) -> Self {
+        Speculos { addr: addr.to_string(), timeout: SPECULOS_TIMEOUT }
+    }
+
+    /// Wait up to `timeout` for expected texts
+    pub fn timeout(self, timeout: Duration) -> Self {
+        Speculos { timeout, ..self }
+    }
+
+    /// Press and release `button`
+    pub fn press(&self, button: Button) -> Result<()> {
+        self.request("POST", button.path(), Some("{\"action\":\"press-and-release\"}"))?;
+        Ok(())
+    }
+
+    pub fn press_all(&self, buttons: &[Button]) -> Result<()> {
+        buttons.iter().try_for_each(|b| self.press(*b))
+    }
+
+    /// Texts on the screen, top to bottom
+    pub fn screen(&self) -> Result<Vec<String>> {
+        let body = self.request("GET", "/events?currentscreenonly=true", None)?;
+        Ok(json_strings(&body, "text"))
+    }
+
+    /// The screen as a PNG image
+    pub fn screenshot(&self) -> Result<Vec<u8>> {
+        self.request_bytes("GET", "/screenshot", None)
+    }
+
+    /// Wait until `text` is one of
+    /// the texts on the screen
+    pub fn wait_for(&self, text: &str) -> Result<()> {
+        let start = Instant::now();
+        loop {
+            let screen = self.screen()?;
+            if screen.iter().any(|t| t == text) {
+                return Ok(())
+            }
+            if start.elapsed() >= self.timeout {
+                return Err(Error::NotShown { expected: text.to_string(), screen })
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    /// Wait until the screen shows all `lines`
+    pub fn expect_page(&self, lines: &[&str]) -> Result<()> {
+        lines.iter().try_for_each(|line| self.wait_for(line))
+    }
+
+    /// Press right until `text` is shown
+    pub fn navigate_to(&self, text: &str) -> Result<()> {
+        for _ in 0..SPECULOS_MAX_PAGES {
+            let screen = self.screen()?;
+            if screen.iter().any(|t| t == text) {
+                return Ok(())
+            }
+            let before = screen;
+            self.press(Button::Right)?;
+            self.wait_change(&before)?;
+        }
+        Err(Error::NotShown { expected: text.to_string(), screen: self.screen()? })
+    }
+
+    /// Go to the approve page of a review and
+    /// confirm it
+    pub fn approve_review(&self) -> Result<()> {
+        self.navigate_to(tr(Text::Approve))?;
+        self.press(Button::Both)
+    }
+
+    /// Go to the reject page of a review and
+    /// confirm it
+    pub fn reject_review(&self) -> Result<()> {
+        self.navigate_to(tr(Text::Reject))?;
+        self.press(Button::Both)
+    }
+
+    /// Wait for the screen to differ from
+    /// `before`, or the timeout, as the last
+    /// page does not change
+    fn wait_change(&self, before: &[String]) -> Result<()> {
+        let start = Instant::now();
+        while start.elapsed() < self.timeout {
+            if self.screen()? != before {
+                break
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        Ok(())
+    }
+
+    fn request(&self, method: &str, path: &str, body: Option<&str>) -> Result<String> {
+        let bytes = self.request_bytes(method, path, body)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Send an HTTP/1.1 request, returning
+    /// the body of the response
+    fn request_bytes(&self, method: &str, path: &str, body: Option<&str>) -> Result<Vec<u8>> {
+        let mut stream = TcpStream::connect(&self.addr)?;
+        let body = body.unwrap_or("");
+        let head = format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n",
+            method, path, self.addr, body.len()
+        );
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(body.as_bytes())?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response)?;
+        let split = response.windows(4).position(|w| w == b"\r\n\r\n")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed HTTP response"))?;
+        let status = String::from_utf8_lossy(&response[..split]).split(' ').nth(1)
+            .and_then(|s| s.parse::<u16>().ok()).unwrap_or(0);
+        if !(200..300).contains(&status) {
+            return Err(Error::Http(status))
+        }
+        let head = String::from_utf8_lossy(&response[..split]).to_ascii_lowercase();
+        let body = response.split_off(split + 4);
+        if head.contains("transfer-encoding: chunked") {
+            return Ok(dechunk(&body))
+        }
+        Ok(body)
+    }
+}
+
+/// Body of a chunked HTTP response
+fn dechunk(mut data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    while let Some(eol) = data.windows(2).position(|w| w == b"\r\n") {
+        let size = std::str::from_utf8(&data[..eol]).ok()
+            .and_then(|s| usize::from_str_radix(s.split(';').next()?.trim(), 16).ok())
+            .unwrap_or(0);
+        let start = eol + 2;
+        if size == 0 || start + size > data.len() {
+            break
+        }
+        out.extend_from_slice(&data[start..start + size]);
+        data = &data[(start + size + 2).min(data.len())..];
+    }
+    out
+}
+
+/// String values of `key` in `json`, in order.
+/// Enough for the flat events Speculos returns.
+fn json_strings(json: &str, key: &str) -> Vec<String> {
+    let pattern = format!("\"{}\"", key);
+    let mut out = Vec::new();
+    let mut rest = json;
+    while let Some(i) = rest.find(&pattern) {
+        rest = rest[i + pattern.len()..].trim_start();
+        let value = match rest.strip_prefix(':').map(str::trim_start) {
+            Some(v) if v.starts_with('"') => &v[1..],
+            _ => continue,
+        };
+        let mut s = String::new();
+        let mut chars = value.char_indices();
+        while let Some((_, c)) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => s.push('\n'),
+                    Some((_, 't')) => s.push('\t'),
+                    Some((_, 'u')) => {
+                        let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                        if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                            s.push(c);
+                        }
+                    }
+                    Some((_, c)) => s.push(c),
+                    None => break,
+                },
+                c => s.push(c),
+            }
+        }
+        out.push(s);
+        rest = value;
+    }
+    out
+}
@@ -0,0 +1,117 @@
+//! Device status reported by SEPH on the
+//! Nano X: battery charge and BLE connection.
+//! `get_event` keeps the latest values, and
+//! optionally `widget::run` draws them in a
+//! status bar over the running widget:
+//!
+//! ```ignore
+//! status::set_overlay(true);
+//! if status::battery().map_or(false, |level| level < 10) { .. }
+//! ```
+//!
+//! The bar takes the top 8 pixel rows, so it
+//! is meant for the 64 pixel high screens.
+
+use crate::bagls::*;
+use crate::widget::Event;
+
+/// Status last reported by the device
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Status {
+    /// Battery charge in percent,
+    /// unknown until reported
+    pub battery: Option<u8>,
+    pub ble_connected: bool,
+}
+
+static mut STATUS: Status = Status { battery: None, ble_connected: false };
+static mut OVERLAY: bool = false;
+/// Painted over every widget, after the bar
+static mut CHROME: Option<fn()> = None;
+
+pub fn get() -> Status {
+    unsafe { STATUS }
+}
+
+pub fn battery() -> Option<u8> {
+    get().battery
+}
+
+pub fn ble_connected() -> bool {
+    get().ble_connected
+}
+
+/// Keep the status carried by `event`
+pub(crate) fn record(event: Event) {
+    match event {
+        Event::BatteryLevel(level) => unsafe { STATUS.battery = Some(level) },
+        Event::BleConnection(connected) => unsafe { STATUS.ble_connected = connected },
+        _ => (),
+    }
+}
+
+/// Draw the status bar over every widget
+/// run by `widget::run`
+pub fn set_overlay(enabled: bool) {
+    unsafe { OVERLAY = enabled };
+}
+
+pub fn overlay() -> bool {
+    unsafe { OVERLAY }
+}
+
+/// Paint `chrome` over every widget run by
+/// `widget::run`, e.g. a status line of the
+/// app, see `navigator`
+pub fn set_chrome(chrome: Option<fn()>) {
+    unsafe { CHROME = chrome };
+}
+
+pub fn chrome() -> Option<fn()> {
+    unsafe { CHROME }
+}
+
+pub(crate) fn paint_overlay() {
+    if overlay() {
+        StatusBar::new(get()).paint();
+    }
+    if let Some(chrome) = chrome() {
+        chrome();
+    }
+}
+
+/// Position and size of the battery gauge
+const BATTERY_X: i16 = 108;
+const BATTERY_WIDTH: u16 = 16;
+
+/// "BT" when connected, and a battery
+/// gauge, at the top right of the screen
+#[derive(Copy, Clone)]
+pub struct StatusBar {
+    pub status: Status,
+}
+
+impl StatusBar {
+    pub const fn new(status: Status) -> Self {
+        StatusBar { status }
+    }
+}
+
+impl Displayable for StatusBar {
+    fn paint(&self) {
+        Rect::new().pos(0, 0).dims(128, 8).colors(0, 0).fill(true).paint();
+        if self.status.ble_connected {
+            LabelLine::new().text("BT").font(Font::LucidaConsole8px)
+                .pos(BATTERY_X - 20, 7).dims(16, 8).paint();
+        }
+        if let Some(level) = self.status.battery {
+            // Outline, terminal, then charge
+            Rect::new().pos(BATTERY_X, 1).dims(BATTERY_WIDTH, 6).paint();
+            Rect::new().pos(BATTERY_X + BATTERY_WIDTH as i16, 3).dims(1, 2).fill(true).paint();
+            let width = (level.min(100) as u16 * (BATTERY_WIDTH - 2)) / 100;
+            if width > 0 {
+                Rect::new().pos(BATTERY_X + 1, 2).dims(width, 4).fill(true).paint();
+            }
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! App-wide look of the widgets, set once
+//! instead of at each call site:
+//!
+//! ```ignore
+//! style::set(Style { bold: false, ..Style::DEFAULT });
+//! ```
+//!
+//! Labels drawn with the default regular or bold
+//! font use the style's fonts instead; labels
+//! given another font keep it.
+
+use crate::bagls::{Font, Icons};
+
+/// Glyphs drawn by widgets for navigation arrows
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Arrows {
+    pub left: u8,
+    pub right: u8,
+    pub up: u8,
+    pub down: u8,
+}
+
+#[derive(Copy, Clone)]
+pub struct Style {
+    /// Font of regular text
+    pub font: Font,
+    /// Font of emphasized text
+    pub bold_font: Font,
+    /// Whether to emphasize titles and selections;
+    /// without it they use `font`
+    pub bold: bool,
+    pub arrows: Arrows,
+    /// Vertical distance between the two
+    /// lines of pages and flow steps
+    pub line_spacing: i16,
+}
+
+impl Style {
+    pub const DEFAULT: Style = Style {
+        font: Font::OpenSansRegular11px,
+        bold_font: Font::OpenSansExtrabold11px,
+        bold: true,
+        arrows: Arrows {
+            left: Icons::Left as u8,
+            right: Icons::Right as u8,
+            up: Icons::Up as u8,
+            down: Icons::Down as u8,
+        },
+        line_spacing: 13,
+    };
+
+    /// y of the top line of a two-line page,
+    /// the bottom one being at `BOTTOM_LINE_Y`
+    pub const fn top_line_y(&self) -> i16 {
+        BOTTOM_LINE_Y - self.line_spacing
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style::DEFAULT
+    }
+}
+
+/// y of the bottom line of two-line pages
+pub const BOTTOM_LINE_Y: i16 = 26;
+
+static mut STYLE: Style = Style::DEFAULT;
+
+pub fn set(style: Style) {
+    unsafe { STYLE = style }
+}
+
+pub fn get() -> Style {
+    unsafe { STYLE }
+}
+
+/// Font actually used for `font`
+pub(crate) fn font(font: Font) -> Font {
+    let style = get();
+    match font as u8 {
+        f if f == Font::OpenSansRegular11px as u8 => style.font,
+        f if f == Font::OpenSansExtrabold11px as u8 && style.bold => style.bold_font,
+        f if f == Font::OpenSansExtrabold11px as u8 => style.font,
+        _ => font,
+    }
+}
+
+/// Glyph actually drawn for `glyph_id`
+pub(crate) fn glyph(glyph_id: u8) -> u8 {
+    let arrows = get().arrows;
+    match glyph_id {
+        g if g == Icons::Left as u8 => arrows.left,
+        g if g == Icons::Right as u8 => arrows.right,
+        g if g == Icons::Up as u8 => arrows.up,
+        g if g == Icons::Down as u8 => arrows.down,
+        g => g,
+    }
+}
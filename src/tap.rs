@@ -0,0 +1,123 @@
+//! Recording and replay of the SEPH events
+//! widgets receive, enabled with the
+//! `event-tap` feature, to reproduce on the
+//! simulator or Speculos a flow misbehaving
+//! on hardware.
+//!
+//! Once recording, each packet `get_event`
+//! receives is printed on the debug channel as
+//! a `seph:` line of hex bytes:
+//!
+//! ```ignore
+//! tap::record_start();
+//! // seph:0e0000
+//! // seph:0500010205
+//! ```
+//!
+//! Those packets, concatenated, are fed back
+//! to widgets in place of SEPH:
+//!
+//! ```ignore
+//! static RECORDING: &[u8] = include_bytes!("skipped_page.seph");
+//! tap::replay(RECORDING);
+//! review.show();
+//! ```
+//!
+//! Replay ends with the recording, after
+//! which events come from SEPH again.
+
+/// Prefix of the recorded lines
+pub const TAP_PREFIX: &str = "seph:";
+
+/// Longest packet recorded, in bytes
+const TAP_MAX_PACKET: usize = 64;
+
+struct Tap {
+    recording: bool,
+    /// Packets left to replay
+    replay: &'static [u8],
+}
+
+static mut TAP: Tap = Tap { recording: false, replay: &[] };
+
+fn tap() -> &'static mut Tap {
+    unsafe { &mut *core::ptr::addr_of_mut!(TAP) }
+}
+
+/// Print the packets received from now on
+pub fn record_start() {
+    tap().recording = true;
+}
+
+pub fn record_stop() {
+    tap().recording = false;
+}
+
+/// Feed `packets` to widgets instead of
+/// SEPH events, until they run out. Each
+/// packet is a tag, a big endian u16 length
+/// and the payload, as received from SEPH.
+pub fn replay(packets: &'static [u8]) {
+    tap().replay = packets;
+}
+
+/// Whether recorded packets are left
+pub fn replaying() -> bool {
+    !tap().replay.is_empty()
+}
+
+/// Length of the packet starting `data`,
+/// header included
+fn packet_len(data: &[u8]) -> usize {
+    match data {
+        [_, hi, lo, ..] => 3 + u16::from_be_bytes([*hi, *lo]) as usize,
+        _ => data.len(),
+    }
+}
+
+/// Print `packet` if recording
+pub(crate) fn record(packet: &[u8]) {
+    if !tap().recording {
+        return
+    }
+    let packet = &packet[..packet_len(packet).min(packet.len()).min(TAP_MAX_PACKET)];
+    let mut line = [0u8; TAP_PREFIX.len() + 2 * TAP_MAX_PACKET + 1];
+    line[..TAP_PREFIX.len()].copy_from_slice(TAP_PREFIX.as_bytes());
+    let mut len = TAP_PREFIX.len();
+    for b in packet {
+        line[len] = HEX[(b >> 4) as usize];
+        line[len + 1] = HEX[(b & 0xf) as usize];
+        len += 2;
+    }
+    line[len] = b'\n';
+    // Only ASCII was written
+    print(core::str::from_utf8(&line[..len + 1]).unwrap_or(""));
+}
+
+const HEX: &[u8; 16] = b"0123456789abcdef";
+
+/// Copy the next recorded packet into
+/// `buffer`, false once replay is over
+pub(crate) fn replay_next(buffer: &mut [u8]) -> bool {
+    let t = tap();
+    if t.replay.is_empty() {
+        return false
+    }
+    let len = packet_len(t.replay).min(t.replay.len());
+    let (packet, rest) = t.replay.split_at(len);
+    t.replay = rest;
+    let n = len.min(buffer.len());
+    buffer.fill(0);
+    buffer[..n].copy_from_slice(&packet[..n]);
+    true
+}
+
+#[cfg(not(feature = "sim"))]
+fn print(s: &str) {
+    nanos_sdk::testing::debug_print(s);
+}
+
+#[cfg(feature = "sim")]
+fn print(s: &str) {
+    std::eprint!("{}", s);
+}
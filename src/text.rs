@@ -0,0 +1,565 @@
+//! Helpers to build label text without
+//! an allocator
+
+use core::fmt;
+
+use crate::bagls::*;
+use crate::fmt::{page_counter, U64_DIGITS};
+
+/// A `core::fmt::Write` sink that formats into
+/// a caller-provided buffer and draws the result
+/// as a label:
+///
+/// ```ignore
+/// let mut buf = [0u8; 20];
+/// let mut line = UiWriter::new(&mut buf);
+/// write!(line, "Nonce: {}", nonce).ok();
+/// line.display();
+/// ```
+///
+/// Text that does not fit is cut on a character
+/// boundary, and `truncated` reports it.
+pub struct UiWriter<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+    truncated: bool,
+    label: LabelLine<'static>,
+}
+
+impl<'a> UiWriter<'a> {
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        UiWriter { buf, len: 0, truncated: false, label: LabelLine::new() }
+    }
+
+    /// Position, dimensions and font used to
+    /// draw the text. Any text set on `label`
+    /// is ignored.
+    pub fn label(self, label: LabelLine<'static>) -> Self {
+        UiWriter { label, ..self }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only whole UTF-8 sequences are ever copied
+        unsafe { core::str::from_utf8_unchecked(&self.buf[..self.len]) }
+    }
+
+    /// Whether some text was dropped
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.truncated = false;
+    }
+}
+
+impl fmt::Write for UiWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let n = floor_char_boundary(s, self.buf.len() - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        if n < s.len() {
+            self.truncated = true;
+        }
+        Ok(())
+    }
+}
+
+impl Displayable for UiWriter<'_> {
+    fn paint(&self) {
+        self.label.text(self.as_str()).paint();
+    }
+}
+
+/// Appended in place of the text dropped
+/// by `UiString`
+pub const TRUNCATION_MARKER: &str = "...";
+
+/// Fixed-capacity string for dynamic labels.
+/// Dereferences to `&str`, so it can be passed
+/// wherever text is expected:
+///
+/// ```ignore
+/// let mut s = UiString::<16>::new();
+/// write!(s, "Account {}", idx).ok();
+/// SingleMessage::new(&s).show();
+/// ```
+///
+/// When text does not fit, it is cut on a
+/// character boundary and ends with
+/// `TRUNCATION_MARKER`.
+#[derive(Copy, Clone)]
+pub struct UiString<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+    truncated: bool,
+}
+
+impl<const N: usize> UiString<N> {
+    pub const fn new() -> Self {
+        UiString { buf: [0; N], len: 0, truncated: false }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only whole UTF-8 sequences are ever copied
+        unsafe { core::str::from_utf8_unchecked(&self.buf[..self.len]) }
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Whether some text was dropped
+    pub const fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.truncated = false;
+    }
+
+    /// Append `s`. Returns `false` if it
+    /// had to be truncated.
+    pub fn push_str(&mut self, s: &str) -> bool {
+        if self.truncated {
+            return false
+        }
+        if self.len + s.len() <= N {
+            self.buf[self.len..self.len + s.len()].copy_from_slice(s.as_bytes());
+            self.len += s.len();
+            return true
+        }
+        // Make room for the marker, dropping already
+        // written text too if the buffer is that small
+        let marker = if TRUNCATION_MARKER.len() <= N { TRUNCATION_MARKER } else { "" };
+        let limit = N - marker.len();
+        if self.len > limit {
+            self.len = floor_char_boundary(self.as_str(), limit);
+        }
+        let n = floor_char_boundary(s, limit - self.len);
+        self.buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        self.buf[self.len..self.len + marker.len()].copy_from_slice(marker.as_bytes());
+        self.len += marker.len();
+        self.truncated = true;
+        false
+    }
+
+    /// Append the formatted `args`, e.g.
+    /// `format_args!("{} {}", amount, ticker)`.
+    /// Returns `false` if it had to be truncated.
+    pub fn push_fmt(&mut self, args: fmt::Arguments) -> bool {
+        // Writing never fails, it truncates
+        let _ = fmt::Write::write_fmt(self, args);
+        !self.truncated
+    }
+
+    /// Append "k/n" for page `page` (from 0)
+    /// of `count`, unless there is a single one,
+    /// e.g. for a page indicator
+    pub fn push_counter(&mut self, page: usize, count: usize) -> bool {
+        let mut buf = [0u8; COUNTER_LEN];
+        match count {
+            0 | 1 => !self.truncated,
+            _ => match page_counter(page + 1, count, &mut buf) {
+                Some(counter) => self.push_str(counter),
+                None => false,
+            },
+        }
+    }
+
+    /// `push_counter` as " (k/n)", e.g.
+    /// after a long field's title
+    pub fn push_title_counter(&mut self, page: usize, count: usize) -> bool {
+        match count {
+            0 | 1 => !self.truncated,
+            _ => self.push_str(" (") && self.push_counter(page, count) && self.push_str(")"),
+        }
+    }
+
+    /// Remove the last character. After a
+    /// truncation, the whole `TRUNCATION_MARKER`
+    /// goes at once and its last character
+    /// is returned.
+    pub fn pop(&mut self) -> Option<char> {
+        if self.truncated && TRUNCATION_MARKER.len() <= N {
+            self.len -= TRUNCATION_MARKER.len();
+            self.truncated = false;
+            return TRUNCATION_MARKER.chars().next_back()
+        }
+        let c = self.as_str().chars().next_back()?;
+        self.len -= c.len_utf8();
+        self.truncated = false;
+        Some(c)
+    }
+}
+
+/// Text composed for a page, e.g. a "title
+/// (k/n)" header or a value line:
+///
+/// ```ignore
+/// let mut header = PageText::<24>::from("Output");
+/// header.push_title_counter(i, n);
+/// write!(value, "{} {}", amount, ticker).ok();
+/// ```
+///
+/// Text that does not fit ends with
+/// `TRUNCATION_MARKER`.
+pub type PageText<const N: usize> = UiString<N>;
+
+/// Longest "k/n" page counter
+const COUNTER_LEN: usize = 2 * U64_DIGITS + 1;
+
+/// Largest index not above `n` that
+/// starts a character of `s`
+fn floor_char_boundary(s: &str, n: usize) -> usize {
+    let mut n = n.min(s.len());
+    while !s.is_char_boundary(n) {
+        n -= 1;
+    }
+    n
+}
+
+impl<const N: usize> Default for UiString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Formats `args`, truncating if needed
+impl<const N: usize> From<fmt::Arguments<'_>> for UiString<N> {
+    fn from(args: fmt::Arguments) -> Self {
+        let mut u = Self::new();
+        u.push_fmt(args);
+        u
+    }
+}
+
+/// Truncates `s` if needed
+impl<const N: usize> From<&str> for UiString<N> {
+    fn from(s: &str) -> Self {
+        let mut u = Self::new();
+        u.push_str(s);
+        u
+    }
+}
+
+impl<const N: usize> core::ops::Deref for UiString<N> {
+    type Target = str;
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for UiString<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> fmt::Write for UiString<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Display for UiString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<const N: usize> fmt::Debug for UiString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> PartialEq<str> for UiString<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for UiString<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+/// Conversions between `heapless` containers
+/// and the types taken by widgets. A
+/// `heapless::String` already dereferences
+/// to `&str`; these cover the remaining cases.
+#[cfg(feature = "heapless")]
+mod heapless_impls {
+    use super::*;
+
+    impl<const N: usize, const M: usize> From<&heapless::String<M>> for UiString<N> {
+        fn from(s: &heapless::String<M>) -> Self {
+            UiString::from(s.as_str())
+        }
+    }
+
+    impl<const N: usize> From<UiString<N>> for heapless::String<N> {
+        fn from(s: UiString<N>) -> Self {
+            let mut h = heapless::String::new();
+            // Cannot fail, both have capacity N
+            let _ = h.push_str(s.as_str());
+            h
+        }
+    }
+}
+
+/// Borrow each string of `items`, e.g. to build
+/// a `Menu` from dynamically generated entries:
+///
+/// ```ignore
+/// let refs = str_refs(&entries);
+/// Menu::new(&refs).show();
+/// ```
+#[cfg(feature = "heapless")]
+pub fn str_refs<const N: usize, const M: usize>(
+    items: &heapless::Vec<heapless::String<N>, M>,
+) -> heapless::Vec<&str, M> {
+    items.iter().map(|s| s.as_str()).collect()
+}
+
+/// Longest line of a `PageBuffer`, in bytes
+pub const PAGE_LINE_LEN: usize = 64;
+/// Most lines of a `PageBuffer`
+pub const PAGE_BUFFER_LINES: usize = 2;
+
+/// Lines of one page, formatted on demand by
+/// a `PageProvider` when the page is shown:
+///
+/// ```ignore
+/// let outputs = |i: usize, page: &mut PageBuffer| {
+///     let _ = write!(page.line(), "Output {}", i + 1);
+///     let _ = write!(page.line(), "{}", Amount(tx.outputs[i].value));
+/// };
+/// MessageValidator::new(&[], &["Approve"], &["Reject"]).provider(tx.outputs.len(), &outputs).ask();
+/// ```
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PageBuffer {
+    lines: [UiString<PAGE_LINE_LEN>; PAGE_BUFFER_LINES],
+    len: usize,
+}
+
+impl PageBuffer {
+    pub const fn new() -> Self {
+        PageBuffer { lines: [UiString::new(); PAGE_BUFFER_LINES], len: 0 }
+    }
+
+    /// Start a new line to `write!` into. Past
+    /// `PAGE_BUFFER_LINES`, the last line is
+    /// cleared and returned again.
+    pub fn line(&mut self) -> &mut UiString<PAGE_LINE_LEN> {
+        self.len = (self.len + 1).min(PAGE_BUFFER_LINES);
+        let line = &mut self.lines[self.len - 1];
+        line.clear();
+        line
+    }
+
+    /// Append `text` as a line. Returns
+    /// false if the page is full.
+    pub fn push(&mut self, text: &str) -> bool {
+        if self.len == PAGE_BUFFER_LINES {
+            return false
+        }
+        self.line().push_str(text);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The lines written, as taken by `Page`
+    pub fn lines(&self) -> [&str; PAGE_BUFFER_LINES] {
+        let mut lines = [""; PAGE_BUFFER_LINES];
+        for (line, s) in lines.iter_mut().zip(&self.lines[..self.len]) {
+            *line = s.as_str();
+        }
+        lines
+    }
+}
+
+/// Fills the `PageBuffer` of page `index`.
+/// `Sync`, so that flows using one can
+/// still be declared `static`.
+pub type PageProvider<'a> = &'a (dyn Fn(usize, &mut PageBuffer) + Sync);
+
+/// A label showing a number that rolls towards
+/// its new value over a few ticks instead of
+/// jumping, e.g. "Inputs signed: 12/40":
+///
+/// ```ignore
+/// let mut counter = Counter::new("Inputs signed: ").total(40);
+/// counter.display();
+/// // after each input
+/// counter.set(signed);
+/// // on each ticker event
+/// counter.tick();
+/// ```
+pub struct Counter<'a> {
+    prefix: &'a str,
+    total: Option<u64>,
+    shown: u64,
+    target: u64,
+    label: LabelLine<'static>,
+}
+
+/// Fraction of the remaining distance
+/// covered at each tick, as a divisor
+const COUNTER_EASING: u64 = 3;
+
+impl<'a> Counter<'a> {
+    pub const fn new(prefix: &'a str) -> Self {
+        Counter { prefix, total: None, shown: 0, target: 0, label: LabelLine::new() }
+    }
+
+    /// Show the value as "value/total"
+    pub const fn total(self, total: u64) -> Self {
+        Counter { total: Some(total), ..self }
+    }
+
+    /// Position, dimensions and font used to
+    /// draw the counter
+    pub const fn label(self, label: LabelLine<'static>) -> Self {
+        Counter { label, ..self }
+    }
+
+    /// Set the value to roll towards
+    pub fn set(&mut self, value: u64) {
+        self.target = value;
+    }
+
+    /// Show `value` right away
+    pub fn jump(&mut self, value: u64) {
+        self.target = value;
+        self.shown = value;
+        self.repaint();
+    }
+
+    pub const fn value(&self) -> u64 {
+        self.target
+    }
+
+    pub const fn animating(&self) -> bool {
+        self.shown != self.target
+    }
+
+    /// Advance the animation by one ticker event
+    /// and repaint if the shown value changed.
+    /// Returns whether the animation goes on.
+    pub fn tick(&mut self) -> bool {
+        if !self.animating() {
+            return false
+        }
+        let step = |diff: u64| (diff / COUNTER_EASING).max(1);
+        if self.shown < self.target {
+            self.shown += step(self.target - self.shown);
+        } else {
+            self.shown -= step(self.shown - self.target);
+        }
+        self.repaint();
+        self.animating()
+    }
+
+    /// Erase the previous value and draw
+    /// the current one
+    fn repaint(&self) {
+        let (x, y) = self.label.pos;
+        let (w, h) = self.label.dims;
+        Rect::new().pos(x, y - h as i16).dims(w, h + 2).colors(0, 0xffffff).fill(true).paint();
+        self.paint();
+    }
+}
+
+impl Displayable for Counter<'_> {
+    fn paint(&self) {
+        let mut text = UiString::<40>::from(self.prefix);
+        let mut digits = [0u8; crate::fmt::U64_DIGITS];
+        if let Some(n) = crate::fmt::u64_to_str(self.shown, &mut digits) {
+            text.push_str(n);
+        }
+        if let Some(total) = self.total {
+            text.push_str("/");
+            if let Some(n) = crate::fmt::u64_to_str(total, &mut digits) {
+                text.push_str(n);
+            }
+        }
+        self.label.text(&text).paint();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    #[test]
+    fn counters() {
+        let mut s = PageText::<16>::from("Amount");
+        assert!(s.push_title_counter(1, 3));
+        assert_eq!(s, "Amount (2/3)");
+        assert!(s.push_title_counter(0, 1));
+        assert_eq!(s, "Amount (2/3)");
+
+        let mut s = PageText::<8>::new();
+        assert!(s.push_counter(9, 10));
+        assert_eq!(s, "10/10");
+        assert!(!s.push_counter(9, 10));
+        assert_eq!(s, "10/10...");
+    }
+
+    #[test]
+    fn write_truncates_with_marker() {
+        let mut s = PageText::<10>::new();
+        let _ = write!(s, "Account {}", 12345);
+        assert_eq!(s, "Account...");
+        assert!(s.truncated());
+
+        let mut s = PageText::<8>::from("é");
+        assert!(!s.push_str("éééé"));
+        assert_eq!(s, "éé...");
+    }
+
+    #[test]
+    fn pop_removes_whole_marker() {
+        let mut s = PageText::<8>::from("Truncated");
+        assert_eq!(s, "Trunc...");
+        assert_eq!(s.pop(), Some('.'));
+        assert_eq!(s, "Trunc");
+        assert!(!s.truncated());
+        assert_eq!(s.pop(), Some('c'));
+        assert!(s.push_str("at"));
+        assert_eq!(s, "Trunat");
+
+        // Too small for the marker
+        let mut s = PageText::<2>::from("abc");
+        assert_eq!(s, "ab");
+        assert_eq!(s.pop(), Some('b'));
+        assert_eq!(s, "a");
+    }
+}
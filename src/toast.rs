@@ -0,0 +1,74 @@
+//! Short notices that go away by themselves,
+//! e.g. once a transaction is signed:
+//!
+//! ```ignore
+//! comm.reply(signature);
+//! Toast::show("Transaction signed", TOAST_TICKS);
+//! ```
+//!
+//! The notice is drawn over the current screen,
+//! which is drawn again when it goes away. A
+//! button press dismisses it early.
+
+use crate::bagls::*;
+use crate::context::UiContext;
+use crate::screen;
+use crate::truncate::{fit, LINE_BUF_LEN};
+use crate::ui::{Actions, WidgetState};
+use crate::widget::{run, Event, Widget};
+
+/// Ticker events a toast stays by
+/// default, 1.5 seconds
+pub const TOAST_TICKS: u32 = 15;
+
+#[derive(Copy, Clone)]
+pub struct Toast<'a> {
+    message: &'a str,
+    /// Ticker events left before it goes away
+    ticks: u32,
+}
+
+impl<'a> Toast<'a> {
+    pub const fn new(message: &'a str, ticks: u32) -> Self {
+        Toast { message, ticks }
+    }
+
+    /// Show `message` for `ticks` ticker events,
+    /// then restore the screen of the widget
+    /// running, or clear it if there is none
+    pub fn show(message: &'a str, ticks: u32) {
+        run(&mut Toast::new(message, ticks));
+        match UiContext::current() {
+            Some(widget) => widget.redraw(),
+            None => screen::clear(),
+        }
+    }
+}
+
+impl Widget for Toast<'_> {
+    type Output = ();
+
+    fn draw(&self) {
+        let screen = screen::current();
+        let mut buf = [0u8; LINE_BUF_LEN];
+        // Black box with a white frame
+        Rect::new().pos(6, screen.y(7)).dims(116, 19).colors(0, 0xffffff).fill(true).paint();
+        Rect::new().pos(6, screen.y(7)).dims(116, 19).paint();
+        LabelLine::new().dims(112, 11).pos(8, screen.y(20))
+            .text(fit(self.message, None, &mut buf)).paint();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<()> {
+        if let Event::Ticker = event {
+            self.ticks = self.ticks.saturating_sub(1);
+            if self.ticks == 0 {
+                return Some(())
+            }
+        }
+        event.action().map(|_| ())
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("Toast", 0, 1).actions(Actions::ALL)
+    }
+}
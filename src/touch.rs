@@ -0,0 +1,86 @@
+//! Touch events of the touch screen devices
+//! (Stax, Flex), decoded from SEPH finger
+//! events when built with the `nbgl` feature.
+//!
+//! A finger going down gives `Press`; lifting
+//! it gives `Release` at the same place, or
+//! `Swipe` once it moved far enough:
+//!
+//! ```ignore
+//! match event {
+//!     Event::Touch(TouchEvent::Swipe(SwipeDirection::Left)) => next_page(),
+//!     Event::Touch(TouchEvent::Release { x, y }) => tap(x, y),
+//!     _ => (),
+//! }
+//! ```
+
+/// SEPH tag of finger events
+pub const FINGER_EVENT: u8 = 0x0C;
+#[cfg(feature = "nbgl")]
+const FINGER_PRESSED: u8 = 1;
+#[cfg(feature = "nbgl")]
+const FINGER_RELEASED: u8 = 2;
+
+/// Distance in pixels the finger must move
+/// between press and release to swipe
+pub const SWIPE_MIN_DISTANCE: u16 = 40;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TouchEvent {
+    Press { x: u16, y: u16 },
+    /// The finger was lifted where it went
+    /// down, or close to it: a tap
+    Release { x: u16, y: u16 },
+    Swipe(SwipeDirection),
+}
+
+/// Direction of a move from `from` to `to`,
+/// if long enough to be a swipe
+#[cfg(feature = "nbgl")]
+fn swipe(from: (u16, u16), to: (u16, u16)) -> Option<SwipeDirection> {
+    let dx = to.0 as i32 - from.0 as i32;
+    let dy = to.1 as i32 - from.1 as i32;
+    let min = SWIPE_MIN_DISTANCE as i32;
+    match (dx, dy) {
+        (dx, dy) if dx.abs() >= dy.abs() && dx <= -min => Some(SwipeDirection::Left),
+        (dx, dy) if dx.abs() >= dy.abs() && dx >= min => Some(SwipeDirection::Right),
+        (dx, dy) if dy.abs() > dx.abs() && dy <= -min => Some(SwipeDirection::Up),
+        (dx, dy) if dy.abs() > dx.abs() && dy >= min => Some(SwipeDirection::Down),
+        _ => None,
+    }
+}
+
+/// Decode the finger event `packet`: tag,
+/// length, state, then big endian x and y.
+/// `start` keeps where the finger went down.
+#[cfg(feature = "nbgl")]
+pub(crate) fn decode(start: &mut Option<(u16, u16)>, packet: &[u8]) -> Option<TouchEvent> {
+    let (state, x, y) = match packet {
+        [FINGER_EVENT, _, _, state, x0, x1, y0, y1, ..] => {
+            (*state, u16::from_be_bytes([*x0, *x1]), u16::from_be_bytes([*y0, *y1]))
+        }
+        _ => return None,
+    };
+    match state {
+        FINGER_PRESSED => {
+            *start = Some((x, y));
+            Some(TouchEvent::Press { x, y })
+        }
+        FINGER_RELEASED => {
+            let from = start.take().unwrap_or((x, y));
+            Some(match swipe(from, (x, y)) {
+                Some(direction) => TouchEvent::Swipe(direction),
+                None => TouchEvent::Release { x, y },
+            })
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,148 @@
+//! Rules for shortening text too long for a
+//! line, applied by every widget so an app
+//! shows long values in one consistent way.
+//!
+//! Widgets use the policy set with `set_default`
+//! (`End` unless changed), or the one given to
+//! their `truncation` builder method.
+
+use crate::fmt::Cursor;
+use crate::text::TRUNCATION_MARKER;
+
+/// Characters that fit on a line in the
+/// default font, conservatively
+pub const LINE_MAX_CHARS: usize = 18;
+/// Buffer length always enough to
+/// hold a truncated line
+pub const LINE_BUF_LEN: usize = LINE_MAX_CHARS * 4;
+
+/// Implementors are `Sync` so that widgets
+/// referring to them can be `static` items
+pub trait TruncationPolicy: Sync {
+    /// Shorten `text` to at most `max_chars`
+    /// characters, using `buf` if it must be
+    /// rewritten. `buf` holds at least
+    /// `max_chars * 4` bytes.
+    fn truncate<'t>(&self, text: &'t str, max_chars: usize, buf: &'t mut [u8]) -> &'t str;
+}
+
+/// Leave text whole: the screen clips it
+pub struct Clip;
+
+/// Keep the start of the text:
+/// "Lorem ipsum dol..."
+pub struct End;
+
+/// Keep both ends of the text, e.g. for
+/// addresses and hashes: "0x1234...cdef"
+pub struct Middle;
+
+impl TruncationPolicy for Clip {
+    fn truncate<'t>(&self, text: &'t str, _max_chars: usize, _buf: &'t mut [u8]) -> &'t str {
+        text
+    }
+}
+
+/// Marker shortened to fit `max_chars`, so
+/// the result never exceeds it
+fn marker(max_chars: usize) -> &'static str {
+    &TRUNCATION_MARKER[..TRUNCATION_MARKER.len().min(max_chars)]
+}
+
+/// Byte offset of character `n` of `text`
+fn char_offset(text: &str, n: usize) -> usize {
+    text.char_indices().nth(n).map_or(text.len(), |(i, _)| i)
+}
+
+impl TruncationPolicy for End {
+    fn truncate<'t>(&self, text: &'t str, max_chars: usize, buf: &'t mut [u8]) -> &'t str {
+        if text.chars().count() <= max_chars {
+            return text
+        }
+        let marker = marker(max_chars);
+        let kept = max_chars - marker.len();
+        let mut out = Cursor::new(buf);
+        let written = out.push(&text.as_bytes()[..char_offset(text, kept)])
+            .and_then(|_| out.push(marker.as_bytes()));
+        match written {
+            Some(()) => out.into_str(),
+            None => text,
+        }
+    }
+}
+
+impl TruncationPolicy for Middle {
+    fn truncate<'t>(&self, text: &'t str, max_chars: usize, buf: &'t mut [u8]) -> &'t str {
+        let count = text.chars().count();
+        if count <= max_chars {
+            return text
+        }
+        let marker = marker(max_chars);
+        let kept = max_chars - marker.len();
+        let head = kept - kept / 2;
+        let tail = kept / 2;
+        let mut out = Cursor::new(buf);
+        let written = out.push(&text.as_bytes()[..char_offset(text, head)])
+            .and_then(|_| out.push(marker.as_bytes()))
+            .and_then(|_| out.push(&text.as_bytes()[char_offset(text, count - tail)..]));
+        match written {
+            Some(()) => out.into_str(),
+            None => text,
+        }
+    }
+}
+
+static mut DEFAULT: &dyn TruncationPolicy = &End;
+
+/// Set the policy used by widgets
+/// not given one explicitly
+pub fn set_default(policy: &'static dyn TruncationPolicy) {
+    unsafe { DEFAULT = policy }
+}
+
+pub fn default_policy() -> &'static dyn TruncationPolicy {
+    unsafe { DEFAULT }
+}
+
+/// Shorten `text` to a line with `policy`,
+/// or the default one
+pub(crate) fn fit<'t>(text: &'t str, policy: Option<&dyn TruncationPolicy>,
+    buf: &'t mut [u8]) -> &'t str {
+    policy.unwrap_or(default_policy()).truncate(text, LINE_MAX_CHARS, buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(policy: &dyn TruncationPolicy, text: &str, max_chars: usize, expected: &str) {
+        let mut buf = [0u8; LINE_BUF_LEN];
+        assert_eq!(policy.truncate(text, max_chars, &mut buf), expected);
+    }
+
+    #[test]
+    fn end() {
+        check(&End, "Lorem ipsum dolor sit", 18, "Lorem ipsum dol...");
+        check(&End, "Lorem", 5, "Lorem");
+        check(&End, "Lorem", 4, "L...");
+        check(&End, "\u{e9}t\u{e9} chaud", 6, "\u{e9}t\u{e9}...");
+    }
+
+    #[test]
+    fn middle() {
+        check(&Middle, "0x1234567890abcdef", 13, "0x123...bcdef");
+        check(&Middle, "0x1234", 6, "0x1234");
+        check(&Middle, "0x1234", 5, "0...4");
+        check(&Middle, "\u{e9}\u{e8}\u{ea}\u{eb}\u{ee}\u{ef}", 5, "\u{e9}...\u{ef}");
+    }
+
+    #[test]
+    fn marker_clamped_to_max_chars() {
+        for max_chars in 0..TRUNCATION_MARKER.len() {
+            check(&End, "Lorem", max_chars, &TRUNCATION_MARKER[..max_chars]);
+            check(&Middle, "Lorem", max_chars, &TRUNCATION_MARKER[..max_chars]);
+        }
+        check(&End, "Lorem", 3, "...");
+        check(&Clip, "Lorem", 2, "Lorem");
+    }
+}
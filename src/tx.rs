@@ -0,0 +1,709 @@
+//! Transaction review common to all chains:
+//! an app describes its transaction with
+//! `TxSummary` and gets the standard review,
+//! long values spanning as many pages as needed.
+//!
+//! ```ignore
+//! impl TxSummary for MyTx {
+//!     fn amount(&self) -> &str { &self.amount_str }
+//!     fn destination(&self) -> &str { &self.to_str }
+//!     fn fee(&self) -> Option<&str> { Some(&self.fee_str) }
+//! }
+//! match tx.review() { .. }
+//! ```
+//!
+//! Apps without a `TxSummary` review plain
+//! `(name, value)` pairs with `FieldsReview`.
+//!
+//! Once signed, `Receipt` recalls what was
+//! signed so the user can spot a mismatch:
+//!
+//! ```ignore
+//! Receipt::of(&tx, &hash).show();
+//! ```
+//!
+//! `Compare` checks the address the host shows
+//! against the one the device derived.
+
+use crate::bagls::*;
+use crate::batch::Batch;
+use crate::flow::Pager;
+use crate::fmt::{chunk_str, hex, Case};
+use crate::i18n::{tr, Text};
+use crate::input::{Action, AutoRepeat};
+use crate::screen;
+use crate::text::{PageText, TRUNCATION_MARKER};
+use crate::truncate::{Middle, TruncationPolicy, LINE_BUF_LEN, LINE_MAX_CHARS};
+use crate::ui::{Actions, Page, WidgetState, PAGE_MAX_LINES};
+use crate::widget::{run, Event, Outcome, Widget};
+
+pub trait TxSummary {
+    /// Formatted amount, with its ticker
+    fn amount(&self) -> &str;
+    fn destination(&self) -> &str;
+    fn fee(&self) -> Option<&str> {
+        None
+    }
+    /// Other `(name, value)` fields, shown
+    /// after the fee. None past the last one.
+    fn extra(&self, _index: usize) -> Option<(&str, &str)> {
+        None
+    }
+
+    /// Show the default review
+    fn review(&self) -> Outcome where Self: Sized {
+        review(self)
+    }
+}
+
+/// Show the default review of `tx`
+pub fn review(tx: &dyn TxSummary) -> Outcome {
+    #[cfg(feature = "nbgl")]
+    return {
+        let mut fields = [("", ""); crate::nbgl::MAX_FIELDS];
+        let mut len = 0;
+        while let Some(field) = fields.get_mut(len).zip(tx.field(len)) {
+            *field.0 = field.1;
+            len += 1;
+        }
+        crate::nbgl::review(&fields[..len], REVIEW_INTRO)
+    };
+    #[cfg(not(feature = "nbgl"))]
+    run(&mut TxReview::new(tx))
+}
+
+/// Fields shown by a review, in order
+trait Fields {
+    /// Field `index`, None past the last one
+    fn field(&self, index: usize) -> Option<(&str, &str)>;
+}
+
+impl Fields for &dyn TxSummary {
+    fn field(&self, index: usize) -> Option<(&str, &str)> {
+        let fee = self.fee().map_or(0, |_| 1);
+        match index {
+            0 => Some(("Amount", self.amount())),
+            1 => Some(("Destination", self.destination())),
+            2 if fee == 1 => self.fee().map(|f| ("Fee", f)),
+            i => self.extra(i - 2 - fee),
+        }
+    }
+}
+
+impl Fields for &[(&str, &str)] {
+    fn field(&self, index: usize) -> Option<(&str, &str)> {
+        self.get(index).copied()
+    }
+}
+
+/// Number of pages showing `value`,
+/// `size` characters per page
+fn chunk_count(value: &str, size: usize) -> usize {
+    value.chars().count().div_ceil(size).max(1)
+}
+
+/// Part of `value` shown on its page `k`
+fn chunk(value: &str, k: usize, size: usize) -> &str {
+    let mut bounds = value.char_indices().map(|(i, _)| i).chain(Some(value.len()));
+    let start = bounds.nth(k * size).unwrap_or(value.len());
+    let end = bounds.nth(size - 1).unwrap_or(value.len());
+    &value[start..end]
+}
+
+/// Bold title over a value, the standard
+/// look of a review page. A long value spans
+/// several pages, the title then ending with
+/// " (k/n)":
+///
+/// ```ignore
+/// let field = Field::new("Memo", memo);
+/// for k in 0..field.page_count() {
+///     BLANK.paint();
+///     field.paint_page(k);
+/// }
+/// ```
+#[derive(Copy, Clone)]
+pub struct Field<'a> {
+    pub title: &'a str,
+    pub value: &'a str,
+}
+
+impl<'a> Field<'a> {
+    pub const fn new(title: &'a str, value: &'a str) -> Self {
+        Field { title, value }
+    }
+
+    pub fn page_count(&self) -> usize {
+        value_page_count(self.value)
+    }
+
+    /// Paint page `k` of the value
+    pub fn paint_page(&self, k: usize) {
+        let mut title = PageText::new();
+        let mut batch = Batch::new();
+        self.queue_page(&mut batch, k, &mut title);
+        batch.flush();
+    }
+
+    /// Queue page `k` in `batch`, its
+    /// title written to `title`
+    pub(crate) fn queue_page<'b>(&self, batch: &mut Batch<'b>, k: usize,
+        title: &'b mut PageText<FIELD_TITLE_LEN>) where 'a: 'b {
+        let screen = screen::current();
+        *title = PageText::from(self.title);
+        title.push_title_counter(k, self.page_count());
+        batch.push(Bagl::LABELLINE(LabelLine::new().text(title.as_str()).pos(0, screen.y(12)).bold()));
+        let value = LabelLine::new().pos(0, screen.y(26));
+        batch.push(Bagl::LABELLINE(match fits_small(self.value) {
+            true => value.text(self.value).font(Font::SMALL),
+            false => value.text(chunk(self.value, k, LINE_MAX_CHARS)),
+        }));
+    }
+}
+
+/// The first page of the field
+impl Displayable for Field<'_> {
+    fn paint(&self) {
+        self.paint_page(0);
+    }
+}
+
+/// Longest field title, with its page counter
+const FIELD_TITLE_LEN: usize = 40;
+
+/// Width of a value line between the arrows
+const VALUE_LINE_WIDTH: usize = 112;
+
+/// Whether `value` is too long for a line in
+/// the regular font, but fits in the small one
+fn fits_small(value: &str) -> bool {
+    value.chars().count() > LINE_MAX_CHARS && Font::SMALL.width_of(value) <= VALUE_LINE_WIDTH
+}
+
+/// Pages of a field value, a single one when
+/// it fits a line in the small font
+fn value_page_count(value: &str) -> usize {
+    match fits_small(value) {
+        true => 1,
+        false => chunk_count(value, LINE_MAX_CHARS),
+    }
+}
+
+/// Pages of a review: an intro page, the
+/// pages of each field, then approve and
+/// reject pages
+fn page_count(fields: &dyn Fields) -> usize {
+    let field_pages: usize = (0..).map_while(|i| fields.field(i))
+        .map(|(_, value)| value_page_count(value))
+        .sum();
+    field_pages + 3
+}
+
+/// Field, chunk and chunk count of
+/// field page `page`
+fn locate(fields: &dyn Fields, mut page: usize) -> Option<((&str, &str), usize, usize)> {
+    for i in 0.. {
+        let f = fields.field(i)?;
+        let count = value_page_count(f.1);
+        if page < count {
+            return Some((f, page, count))
+        }
+        page -= count;
+    }
+    None
+}
+
+fn draw_review(fields: &dyn Fields, intro: [&str; 2], page: usize) {
+    let count = page_count(fields);
+    let mut bufs = [[0u8; LINE_BUF_LEN]; PAGE_MAX_LINES];
+    let mut title = PageText::new();
+    let (approve, reject) = ([tr(Text::Approve)], [tr(Text::Reject)]);
+    let mut batch = Batch::new();
+    batch.clear();
+    if page == 0 {
+        Page::new(&intro).icon(Icons::EyeBadge).queue(&mut batch, None, &mut bufs);
+    } else if page == count - 2 {
+        Page::new(&approve).icon(Icons::CheckBadge).queue(&mut batch, None, &mut bufs);
+    } else if page == count - 1 {
+        Page::new(&reject).icon(Icons::CrossBadge).queue(&mut batch, None, &mut bufs);
+    } else if let Some(((name, value), k, _)) = locate(fields, page - 1) {
+        Field::new(name, value).queue_page(&mut batch, k, &mut title);
+    }
+    Pager::new(page, count).queue_arrows(&mut batch);
+    batch.flush();
+}
+
+/// Move `page` from `event`, returning
+/// the outcome once chosen
+fn handle_review(fields: &dyn Fields, page: &mut usize, repeat: &mut AutoRepeat,
+    event: Event) -> Option<Outcome> {
+    let count = page_count(fields);
+    match repeat.action(event)? {
+        Action::Previous if *page > 0 => {
+            *page -= 1;
+        }
+        Action::Next if *page + 1 < count => {
+            *page += 1;
+        }
+        Action::Confirm if *page == count - 2 => {
+            return Some(Outcome::Approved)
+        }
+        Action::Confirm if *page == count - 1 => {
+            return Some(Outcome::Rejected)
+        }
+        Action::Reject => return Some(Outcome::Rejected),
+        _ => ()
+    }
+    None
+}
+
+fn review_state(fields: &dyn Fields, widget: &'static str, page: usize) -> WidgetState {
+    let count = page_count(fields);
+    WidgetState::new(widget, page, count)
+        .actions(Actions::PREVIOUS.when(page > 0)
+            .with(Actions::NEXT.when(page + 1 < count))
+            .with(Actions::CONFIRM.when(page + 2 >= count)))
+}
+
+/// Intro page of the reviews
+const REVIEW_INTRO: [&str; 2] = ["Review", "transaction"];
+
+/// The review: an intro page, the pages of
+/// each field, then approve and reject pages
+#[derive(Copy, Clone)]
+pub struct TxReview<'t> {
+    tx: &'t dyn TxSummary,
+    /// Page currently shown
+    page: usize,
+    repeat: AutoRepeat,
+}
+
+impl<'t> TxReview<'t> {
+    pub const fn new(tx: &'t dyn TxSummary) -> Self {
+        TxReview { tx, page: 0, repeat: AutoRepeat::new() }
+    }
+
+    pub fn page_count(&self) -> usize {
+        page_count(&self.tx)
+    }
+}
+
+impl Widget for TxReview<'_> {
+    type Output = Outcome;
+
+    fn draw(&self) {
+        draw_review(&self.tx, REVIEW_INTRO, self.page);
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Outcome> {
+        let page = self.page;
+        let output = handle_review(&self.tx, &mut self.page, &mut self.repeat, event);
+        if self.page != page {
+            self.draw();
+        }
+        output
+    }
+
+    fn state(&self) -> WidgetState {
+        review_state(&self.tx, "TxReview", self.page)
+    }
+}
+
+/// Review of arbitrary `(name, value)` fields,
+/// such as amount, fee, destination or memo,
+/// long values spanning as many pages as needed:
+///
+/// ```ignore
+/// let fields = [("Amount", amount), ("Memo", memo)];
+/// if FieldsReview::new(&fields).ask().is_approved() { .. }
+/// ```
+#[derive(Copy, Clone)]
+pub struct FieldsReview<'a> {
+    fields: &'a [(&'a str, &'a str)],
+    intro: [&'a str; 2],
+    /// Page currently shown
+    page: usize,
+    repeat: AutoRepeat,
+}
+
+impl<'a> FieldsReview<'a> {
+    pub const fn new(fields: &'a [(&'a str, &'a str)]) -> Self {
+        FieldsReview { fields, intro: REVIEW_INTRO, page: 0, repeat: AutoRepeat::new() }
+    }
+
+    /// Replace the "Review transaction" intro
+    pub const fn intro(self, line1: &'a str, line2: &'a str) -> Self {
+        FieldsReview { intro: [line1, line2], ..self }
+    }
+
+    pub fn page_count(&self) -> usize {
+        page_count(&self.fields)
+    }
+
+    pub fn ask(&self) -> Outcome {
+        #[cfg(feature = "nbgl")]
+        return crate::nbgl::review(self.fields, self.intro);
+        #[cfg(not(feature = "nbgl"))]
+        run(&mut { *self })
+    }
+}
+
+impl Widget for FieldsReview<'_> {
+    type Output = Outcome;
+
+    fn draw(&self) {
+        draw_review(&self.fields, self.intro, self.page);
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Outcome> {
+        let page = self.page;
+        let output = handle_review(&self.fields, &mut self.page, &mut self.repeat, event);
+        if self.page != page {
+            self.draw();
+        }
+        output
+    }
+
+    fn state(&self) -> WidgetState {
+        review_state(&self.fields, "FieldsReview", self.page)
+    }
+}
+
+/// Characters per group of an `AddressConfirm`
+pub const ADDRESS_GROUP_LEN: usize = 4;
+
+/// Confirmation of an address or hash, split in
+/// groups of characters that are easy to compare
+/// ("0x12 3456 789a"), over as many "Address (k/n)"
+/// pages as needed, then approve and reject pages
+#[derive(Copy, Clone)]
+pub struct AddressConfirm<'a> {
+    address: &'a str,
+    title: &'a str,
+    /// Characters per group
+    group: usize,
+    /// Page currently shown
+    page: usize,
+}
+
+impl<'a> AddressConfirm<'a> {
+    pub const fn new(address: &'a str) -> Self {
+        AddressConfirm { address, title: "Address", group: ADDRESS_GROUP_LEN, page: 0 }
+    }
+
+    /// Replace the "Address" title
+    pub const fn title(self, title: &'a str) -> Self {
+        AddressConfirm { title, ..self }
+    }
+
+    /// Characters per group, at least 1
+    pub const fn group(self, group: usize) -> Self {
+        AddressConfirm { group: if group == 0 { 1 } else { group }, ..self }
+    }
+
+    /// Characters of the address per page:
+    /// as many groups as fit on a line
+    fn page_len(&self) -> usize {
+        let groups = (LINE_MAX_CHARS + 1) / (self.group + 1);
+        self.group * groups.max(1)
+    }
+
+    fn address_pages(&self) -> usize {
+        chunk_count(self.address, self.page_len())
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.address_pages() + 2
+    }
+
+    pub(crate) const fn address(&self) -> &'a str {
+        self.address
+    }
+
+    /// Page currently shown
+    pub(crate) const fn page(&self) -> usize {
+        self.page
+    }
+
+    pub fn ask(&self) -> Outcome {
+        run(&mut { *self })
+    }
+}
+
+impl Widget for AddressConfirm<'_> {
+    type Output = Outcome;
+
+    fn draw(&self) {
+        let (pages, count) = (self.address_pages(), self.page_count());
+        BLANK.paint();
+        if self.page == count - 2 {
+            Page::new(&[tr(Text::Approve)]).icon(Icons::CheckBadge).paint();
+        } else if self.page == count - 1 {
+            Page::new(&[tr(Text::Reject)]).icon(Icons::CrossBadge).paint();
+        } else {
+            let mut title = PageText::<40>::from(self.title);
+            title.push_title_counter(self.page, pages);
+            let mut buf = [0u8; 2 * LINE_BUF_LEN];
+            let part = chunk(self.address, self.page, self.page_len());
+            let grouped = chunk_str(part, self.group, " ", &mut buf).unwrap_or(part);
+            LabelLine::new().text(title.as_str()).pos(0, 12).bold().paint();
+            LabelLine::new().text(grouped).pos(0, 26).paint();
+        }
+        if self.page > 0 {
+            LEFT_ARROW.paint();
+        }
+        if self.page + 1 < count {
+            RIGHT_ARROW.paint();
+        }
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Outcome> {
+        let count = self.page_count();
+        match event.action()? {
+            Action::Previous if self.page > 0 => {
+                self.page -= 1;
+                self.draw();
+            }
+            Action::Next if self.page + 1 < count => {
+                self.page += 1;
+                self.draw();
+            }
+            Action::Confirm if self.page == count - 2 => {
+                return Some(Outcome::Approved)
+            }
+            Action::Confirm if self.page == count - 1 => {
+                return Some(Outcome::Rejected)
+            }
+            Action::Reject => return Some(Outcome::Rejected),
+            _ => ()
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        let count = self.page_count();
+        WidgetState::new("AddressConfirm", self.page, count)
+            .actions(Actions::PREVIOUS.when(self.page > 0)
+                .with(Actions::NEXT.when(self.page + 1 < count))
+                .with(Actions::CONFIRM.when(self.page + 2 >= count)))
+    }
+}
+
+/// The value the device expects over the one
+/// the host sent, a group of characters of
+/// each per page, the expected one in bold,
+/// then "Match" and "Mismatch" pages. Approved
+/// if the user found they match.
+#[derive(Copy, Clone)]
+pub struct Compare<'a> {
+    expected: &'a str,
+    received: &'a str,
+    /// Characters per group
+    group: usize,
+    /// Page currently shown
+    page: usize,
+}
+
+impl<'a> Compare<'a> {
+    pub const fn new(expected: &'a str, received: &'a str) -> Self {
+        Compare { expected, received, group: ADDRESS_GROUP_LEN, page: 0 }
+    }
+
+    /// Characters per group, at least 1
+    pub const fn group(self, group: usize) -> Self {
+        Compare { group: if group == 0 { 1 } else { group }, ..self }
+    }
+
+    /// Characters of each value per page,
+    /// as in `AddressConfirm`
+    fn page_len(&self) -> usize {
+        let groups = (LINE_MAX_CHARS + 1) / (self.group + 1);
+        self.group * groups.max(1)
+    }
+
+    fn value_pages(&self) -> usize {
+        let size = self.page_len();
+        chunk_count(self.expected, size).max(chunk_count(self.received, size))
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.value_pages() + 2
+    }
+
+    pub fn ask(&self) -> Outcome {
+        run(&mut { *self })
+    }
+}
+
+impl Widget for Compare<'_> {
+    type Output = Outcome;
+
+    fn draw(&self) {
+        let count = self.page_count();
+        BLANK.paint();
+        if self.page == count - 2 {
+            Page::new(&[tr(Text::Match)]).icon(Icons::CheckBadge).paint();
+        } else if self.page == count - 1 {
+            Page::new(&[tr(Text::Mismatch)]).icon(Icons::CrossBadge).paint();
+        } else {
+            let size = self.page_len();
+            let mut expected = [0u8; 2 * LINE_BUF_LEN];
+            let mut received = [0u8; 2 * LINE_BUF_LEN];
+            let part = chunk(self.expected, self.page, size);
+            let grouped = chunk_str(part, self.group, " ", &mut expected).unwrap_or(part);
+            LabelLine::new().text(grouped).pos(0, 12).bold().paint();
+            let part = chunk(self.received, self.page, size);
+            let grouped = chunk_str(part, self.group, " ", &mut received).unwrap_or(part);
+            LabelLine::new().text(grouped).pos(0, 26).paint();
+        }
+        if self.page > 0 {
+            LEFT_ARROW.paint();
+        }
+        if self.page + 1 < count {
+            RIGHT_ARROW.paint();
+        }
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Outcome> {
+        let count = self.page_count();
+        match event.action()? {
+            Action::Previous if self.page > 0 => {
+                self.page -= 1;
+                self.draw();
+            }
+            Action::Next if self.page + 1 < count => {
+                self.page += 1;
+                self.draw();
+            }
+            Action::Confirm if self.page == count - 2 => {
+                return Some(Outcome::Approved)
+            }
+            Action::Confirm if self.page == count - 1 => {
+                return Some(Outcome::Rejected)
+            }
+            Action::Reject => return Some(Outcome::Rejected),
+            _ => ()
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        let count = self.page_count();
+        WidgetState::new("Compare", self.page, count)
+            .actions(Actions::PREVIOUS.when(self.page > 0)
+                .with(Actions::NEXT.when(self.page + 1 < count))
+                .with(Actions::CONFIRM.when(self.page + 2 >= count)))
+    }
+}
+
+/// Bytes of the hash kept at each end
+/// of its short form
+const HASH_SHORT_BYTES: usize = 4;
+
+/// "01ab23cd...89ef4567", or the whole
+/// hash in hex if it is short enough
+fn short_hash(hash: &[u8]) -> PageText<24> {
+    let mut out = PageText::new();
+    let mut buf = [0u8; 2 * HASH_SHORT_BYTES];
+    if hash.len() <= 2 * HASH_SHORT_BYTES {
+        let mut whole = [0u8; 4 * HASH_SHORT_BYTES];
+        out.push_str(hex(hash, &mut whole, Case::Lower).unwrap_or(""));
+        return out
+    }
+    out.push_str(hex(&hash[..HASH_SHORT_BYTES], &mut buf, Case::Lower).unwrap_or(""));
+    out.push_str(TRUNCATION_MARKER);
+    out.push_str(hex(&hash[hash.len() - HASH_SHORT_BYTES..], &mut buf, Case::Lower).unwrap_or(""));
+    out
+}
+
+/// Summary shown after signing: the amount,
+/// destination and short transaction hash,
+/// until the last page is acknowledged
+#[derive(Copy, Clone)]
+pub struct Receipt<'a> {
+    pub amount: &'a str,
+    pub destination: &'a str,
+    pub hash: &'a [u8],
+    /// Page currently shown
+    page: usize,
+}
+
+impl<'a> Receipt<'a> {
+    /// Number of pages, the last one
+    /// acknowledging the receipt
+    pub const PAGE_COUNT: usize = 5;
+
+    pub const fn new(amount: &'a str, destination: &'a str, hash: &'a [u8]) -> Self {
+        Receipt { amount, destination, hash, page: 0 }
+    }
+
+    /// Receipt of the reviewed `tx`
+    pub fn of(tx: &'a dyn TxSummary, hash: &'a [u8]) -> Self {
+        Receipt::new(tx.amount(), tx.destination(), hash)
+    }
+
+    /// Browse the receipt until acknowledged
+    pub fn show(&self) {
+        run(&mut { *self })
+    }
+}
+
+impl Widget for Receipt<'_> {
+    type Output = ();
+
+    fn draw(&self) {
+        BLANK.paint();
+        let mut buf = [0u8; 4 * LINE_MAX_CHARS];
+        let hash = short_hash(self.hash);
+        let field = match self.page {
+            0 => {
+                Page::new(&["Transaction", "signed"]).icon(Icons::CheckBadge).paint();
+                None
+            }
+            1 => Some(("Amount", self.amount)),
+            2 => Some(("To", Middle.truncate(self.destination, LINE_MAX_CHARS, &mut buf))),
+            3 => Some(("Tx hash", hash.as_str())),
+            _ => {
+                Page::new(&["Done"]).icon(Icons::CheckBadge).paint();
+                None
+            }
+        };
+        if let Some((name, value)) = field {
+            LabelLine::new().text(name).pos(0, 12).bold().paint();
+            LabelLine::new().text(value).pos(0, 26).paint();
+        }
+        if self.page > 0 {
+            LEFT_ARROW.paint();
+        }
+        if self.page + 1 < Self::PAGE_COUNT {
+            RIGHT_ARROW.paint();
+        }
+    }
+
+    fn handle(&mut self, event: Event) -> Option<()> {
+        match event.action()? {
+            Action::Previous if self.page > 0 => {
+                self.page -= 1;
+                self.draw();
+            }
+            Action::Next if self.page + 1 < Self::PAGE_COUNT => {
+                self.page += 1;
+                self.draw();
+            }
+            Action::Confirm if self.page + 1 == Self::PAGE_COUNT => return Some(()),
+            _ => ()
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("Receipt", self.page, Self::PAGE_COUNT)
+            .actions(Actions::PREVIOUS.when(self.page > 0)
+                .with(Actions::NEXT.when(self.page + 1 < Self::PAGE_COUNT))
+                .with(Actions::CONFIRM.when(self.page + 1 == Self::PAGE_COUNT)))
+    }
+
+    fn on_idle(&mut self) -> Option<()> {
+        Some(())
+    }
+}
@@ -1,113 +1,566 @@
 #![allow(dead_code)] 
 
-use nanos_sdk::*;
-use nanos_sdk::buttons::{ButtonsState, ButtonEvent, get_button_event};
+use crate::seph;
+use crate::buttons::{ButtonEvent, ButtonsState, get_button_event};
 use crate::bagls::*;
+use crate::batch::Batch;
+use crate::breadcrumb;
+use crate::canvas::Frame;
+use crate::flow::{Pager, INDICATOR_LEN};
+use crate::screen;
+use crate::i18n::{tr, Text};
+use crate::style::{self, BOTTOM_LINE_Y};
+use crate::text::{PageBuffer, PageProvider, PageText};
+use crate::truncate::{fit, TruncationPolicy, LINE_BUF_LEN, LINE_MAX_CHARS};
+use crate::input::{input_of, Action, AutoRepeat, Input, KeyMap, Keys};
+use crate::widget::{run, run_for, run_until, AbortReason, Event, Outcome, Widget, UNKNOWN_DATA_LEN};
 
 
 /// Handles communication to filter
 /// out actual events, and converts key
-/// events into presses/releases
-pub fn get_event(buttons: &mut ButtonsState) -> Option<ButtonEvent> {
+/// events into presses/releases. Ticker
+/// events (every 100 ms) are passed on so
+/// loops can expire.
+pub fn get_event(buttons: &mut ButtonsState) -> Option<Event> {
+    if let Some(event) = buttons.pending.take() {
+        return Some(Event::Button(event))
+    }
+    // Received while painting
+    while let Some(packet) = crate::bagls::take_deferred() {
+        buttons.cmd_buffer = packet;
+        if let Some(event) = decode(buttons) {
+            return event
+        }
+    }
+    // A replayed recording stands in for SEPH
+    #[cfg(feature = "event-tap")]
+    while crate::tap::replay_next(&mut buttons.cmd_buffer) {
+        if let Some(event) = decode(buttons) {
+            return event
+        }
+    }
     if !seph::is_status_sent() {
         seph::send_general_status();
     }
 
-    // TODO: Receiving an APDU while in UX will lead to .. exit ?
+    // APDUs are not read here: widgets that must
+    // keep the transport alive run with
+    // `sign::run_with_comm`
     while seph::is_status_sent() {
         seph::seph_recv(&mut buttons.cmd_buffer, 0);
-        let tag = buttons.cmd_buffer[0];
-
-        // button push event
-        if tag == 0x05 { 
-            let button_info = buttons.cmd_buffer[3]>>1;
-            return get_button_event(buttons, button_info)
+        #[cfg(feature = "state-dump")]
+        crate::debug::record_event(&buttons.cmd_buffer);
+        #[cfg(feature = "event-tap")]
+        crate::tap::record(&buttons.cmd_buffer);
+        if let Some(event) = decode(buttons) {
+            return event
         }
     }
     None
 }
 
+/// Event of the packet in `cmd_buffer`,
+/// None for packets widgets ignore
+fn decode(buttons: &mut ButtonsState) -> Option<Option<Event>> {
+    let tag = buttons.cmd_buffer[0];
+
+    // button push event
+    if tag == 0x05 { 
+        buttons.idle_ticks = 0;
+        let button_info = buttons.cmd_buffer[3]>>1;
+        return Some(get_button_event(buttons, button_info).map(Event::Button))
+    }
+    // ticker event
+    if tag == 0x0E {
+        buttons.idle_ticks = buttons.idle_ticks.saturating_add(1);
+        // Given by the next call, after the tick
+        if let Some(long_press) = buttons.tick() {
+            buttons.pending = Some(long_press);
+        }
+        return Some(Some(Event::Ticker))
+    }
+    // battery notification (Nano X)
+    if tag == 0x08 {
+        let event = Event::BatteryLevel(buttons.cmd_buffer[3].min(100));
+        crate::status::record(event);
+        return Some(Some(event))
+    }
+    // BLE connection change (Nano X)
+    if tag == 0x0A {
+        let event = Event::BleConnection(buttons.cmd_buffer[3] != 0);
+        crate::status::record(event);
+        return Some(Some(event))
+    }
+    // finger event (Stax, Flex)
+    #[cfg(feature = "nbgl")]
+    if tag == crate::touch::FINGER_EVENT {
+        let packet = buttons.cmd_buffer;
+        if let Some(touch) = crate::touch::decode(&mut buttons.touch_start, &packet) {
+            buttons.idle_ticks = 0;
+            return Some(Some(Event::Touch(touch)))
+        }
+        return None
+    }
+    // display processed: painting waits for it
+    if tag == DISPLAY_PROCESSED_EVENT {
+        return None
+    }
+    let mut data = [0u8; UNKNOWN_DATA_LEN];
+    data.copy_from_slice(&buttons.cmd_buffer[3..3 + UNKNOWN_DATA_LEN]);
+    Some(Some(Event::Unknown { tag, data }))
+}
+
+/// Snapshot of a widget's navigation state.
+/// Widgets report it each time they wait for
+/// an event; the `sim` backend keeps the history
+/// so tests can assert intermediate states.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct WidgetState {
+    pub widget: &'static str,
+    /// Current page, entry or selected option
+    pub index: usize,
+    /// Number of pages, entries or options
+    pub count: usize,
+    /// Actions accepted in the current state
+    pub actions: Actions,
+}
+
+impl WidgetState {
+    pub const fn new(widget: &'static str, index: usize, count: usize) -> Self {
+        WidgetState { widget, index, count, actions: Actions::NONE }
+    }
+
+    pub const fn actions(self, actions: Actions) -> Self {
+        WidgetState { actions, ..self }
+    }
+}
+
+/// Set of navigation actions a widget accepts
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Actions(pub u8);
+
+impl Actions {
+    pub const NONE: Actions = Actions(0);
+    /// Left button: previous page or entry
+    pub const PREVIOUS: Actions = Actions(1);
+    /// Right button: next page or entry
+    pub const NEXT: Actions = Actions(2);
+    /// Both buttons: confirm, select or exit
+    pub const CONFIRM: Actions = Actions(4);
+    pub const ALL: Actions = Actions(7);
+
+    pub const fn with(self, other: Actions) -> Actions {
+        Actions(self.0 | other.0)
+    }
+
+    /// `self` if `cond` holds, `NONE` otherwise
+    pub const fn when(self, cond: bool) -> Actions {
+        if cond { self } else { Actions::NONE }
+    }
+
+    pub const fn contains(self, other: Actions) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+#[inline(always)]
+pub(crate) fn report(_state: WidgetState) {
+    #[cfg(feature = "sim")]
+    crate::sim::record_state(_state);
+    #[cfg(feature = "state-dump")]
+    crate::debug::record_state(_state);
+    #[cfg(feature = "ragger")]
+    crate::nav::record(_state);
+}
+
 /// Shorthand to display a single message
 /// and wait for button action
 pub fn popup(message: &str) {
     SingleMessage::new(&message).show_and_wait();
 }
 
+/// `popup`, dismissed by itself after `ticks`
+/// ticker events. Returns whether the user
+/// dismissed it first.
+pub fn popup_timeout(message: &str, ticks: u32) -> bool {
+    SingleMessage::new(message).show_for(ticks)
+}
+
+/// Longest text accepted for a single label,
+/// bounded by the size of SEPH packets
+pub const TEXT_MAX_LEN: usize = 255;
+
+/// Invalid widget input, reported by the
+/// `try_new` constructors rather than at
+/// draw time inside the event loop
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Nothing to show
+    Empty,
+    /// Fewer entries than the widget needs
+    TooFew,
+    /// A string longer than `TEXT_MAX_LEN`
+    TooLong,
+    /// More lines than a page holds
+    TooManyLines,
+    /// A flow starting with a step that
+    /// requires the PIN
+    PinFirst,
+}
+
+pub(crate) fn check_text(text: &str) -> Result<(), Error> {
+    match text.len() {
+        n if n > TEXT_MAX_LEN => Err(Error::TooLong),
+        _ => Ok(()),
+    }
+}
+
+fn check_lines(lines: &[&str]) -> Result<(), Error> {
+    if lines.len() > PAGE_MAX_LINES {
+        return Err(Error::TooManyLines)
+    }
+    lines.iter().try_for_each(|l| check_text(l))
+}
+
 /// Display a single screen with a message,
-/// and exit the function with 'true'
+/// and exit the function with `Approved`
 /// if the user validated 'message'
-/// or false if the user aborted
+/// or `Rejected` if the user aborted
+#[derive(Copy, Clone)]
 pub struct Validator<'a> {
-    message: &'a str,
+    pub(crate) message: &'a str,
+    pub(crate) truncation: Option<&'a dyn TruncationPolicy>,
+    /// Whether the message is selected,
+    /// rather than cancel
+    pub(crate) response: bool,
+    /// Buttons giving each action, instead
+    /// of the installed mapper
+    pub(crate) keys: Option<Keys>,
 }
 
 impl<'a> Validator<'a> {
-    pub fn new(message: &'a str) -> Self {
-        Validator { message }
+    pub const fn new(message: &'a str) -> Self {
+        Validator { message, truncation: None, response: true, keys: None }
+    }
+
+    pub fn try_new(message: &'a str) -> Result<Self, Error> {
+        check_text(message)?;
+        Ok(Self::new(message))
     }
 
-    pub fn ask(&self) -> bool {
-        let mut buttons = ButtonsState::new();
+    /// Shorten a long message with `policy`
+    /// instead of the default one
+    pub const fn truncation(self, policy: &'a dyn TruncationPolicy) -> Self {
+        Validator { truncation: Some(policy), ..self }
+    }
 
-        let cancel = LabelLine::new().dims(128, 11).pos(0, 26).text("Cancel"); 
-        let yes = LabelLine::new().dims(128, 11).pos(0, 12)
-                                    .text(self.message);
+    /// Take actions from the buttons as `keys`
+    /// maps them, e.g. `KeyMap::HOLD_TO_CONFIRM`
+    pub const fn keys(self, keys: KeyMap) -> Self {
+        Validator { keys: Some(Keys::new(keys)), ..self }
+    }
 
-        cancel.display();
-        yes.bold().paint();
+    /// Message and cancel labels
+    fn labels<'b>(&self, buf: &'b mut [u8]) -> (LabelLine<'b>, LabelLine<'b>) where 'a: 'b {
+        let screen = screen::current();
+        let yes = LabelLine::new().dims(128, 11).pos(0, screen.y(12))
+                                    .text(fit(self.message, self.truncation, buf));
+        let cancel = LabelLine::new().dims(128, 11).pos(0, screen.y(26)).text(tr(Text::Cancel));
+        (yes, cancel)
+    }
 
-        let mut response = true;
+    pub fn ask(&self) -> Outcome {
+        #[cfg(feature = "nbgl")]
+        return crate::nbgl::choice(self.message);
+        #[cfg(not(feature = "nbgl"))]
+        run(&mut { *self })
+    }
 
-        loop {
-            match get_event(&mut buttons) {
-                Some(ButtonEvent::LeftButtonPress) => {
-                    UP_ARROW.paint();
-                }
-                Some(ButtonEvent::RightButtonPress) => {
-                    DOWN_ARROW.paint();
-                }
-                Some(ButtonEvent::LeftButtonRelease) => {
-                    response = true;
-                    cancel.display();
-                    yes.bold().paint();
-                } 
-                Some(ButtonEvent::RightButtonRelease) => {
-                    response = false;
-                    cancel.bold().display();
-                    yes.paint();
-                }
-                Some(ButtonEvent::BothButtonsPress) => {
-                    match response {
-                        true => {
-                            yes.bold().display();
-                        },
-                        false => {
-                            cancel.bold().display();
-                        } 
-                    };
-                }
-                Some(ButtonEvent::BothButtonsRelease) => {
-                    return response
+    /// `ask`, aborted once `should_abort` holds,
+    /// see `run_until`. On NBGL devices it is
+    /// not checked.
+    pub fn ask_until(&self, should_abort: impl Fn() -> bool) -> Outcome {
+        #[cfg(feature = "nbgl")]
+        let outcome = {
+            let _ = should_abort;
+            crate::nbgl::choice(self.message)
+        };
+        #[cfg(not(feature = "nbgl"))]
+        let outcome = run_until(&mut { *self }, should_abort).unwrap_or(Outcome::Aborted(AbortReason::App));
+        outcome
+    }
+
+    /// `ask`, giving up after `ticks` ticker
+    /// events (10 per second): whether the
+    /// message was approved, None if timed out.
+    /// On NBGL devices it does not time out.
+    pub fn ask_with_timeout(&self, ticks: u32) -> Option<bool> {
+        #[cfg(feature = "nbgl")]
+        let outcome = {
+            let _ = ticks;
+            Some(crate::nbgl::choice(self.message))
+        };
+        #[cfg(not(feature = "nbgl"))]
+        let outcome = run_for(&mut { *self }, ticks);
+        outcome.map(Outcome::is_approved)
+    }
+
+    /// `ask`, awaited instead of blocking,
+    /// see `future`. On NBGL devices it blocks.
+    pub async fn ask_async(&self) -> Outcome {
+        #[cfg(feature = "nbgl")]
+        let outcome = crate::nbgl::choice(self.message);
+        #[cfg(not(feature = "nbgl"))]
+        let outcome = crate::future::run_async(&mut { *self }).await;
+        outcome
+    }
+}
+
+impl Widget for Validator<'_> {
+    type Output = Outcome;
+
+    fn enter(&mut self) {
+        if let Some(keys) = &mut self.keys {
+            keys.reset();
+        }
+    }
+
+    fn draw(&self) {
+        let mut buf = [0u8; LINE_BUF_LEN];
+        let (yes, cancel) = self.labels(&mut buf);
+        if self.response {
+            cancel.display();
+            yes.bold().paint();
+        } else {
+            cancel.bold().display();
+            yes.paint();
+        }
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Outcome> {
+        match input_of(&mut self.keys, event)? {
+            Input::Pending(Action::Previous) => {
+                screen::current().place(UP_ARROW).paint();
+            }
+            Input::Pending(Action::Next) => {
+                screen::current().place(DOWN_ARROW).paint();
+            }
+            Input::Complete(Action::Previous) => {
+                self.response = true;
+                self.draw();
+            }
+            Input::Complete(Action::Next) => {
+                self.response = false;
+                self.draw();
+            }
+            Input::Pending(Action::Confirm) => {
+                let mut buf = [0u8; LINE_BUF_LEN];
+                let (yes, cancel) = self.labels(&mut buf);
+                match self.response {
+                    true => yes.bold().display(),
+                    false => cancel.bold().display(),
                 }
-                _ => ()
             }
+            Input::Complete(Action::Confirm) => {
+                return Some(self.response.into())
+            }
+            Input::Complete(Action::Reject) => {
+                return Some(Outcome::Rejected)
+            }
+            Input::Pending(Action::Reject) => (),
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("Validator", !self.response as usize, 2).actions(Actions::ALL)
+    }
+}
+
+/// A message between a cross above the left
+/// button and a check above the right one, as
+/// in the official apps: releasing the left
+/// button rejects, the right one approves.
+/// Pressing both does nothing.
+#[derive(Copy, Clone)]
+pub struct BinaryChoice<'a> {
+    pub(crate) message: &'a str,
+    pub(crate) truncation: Option<&'a dyn TruncationPolicy>,
+}
+
+impl<'a> BinaryChoice<'a> {
+    pub const fn new(message: &'a str) -> Self {
+        BinaryChoice { message, truncation: None }
+    }
+
+    pub fn try_new(message: &'a str) -> Result<Self, Error> {
+        check_text(message)?;
+        Ok(Self::new(message))
+    }
+
+    /// Shorten a long message with `policy`
+    /// instead of the default one
+    pub const fn truncation(self, policy: &'a dyn TruncationPolicy) -> Self {
+        BinaryChoice { truncation: Some(policy), ..self }
+    }
+
+    pub fn ask(&self) -> Outcome {
+        #[cfg(feature = "nbgl")]
+        return crate::nbgl::choice(self.message);
+        #[cfg(not(feature = "nbgl"))]
+        run(&mut { *self })
+    }
+}
+
+impl Widget for BinaryChoice<'_> {
+    type Output = Outcome;
+
+    fn draw(&self) {
+        let screen = screen::current();
+        let mut buf = [0u8; LINE_BUF_LEN];
+        LabelLine::new().dims(104, 11).pos(12, screen.y(19))
+            .text(fit(self.message, self.truncation, &mut buf)).display();
+        screen.place(Icon::new(Icons::Cross).pos(3, 12)).paint();
+        screen.place(Icon::new(Icons::Check).pos(118, 12)).paint();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Outcome> {
+        // The glyphs sit above the buttons
+        // themselves, whatever the mapper
+        match event {
+            Event::Button(ButtonEvent::LeftButtonRelease) => Some(Outcome::Rejected),
+            Event::Button(ButtonEvent::RightButtonRelease) => Some(Outcome::Approved),
+            Event::Input(Input::Complete(Action::Reject)) => Some(Outcome::Rejected),
+            Event::Input(Input::Complete(Action::Confirm)) => Some(Outcome::Approved),
+            _ => None,
+        }
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("BinaryChoice", 0, 1).actions(Actions::ALL)
+    }
+}
+
+/// Maximum number of text lines on a `Page`
+pub const PAGE_MAX_LINES: usize = 2;
+
+/// A screen made of an optional icon and up
+/// to `PAGE_MAX_LINES` lines of text, usually
+/// declared with `ui_pages!`
+#[derive(Copy, Clone)]
+pub struct Page<'a> {
+    pub icon: Option<Icon>,
+    pub lines: &'a [&'a str],
+}
+
+impl<'a> Page<'a> {
+    pub const fn new(lines: &'a [&'a str]) -> Self {
+        Page { icon: None, lines }
+    }
+
+    pub fn try_new(lines: &'a [&'a str]) -> Result<Self, Error> {
+        check_lines(lines)?;
+        Ok(Self::new(lines))
+    }
+
+    pub const fn icon(self, icon: Icons) -> Self {
+        Page { icon: Some(Icon::new(icon).pos(16, 12)), ..self }
+    }
+
+    /// Paint the page, shortening long lines
+    /// with `truncation` or the default policy
+    pub(crate) fn paint_with(&self, truncation: Option<&dyn TruncationPolicy>) {
+        let mut bufs = [[0u8; LINE_BUF_LEN]; PAGE_MAX_LINES];
+        let mut batch = Batch::new();
+        self.queue(&mut batch, truncation, &mut bufs);
+        batch.flush();
+    }
+
+    /// Queue the page in `batch`, shortening
+    /// long lines into `bufs`
+    pub(crate) fn queue<'b>(&self, batch: &mut Batch<'b>, truncation: Option<&dyn TruncationPolicy>,
+        bufs: &'b mut [[u8; LINE_BUF_LEN]; PAGE_MAX_LINES]) where 'a: 'b {
+        widget_debug_assert!(self.lines.len() <= PAGE_MAX_LINES, "a page has at most two lines");
+        let screen = screen::current();
+        if let Some(icon) = self.icon {
+            batch.push(Bagl::ICON(screen.place(icon)));
+        }
+        let style = style::get();
+        let ys: &[i16] = match self.lines.len() {
+            0 => &[],
+            1 => &[20],
+            _ => &[style.top_line_y(), BOTTOM_LINE_Y],
+        };
+        for ((line, y), buf) in self.lines.iter().zip(ys).zip(bufs.iter_mut()) {
+            let label = LabelLine::new().text(fit(line, truncation, buf)).pos(0, screen.y(*y));
+            batch.push(Bagl::LABELLINE(match &self.icon {
+                Some(icon) => label.beside(icon),
+                None => label,
+            }));
         }
     }
 }
 
+impl Displayable for Page<'_> {
+    fn paint(&self) {
+        self.paint_with(None);
+    }
+}
+
+/// Declare a `static` slice of `Page`s, each a
+/// list of lines optionally preceded by an icon:
+///
+/// ```ignore
+/// ui_pages!(static REVIEW = [
+///     [Icons::EyeBadge; "Review", "transaction"],
+///     ["Amount", "0.1 BTC"],
+///     ["Fees", "0.0001 BTC"],
+/// ]);
+///
+/// HScroller::new(&[]).pages(REVIEW).event_loop();
+/// ```
+#[macro_export]
+macro_rules! ui_pages {
+    (static $name:ident = [$($page:tt),* $(,)?]) => {
+        static $name: &[$crate::ui::Page<'static>] = &[$($crate::ui_pages!(@page $page)),*];
+    };
+    (@page [$icon:expr; $($line:expr),* $(,)?]) => {
+        $crate::ui::Page::new(&[$($line),*]).icon($icon)
+    };
+    (@page [$($line:expr),* $(,)?]) => {
+        $crate::ui::Page::new(&[$($line),*])
+    };
+}
+
+#[derive(Copy, Clone)]
 pub struct MessageValidator<'a> {
     /// Strings displayed in the pages. One string per page. Can be empty.
-    message: &'a [&'a str],
+    pub(crate) message: &'a [&'a str],
+    /// Pages displayed after `message`
+    pub(crate) pages: &'a [Page<'a>],
+    /// Number of pages formatted on demand,
+    /// displayed after `pages`, and their provider
+    pub(crate) provider: Option<(usize, PageProvider<'a>)>,
     /// Strings displayed in the confirmation page.
     /// 0 element: only the icon is displayed, in center of the screen.
     /// 1 element: icon and one line of text displayed.
     /// 2 elements: icon and two lines of text displayed.
-    confirm: &'a [&'a str],
+    pub(crate) confirm: &'a [&'a str],
     /// Strings displayed in the cancel page.
     /// 0 element: only the icon is displayed, in center of the screen.
     /// 1 element: icon and one line of text displayed.
     /// 2 elements: icon and two lines of text displayed.
-    cancel: &'a [&'a str]
+    pub(crate) cancel: &'a [&'a str],
+    pub(crate) truncation: Option<&'a dyn TruncationPolicy>,
+    /// Page currently shown
+    pub(crate) page: usize,
+    /// Go from the last page to the first
+    pub(crate) wrap: bool,
+    /// Show the "k/n" page indicator
+    pub(crate) indicator: bool,
+    /// Buttons giving each action, instead
+    /// of the installed mapper
+    pub(crate) keys: Option<Keys>,
 }
 
 impl<'a> MessageValidator<'a> {
@@ -116,316 +569,1602 @@ impl<'a> MessageValidator<'a> {
 
         MessageValidator {
             message: message,
+            pages: &[],
+            provider: None,
             confirm: confirm,
-            cancel: cancel
-        }
-    }
-
-    pub fn ask(&self) -> bool {
-        let page_count = &self.message.len() + 2;
-        let mut cur_page = 0;
-
-        let draw_icon_and_text = |icon: Icons, strings: &[&str]| {
-            // Draw icon on the center if there is no text.
-            let (x, y) = match strings.len() {
-                0 => (16, 12),
-                _ => (16, 12)
-            };
-            Bagl::ICON(Icon::new(icon).pos(x, y)).display();
-            match strings.len() {
-                0 => {},
-                1 => {
-                    Bagl::LABELLINE(LabelLine::new().text(&strings[0])
-                        .pos(0, 20)).paint();
-                },
-                _ => {
-                    Bagl::LABELLINE(LabelLine::new().text(&strings[0])
-                        .pos(0, 13)).paint();
-                    Bagl::LABELLINE(LabelLine::new().text(&strings[1])
-                        .pos(0, 26)).paint();
-                }
-            }
-        };
+            cancel: cancel,
+            truncation: None,
+            page: 0,
+            wrap: false,
+            indicator: false,
+            keys: None,
+        }
+    }
 
-        let draw = |page: usize| {
-            if page == page_count - 2 {
-                draw_icon_and_text(Icons::CheckBadge, &self.confirm);
-                RIGHT_ARROW.paint();
-            } else if page == page_count - 1 {
-                draw_icon_and_text(Icons::CrossBadge, &self.cancel);
-            } else {
-                Bagl::LABELLINE(LabelLine::new().text(&self.message[page]))
-                    .display();
-                RIGHT_ARROW.paint();
-            }
-            if page > 0 {
-                LEFT_ARROW.paint();
-            }
+    pub fn try_new(message: &'a [&'a str], confirm: &'a [&'a str],
+        cancel: &'a [&'a str]) -> Result<Self, Error> {
+        message.iter().try_for_each(|m| check_text(m))?;
+        check_lines(confirm)?;
+        check_lines(cancel)?;
+        Ok(Self::new(message, confirm, cancel))
+    }
+
+    /// Shorten long lines with `policy`
+    /// instead of the default one
+    pub const fn truncation(self, policy: &'a dyn TruncationPolicy) -> Self {
+        MessageValidator { truncation: Some(policy), ..self }
+    }
+
+    /// Take actions from the buttons as `keys`
+    /// maps them, e.g. `KeyMap::HOLD_TO_CONFIRM`
+    pub const fn keys(self, keys: KeyMap) -> Self {
+        MessageValidator { keys: Some(Keys::new(keys)), ..self }
+    }
+
+    /// Show `pages` after the message strings
+    pub const fn pages(self, pages: &'a [Page<'a>]) -> Self {
+        MessageValidator { pages, ..self }
+    }
+
+    /// Go from the cancel page to the first
+    /// one, and back
+    pub const fn wrap(self) -> Self {
+        MessageValidator { wrap: true, ..self }
+    }
+
+    /// Show the number of the page in
+    /// a corner, see `Pager::queue_indicator`
+    pub const fn page_indicator(self) -> Self {
+        MessageValidator { indicator: true, ..self }
+    }
+
+    /// Show `count` more pages, each formatted
+    /// by `provider` only when shown
+    pub const fn provider(self, count: usize, provider: PageProvider<'a>) -> Self {
+        MessageValidator { provider: Some((count, provider)), ..self }
+    }
+
+    /// Number of pages, including the
+    /// confirmation and cancel pages
+    pub const fn page_count(&self) -> usize {
+        let provided = match self.provider {
+            Some((count, _)) => count,
+            None => 0,
         };
+        self.message.len() + self.pages.len() + provided + 2
+    }
 
-        draw(cur_page);
+    pub fn ask(&self) -> Outcome {
+        run(&mut { *self })
+    }
 
-        let mut buttons = ButtonsState::new();
-        loop {
-            match get_event(&mut buttons) {
-                Some(ButtonEvent::LeftButtonRelease) => {
-                    if cur_page > 0 {
-                        cur_page -= 1;
-                        draw(cur_page);
-                    }
-                }
-                Some(ButtonEvent::RightButtonRelease) => {
-                    if cur_page < page_count - 1 {
-                        cur_page += 1;
-                        draw(cur_page);
-                    }
+    /// `ask`, aborted once `should_abort`
+    /// holds, see `run_until`
+    pub fn ask_until(&self, should_abort: impl Fn() -> bool) -> Outcome {
+        run_until(&mut { *self }, should_abort).unwrap_or(Outcome::Aborted(AbortReason::App))
+    }
+}
+
+impl Widget for MessageValidator<'_> {
+    type Output = Outcome;
+
+    fn enter(&mut self) {
+        if let Some(keys) = &mut self.keys {
+            keys.reset();
+        }
+    }
+
+    fn draw(&self) {
+        let page_count = self.page_count();
+        let page = self.page;
+        let (pages_end, truncation) = (self.message.len() + self.pages.len(), self.truncation);
+        let mut bufs = [[0u8; LINE_BUF_LEN]; PAGE_MAX_LINES];
+        let mut provided = PageBuffer::new();
+        if let (Some((_, provider)), true) = (self.provider, (pages_end..page_count - 2).contains(&page)) {
+            provider(page - pages_end, &mut provided);
+        }
+        let provided_lines = provided.lines();
+        // The page is swapped in whole, leaving
+        // what it shares with the previous one
+        let mut indicator = PageText::<INDICATOR_LEN>::new();
+        let mut batch = Batch::new();
+        if page == page_count - 2 {
+            Page::new(self.confirm).icon(Icons::CheckBadge).queue(&mut batch, truncation, &mut bufs);
+        } else if page == page_count - 1 {
+            Page::new(self.cancel).icon(Icons::CrossBadge).queue(&mut batch, truncation, &mut bufs);
+        } else if page >= pages_end {
+            Page::new(&provided_lines[..provided.len()]).queue(&mut batch, truncation, &mut bufs);
+        } else if page >= self.message.len() {
+            self.pages[page - self.message.len()].queue(&mut batch, truncation, &mut bufs);
+        } else {
+            let line = fit(self.message[page], truncation, &mut bufs[0]);
+            let label = LabelLine::new().text(line);
+            batch.push(Bagl::LABELLINE(label.pos(0, screen::current().y(label.pos.1))));
+        }
+        let pager = Pager::new(page, page_count).wrap(self.wrap);
+        pager.queue_arrows(&mut batch);
+        if self.indicator {
+            pager.queue_indicator(&mut batch, &mut indicator);
+        }
+        batch.swap();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Outcome> {
+        let page_count = self.page_count();
+        let action = match input_of(&mut self.keys, event)? {
+            Input::Complete(action) => action,
+            Input::Pending(_) => return None,
+        };
+        match action {
+            action @ (Action::Previous | Action::Next) => {
+                if let Some(page) = Pager::new(self.page, page_count).wrap(self.wrap).turn(action) {
+                    self.page = page;
+                    self.draw();
                 }
-                Some(ButtonEvent::BothButtonsRelease) => {
-                    if cur_page == page_count - 2 {
-                        // Confirm
-                        return true;
-                    } else if cur_page == page_count - 1 {
-                        // Abort
-                        return false;
-                    }
+            }
+            Action::Reject => return Some(Outcome::Rejected),
+            Action::Confirm => {
+                if self.page == page_count - 2 {
+                    // Confirm
+                    return Some(Outcome::Approved);
+                } else if self.page == page_count - 1 {
+                    // Abort
+                    return Some(Outcome::Rejected);
                 }
-                _ => ()
             }
         }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        let page_count = self.page_count();
+        WidgetState::new("MessageValidator", self.page, page_count)
+            .actions(Pager::new(self.page, page_count).wrap(self.wrap).actions()
+                .with(Actions::CONFIRM.when(self.page + 2 >= page_count)))
     }
 }
 
+#[derive(Copy, Clone)]
 pub struct Menu<'a> {
-    panels: &'a[&'a str],
+    pub(crate) panels: &'a[&'a str],
+    /// Glyph drawn before each entry
+    pub(crate) icons: &'a [Option<Icon>],
+    /// Right-aligned value of each entry
+    pub(crate) values: &'a [&'a str],
+    pub(crate) truncation: Option<&'a dyn TruncationPolicy>,
+    /// Show a "Back" entry after the panels
+    pub(crate) back: bool,
+    /// Selected entry
+    pub(crate) index: usize,
+    /// Ticks both buttons have been held for
+    pub(crate) held: Option<u8>,
+    /// Fast scrolling while an arrow is held
+    pub(crate) repeat: AutoRepeat,
+    /// Go from the last entry to the first
+    pub(crate) wrap: bool,
+    /// Buttons giving each action, instead
+    /// of the installed mapper
+    pub(crate) keys: Option<Keys>,
+}
+
+/// How the user left a `Menu`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MenuResult {
+    /// Index of the selected entry
+    Selected(usize),
+    /// The "Back" entry was selected, both
+    /// buttons were held, or the input
+    /// backend rejected the menu
+    Back,
 }
 
+impl MenuResult {
+    pub const fn selected(self) -> Option<usize> {
+        match self {
+            MenuResult::Selected(index) => Some(index),
+            MenuResult::Back => None,
+        }
+    }
+}
+
+/// Ticker events (10 per second) both buttons
+/// must be held for to leave a `Menu`
+pub const MENU_LONG_PRESS_TICKS: u8 = 10;
+
+/// Width kept for the value of a menu entry
+pub const MENU_VALUE_WIDTH: u16 = 40;
+
 impl<'a> Menu<'a> {
-    pub fn new(panels: &'a[&'a str]) -> Self {
-        Menu { panels }
+    pub const fn new(panels: &'a[&'a str]) -> Self {
+        Menu { panels, icons: &[], values: &[], truncation: None, back: false, index: 0, held: None,
+            repeat: AutoRepeat::new(), wrap: false, keys: None }
     }
 
-    pub fn show(&self) -> usize {
-        let mut buttons = ButtonsState::new();
+    pub fn try_new(panels: &'a[&'a str]) -> Result<Self, Error> {
+        if panels.is_empty() {
+            return Err(Error::Empty)
+        }
+        panels.iter().try_for_each(|p| check_text(p))?;
+        Ok(Self::new(panels))
+    }
 
-        let bot = LabelLine::new().dims(128, 11).pos(0, 26);
-        let top = LabelLine::new().dims(128, 11).pos(0, 12);
+    /// Shorten long entries with `policy`
+    /// instead of the default one
+    pub const fn truncation(self, policy: &'a dyn TruncationPolicy) -> Self {
+        Menu { truncation: Some(policy), ..self }
+    }
 
-        bot.text(self.panels[1]).display();
-        top.text(self.panels[0]).bold().paint();
+    /// Take actions from the buttons as `keys`
+    /// maps them, e.g. `KeyMap::HOLD_TO_CONFIRM`
+    pub const fn keys(self, keys: KeyMap) -> Self {
+        Menu { keys: Some(Keys::new(keys)), ..self }
+    }
 
-        UP_ARROW.paint();
-        DOWN_ARROW.paint();
+    /// Add a translated "Back" entry
+    /// after the panels
+    pub const fn back(self) -> Self {
+        Menu { back: true, ..self }
+    }
 
-        let mut index = 0;
+    /// Go from the last entry to the first
+    /// one, and back, as the dashboard does
+    pub const fn wrap(self) -> Self {
+        Menu { wrap: true, ..self }
+    }
 
-        loop {
-            match get_event(&mut buttons) {
-                Some(ButtonEvent::LeftButtonPress) => {
-                    UP_S_ARROW.paint();
-                }
-                Some(ButtonEvent::RightButtonPress) => {
-                    DOWN_S_ARROW.paint();
-                }
-                Some(ButtonEvent::BothButtonsRelease) => {
-                    return index 
-                }
-                Some(x) => {
-                    match x {
-                        ButtonEvent::LeftButtonRelease => { 
-                           index = index.saturating_sub(1);
-                        },
-                        ButtonEvent::RightButtonRelease => { 
-                            if index < self.panels.len() - 1 {
-                                index += 1;
-                            }
-                        }
-                        _ => ()
-                    }
-                    UP_ARROW.display();
-                    DOWN_ARROW.paint();
-                    let a = (index / 2) * 2;
-                    let newtop = self.panels[a];
-                    let newbot = self.panels.get(a+1);
-
-                    if index & 1 == 0 {
-                        top.text(newtop).bold().paint();
-                        if let Some(b) = newbot {
-                            bot.text(b).paint();
-                        }
-                    } else {
-                        top.text(newtop).paint();
-                        if let Some(b) = newbot {
-                            bot.text(b).bold().paint();
-                        }
-                    }
-               } 
-                _ => ()
-            }
-        }
+    /// Number of entries, "Back" included
+    pub fn len(&self) -> usize {
+        self.panels.len() + self.back as usize
     }
-}
 
-/// A gadget that displays
-/// a short message in the 
-/// middle of the screen and
-/// waits for a button press
-pub struct SingleMessage<'a> {
-    message: &'a str,
-}
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 
-impl<'a> SingleMessage<'a> {
-    pub fn new(message: &'a str) -> Self {
-        SingleMessage { message }
+    /// Position among the entries
+    fn pager(&self) -> Pager {
+        Pager::new(self.index, self.len()).wrap(self.wrap)
     }
 
-    pub fn show(&self) {
-        LabelLine::new().text(self.message).display();
+    /// Label of entry `index`
+    pub(crate) fn entry(&self, index: usize) -> &'a str {
+        self.panels.get(index).copied().unwrap_or_else(|| tr(Text::Back))
     }
-    /// Display the message and wait
-    /// for any kind of button release 
-    pub fn show_and_wait(&self) {
-        let mut buttons = ButtonsState::new();
 
-        self.show();
+    /// Draw `icons[i]` before entry `i`,
+    /// if given. Entries with an icon or a
+    /// value are aligned to the left.
+    pub const fn icons(self, icons: &'a [Option<Icon>]) -> Self {
+        Menu { icons, ..self }
+    }
 
-        loop {
-            match get_event(&mut buttons) {
-                Some(ButtonEvent::LeftButtonRelease) | 
-                Some(ButtonEvent::RightButtonRelease) | 
-                Some(ButtonEvent::BothButtonsRelease) => return,
-                _ => ()
+    /// Show `values[i]` at the right of entry
+    /// `i`, e.g. the state of a setting.
+    /// Empty values are not shown.
+    pub const fn values(self, values: &'a [&'a str]) -> Self {
+        Menu { values, ..self }
+    }
+
+    /// Draw `label` showing entry `index`
+    fn draw_entry(&self, frame: &mut Frame, label: LabelLine, index: usize) {
+        let mut buf = [0u8; LINE_BUF_LEN];
+        let text = fit(self.entry(index), self.truncation, &mut buf);
+        let icon = self.icons.get(index).copied().flatten();
+        let value = self.values.get(index).copied().filter(|v| !v.is_empty());
+        if icon.is_none() && value.is_none() {
+            return frame.draw(Bagl::LABELLINE(label.text(text)))
+        }
+        if label.inverted {
+            // One bar across the icon and value
+            frame.draw(Bagl::RECT(label.highlight()));
+        }
+        let (_, y) = label.pos;
+        let x = match icon {
+            Some(icon) => {
+                frame.draw(Bagl::ICON(icon.pos(4, y - 9)));
+                16
             }
+            None => 4,
+        };
+        let reserved = if value.is_some() { MENU_VALUE_WIDTH } else { 0 };
+        frame.draw(Bagl::LABELLINE(label.text(text).pos(x, y)
+            .dims(124 - x as u16 - reserved, label.dims.1).align(Alignment::Left)));
+        if let Some(value) = value {
+            frame.draw(Bagl::LABELLINE(label.text(value).pos(124 - MENU_VALUE_WIDTH as i16, y)
+                .dims(MENU_VALUE_WIDTH, label.dims.1).align(Alignment::Right)));
         }
     }
+
+    /// Show the menu. An empty menu shows
+    /// `Text::NoEntries` until confirmed,
+    /// giving `MenuResult::Back`.
+    pub fn show(&self) -> MenuResult {
+        #[cfg(feature = "nbgl")]
+        return {
+            let mut entries = [""; crate::nbgl::MAX_ENTRIES];
+            let len = self.len().min(entries.len());
+            for (i, entry) in entries[..len].iter_mut().enumerate() {
+                *entry = self.entry(i);
+            }
+            crate::nbgl::menu(&entries[..len], Some(self.panels.len()).filter(|_| self.back))
+        };
+        #[cfg(not(feature = "nbgl"))]
+        run(&mut { *self })
+    }
 }
 
+impl Widget for Menu<'_> {
+    type Output = MenuResult;
 
+    fn enter(&mut self) {
+        if let Some(keys) = &mut self.keys {
+            keys.reset();
+        }
+    }
 
-/// A horizontal scroller that 
-/// splits any given message
-/// over several panes in chunks
-/// of CHAR_N characters.
-/// Press both buttons to exit.
-pub struct MessageScroller<'a> {
-    message: &'a str,
-}
+    fn draw(&self) {
+        let screen = screen::current();
+        let bot = LabelLine::new().dims(128, 11).pos(0, screen.y(26));
+        let top = LabelLine::new().dims(128, 11).pos(0, screen.y(12));
 
-impl<'a> MessageScroller<'a> {
-    pub fn new(message: &'a str) -> Self {
-        MessageScroller { message }
-    }
+        // Only the entries that changed are
+        // drawn again when moving in the menu
+        let mut frame = Frame::begin();
+        if self.is_empty() {
+            let empty = LabelLine::new().text(tr(Text::NoEntries)).pos(0, screen.y(20));
+            frame.draw(Bagl::LABELLINE(empty));
+        } else if breadcrumb::depth() > 0 {
+            // The header takes the place of the top entry
+            let header = breadcrumb::header();
+            if !header.is_empty() {
+                frame.draw(Bagl::LABELLINE(breadcrumb::label(header.as_str())));
+            }
+            self.draw_entry(&mut frame, bot.bold().invert(), self.index);
+        } else {
+            let a = (self.index / 2) * 2;
+            let has_bot = a + 1 < self.len();
 
-    pub fn event_loop(&self) {
-        let mut buttons = ButtonsState::new();
-        const CHAR_N: usize = 16;
-        let page_count = (self.message.len()-1) / CHAR_N + 1;
-        if page_count == 0 {
-            return
+            if self.index & 1 == 0 {
+                self.draw_entry(&mut frame, top.bold().invert(), a);
+                if has_bot {
+                    self.draw_entry(&mut frame, bot, a + 1);
+                }
+            } else {
+                self.draw_entry(&mut frame, top, a);
+                if has_bot {
+                    self.draw_entry(&mut frame, bot.bold().invert(), a + 1);
+                }
+            }
+        }
+        // Over the highlighted entry
+        if self.len() > 1 {
+            frame.draw(Bagl::ICON(screen.place(UP_ARROW)));
+            frame.draw(Bagl::ICON(screen.place(DOWN_ARROW)));
         }
-        let label = LabelLine::new(); 
-        let mut cur_page = 0;
+        frame.end();
+    }
 
-        // A closure to draw common elements of the screen
-        // cur_page passed as parameter to prevent borrowing
-        let draw = |page: usize| {
-            let start = page * CHAR_N;
-            let end = (start + CHAR_N).min(self.message.len());
-            let chunk = &self.message[start..end];
-            label.text(&chunk).display();
-            if page > 0 {
-                LEFT_ARROW.paint();
+    fn handle(&mut self, event: Event) -> Option<MenuResult> {
+        if event == Event::Ticker {
+            self.held = self.held.map(|ticks| ticks.saturating_add(1));
+        }
+        match self.repeat.input_with(event, &mut self.keys)? {
+            Input::Pending(Action::Previous) => {
+                screen::current().place(UP_S_ARROW).paint();
             }
-            if page + 1 < page_count {
-                RIGHT_ARROW.paint();
+            Input::Pending(Action::Next) => {
+                screen::current().place(DOWN_S_ARROW).paint();
             }
-        };
-
-        draw(cur_page);
-
-        loop {
-            match get_event(&mut buttons) {
-                Some(ButtonEvent::LeftButtonPress) => {
-                    LEFT_S_ARROW.paint();
-                }
-                Some(ButtonEvent::RightButtonPress) => {
-                    RIGHT_S_ARROW.paint();
+            Input::Pending(Action::Confirm) => {
+                self.held = Some(0);
+            }
+            Input::Complete(Action::Confirm) => {
+                let held = self.held.take().unwrap_or(0);
+                if held >= MENU_LONG_PRESS_TICKS || self.index >= self.panels.len() {
+                    return Some(MenuResult::Back)
                 }
-                Some(ButtonEvent::LeftButtonRelease) => {
-                    if cur_page > 0 {
-                        cur_page -= 1;
-                    }
-                    // We need to draw anyway to clear button press arrow
-                    draw(cur_page);
-                }    
-                Some(ButtonEvent::RightButtonRelease) => {
-                    if cur_page + 1 < page_count {
-                        cur_page += 1;
+                return Some(MenuResult::Selected(self.index))
+            }
+            Input::Complete(Action::Reject) => {
+                return Some(MenuResult::Back)
+            }
+            x => {
+                if let Input::Complete(action @ (Action::Previous | Action::Next)) = x {
+                    if let Some(index) = self.pager().turn(action) {
+                        self.index = index;
                     }
-                    // We need to draw anyway to clear button press arrow
-                    draw(cur_page);
                 }
-                Some(ButtonEvent::BothButtonsRelease) => break,
-                Some(_) | None => ()
+                self.draw();
             }
         }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("Menu", self.index, self.len())
+            .actions(self.pager().actions().with(Actions::CONFIRM))
     }
 }
 
-/// Horizontal scroller that
-/// displays a number of Bagls 
-/// over the same number of panes
-pub struct HScroller<'a> {
-    screens: &'a[Bagl<'a>],
+/// Entry of a `NestedMenu`
+#[derive(Copy, Clone, Debug)]
+pub enum MenuItem<'a> {
+    /// Ends the menu when selected
+    Action(&'a str),
+    /// Opens the given entries
+    SubMenu(&'static str, &'a [MenuItem<'a>]),
+    /// Returns to the parent menu,
+    /// or ends the menu at the top
+    Back,
 }
 
-impl<'a> HScroller<'a> {
-    pub fn new(screens: &'a [Bagl<'a>]) -> Self {
-        HScroller { screens }
+impl<'a> MenuItem<'a> {
+    pub fn label(&self) -> &'a str {
+        match *self {
+            MenuItem::Action(label) | MenuItem::SubMenu(label, _) => label,
+            MenuItem::Back => tr(Text::Back),
+        }
     }
+}
 
-    pub fn event_loop(&self) {
-        let mut buttons = ButtonsState::new();
-        let mut cur_idx = 0;
+/// Most entries per level of a `NestedMenu`
+pub const MENU_MAX_ITEMS: usize = 16;
+/// Deepest `NestedMenu`, as deep
+/// as the breadcrumb goes
+pub const MENU_MAX_DEPTH: usize = breadcrumb::MAX_DEPTH;
 
-        RIGHT_ARROW.display();
-        self.screens[cur_idx].paint();
+/// Entry selected in a `NestedMenu`: its index
+/// at each level, from the top
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MenuPath {
+    indices: [usize; MENU_MAX_DEPTH + 1],
+    len: usize,
+}
 
-        loop {
-            match get_event(&mut buttons) {
-                Some(ButtonEvent::LeftButtonPress) => {
-                    LEFT_S_ARROW.paint();
-                }
-                Some(ButtonEvent::RightButtonPress) => {
-                    RIGHT_S_ARROW.paint();
-                }
-                Some(ButtonEvent::LeftButtonRelease) => {
-                    if cur_idx > 0 {
-                        cur_idx -= 1; // Otherwise block onto first panel
-                    } 
-
-                    RIGHT_ARROW.display();
-                    if cur_idx != 0 {
-                        LEFT_ARROW.paint();
-                    }
-                    self.screens[cur_idx].paint();
-                }    
-                Some(ButtonEvent::RightButtonRelease) => {
-                    let last_item = self.screens.len() - 1;
-                    if cur_idx < last_item {
-                        cur_idx += 1; // Otherwise block onto last panel
-                    }
+impl MenuPath {
+    pub fn as_slice(&self) -> &[usize] {
+        &self.indices[..self.len]
+    }
+}
 
-                    LEFT_ARROW.display();
-                    if cur_idx != last_item {
-                        RIGHT_ARROW.paint();
+/// Menu of `MenuItem`s, entering submenus and
+/// going back up without leaving the widget.
+/// Each submenu shows its name in the
+/// breadcrumb header.
+///
+/// ```ignore
+/// const DISPLAY: &[MenuItem] = &[MenuItem::Action("Contrast"), MenuItem::Back];
+/// const SETTINGS: &[MenuItem] = &[MenuItem::SubMenu("Display", DISPLAY), MenuItem::Back];
+/// const HOME: &[MenuItem] = &[MenuItem::Action("Start"), MenuItem::SubMenu("Settings", SETTINGS)];
+/// match NestedMenu::new(HOME).show().as_ref().map(MenuPath::as_slice) {
+///     Some([1, 0, 0]) => set_contrast(),
+///     ..
+/// }
+/// ```
+#[derive(Copy, Clone)]
+pub struct NestedMenu<'a> {
+    pub(crate) root: &'a [MenuItem<'a>],
+    /// Submenus entered, by their index
+    /// in their parent
+    pub(crate) path: MenuPath,
+    /// Selected entry of the current level
+    pub(crate) index: usize,
+    /// Ticks both buttons have been held for
+    pub(crate) held: Option<u8>,
+    pub(crate) repeat: AutoRepeat,
+}
+
+impl<'a> NestedMenu<'a> {
+    pub const fn new(root: &'a [MenuItem<'a>]) -> Self {
+        let path = MenuPath { indices: [0; MENU_MAX_DEPTH + 1], len: 0 };
+        NestedMenu { root, path, index: 0, held: None, repeat: AutoRepeat::new() }
+    }
+
+    /// Entries of the current level
+    fn items(&self) -> &'a [MenuItem<'a>] {
+        let mut items = self.root;
+        for &i in self.path.as_slice() {
+            if let Some(MenuItem::SubMenu(_, sub)) = items.get(i) {
+                items = sub;
+            }
+        }
+        items
+    }
+
+    /// `Menu` showing the current level
+    fn with_menu<R>(&self, f: impl FnOnce(&mut Menu) -> R) -> R {
+        let items = self.items();
+        let items = &items[..items.len().min(MENU_MAX_ITEMS)];
+        let mut labels = [""; MENU_MAX_ITEMS];
+        for (label, item) in labels.iter_mut().zip(items) {
+            *label = item.label();
+        }
+        let mut menu = Menu { index: self.index, held: self.held, repeat: self.repeat,
+            ..Menu::new(&labels[..items.len()]) };
+        f(&mut menu)
+    }
+
+    /// Leave every submenu, popping their names
+    fn exit(&mut self) {
+        for _ in 0..self.path.len {
+            breadcrumb::pop();
+        }
+        self.path.len = 0;
+    }
+
+    /// Show the menu, returning the selected
+    /// action, or None if "Back" was selected
+    /// at the top
+    pub fn show(&self) -> Option<MenuPath> {
+        run(&mut { *self })
+    }
+}
+
+impl Widget for NestedMenu<'_> {
+    type Output = Option<MenuPath>;
+
+    fn draw(&self) {
+        self.with_menu(|m| m.draw());
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Option<MenuPath>> {
+        let (result, index, held, repeat) = self.with_menu(|m| (m.handle(event), m.index, m.held, m.repeat));
+        self.index = index;
+        self.held = held;
+        self.repeat = repeat;
+        let item = match result? {
+            MenuResult::Selected(selected) => self.items().get(selected)?,
+            MenuResult::Back => &MenuItem::Back,
+        };
+        match item {
+            MenuItem::Action(_) => {
+                let mut path = self.path;
+                path.indices[path.len] = self.index;
+                path.len += 1;
+                self.exit();
+                return Some(Some(path))
+            }
+            MenuItem::SubMenu(name, items) => {
+                if items.is_empty() || self.path.len == MENU_MAX_DEPTH || !breadcrumb::push(name) {
+                    return None
+                }
+                self.path.indices[self.path.len] = self.index;
+                self.path.len += 1;
+                self.index = 0;
+            }
+            MenuItem::Back => {
+                if self.path.len == 0 {
+                    return Some(None)
+                }
+                breadcrumb::pop();
+                self.path.len -= 1;
+                self.index = self.path.indices[self.path.len];
+            }
+        }
+        self.draw();
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        let state = self.with_menu(|m| m.state());
+        WidgetState { widget: "NestedMenu", ..state }
+    }
+}
+
+/// A gadget that displays
+/// a short message in the 
+/// middle of the screen and
+/// waits for a button press
+#[derive(Copy, Clone)]
+pub struct SingleMessage<'a> {
+    pub(crate) message: &'a str,
+}
+
+impl<'a> SingleMessage<'a> {
+    pub const fn new(message: &'a str) -> Self {
+        SingleMessage { message }
+    }
+
+    pub fn try_new(message: &'a str) -> Result<Self, Error> {
+        check_text(message)?;
+        Ok(Self::new(message))
+    }
+
+    pub fn show(&self) {
+        LabelLine::new().text(self.message).display();
+    }
+    /// Display the message and wait
+    /// for any kind of button release 
+    pub fn show_and_wait(&self) {
+        run(&mut { *self })
+    }
+
+    /// `show_and_wait`, giving up once
+    /// `should_abort` holds. Returns whether
+    /// the user dismissed the message.
+    pub fn show_until(&self, should_abort: impl Fn() -> bool) -> bool {
+        run_until(&mut { *self }, should_abort).is_some()
+    }
+
+    /// `show_and_wait`, giving up after `ticks`
+    /// ticker events. Returns whether the user
+    /// dismissed the message.
+    pub fn show_for(&self, ticks: u32) -> bool {
+        run_for(&mut { *self }, ticks).is_some()
+    }
+}
+
+impl Widget for SingleMessage<'_> {
+    type Output = ();
+
+    fn draw(&self) {
+        self.show();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<()> {
+        event.action().map(|_| ())
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("SingleMessage", 0, 1).actions(Actions::ALL)
+    }
+
+    fn on_idle(&mut self) -> Option<()> {
+        Some(())
+    }
+}
+
+/// Ticks a `ScrollingLabel` waits at
+/// each end of its text
+pub const SCROLL_PAUSE_TICKS: u8 = 10;
+/// Ticks between two moves of a `ScrollingLabel`
+pub const SCROLL_STEP_TICKS: u8 = 2;
+
+/// A value too long for one line, scrolled
+/// one character at a time on ticker events,
+/// pausing at both ends, under an optional
+/// bold title. Any button release exits.
+#[derive(Copy, Clone)]
+pub struct ScrollingLabel<'a> {
+    pub(crate) title: Option<&'a str>,
+    pub(crate) text: &'a str,
+    /// First character shown
+    pub(crate) offset: usize,
+    /// Ticks until the next move
+    pub(crate) wait: u8,
+}
+
+impl<'a> ScrollingLabel<'a> {
+    pub const fn new(text: &'a str) -> Self {
+        ScrollingLabel { title: None, text, offset: 0, wait: SCROLL_PAUSE_TICKS }
+    }
+
+    pub const fn title(self, title: &'a str) -> Self {
+        ScrollingLabel { title: Some(title), ..self }
+    }
+
+    /// Last value of `offset`, 0 if the text fits
+    fn max_offset(&self) -> usize {
+        self.text.chars().count().saturating_sub(LINE_MAX_CHARS)
+    }
+
+    /// Part of the text currently shown
+    fn window(&self) -> &'a str {
+        let mut bounds = self.text.char_indices().map(|(i, _)| i).chain(Some(self.text.len()));
+        let start = bounds.nth(self.offset).unwrap_or(self.text.len());
+        let end = bounds.nth(LINE_MAX_CHARS - 1).unwrap_or(self.text.len());
+        &self.text[start..end]
+    }
+
+    /// Advance the animation by one tick.
+    /// Returns whether the text moved.
+    pub fn tick(&mut self) -> bool {
+        let max = self.max_offset();
+        if max == 0 {
+            return false
+        }
+        self.wait = self.wait.saturating_sub(1);
+        if self.wait > 0 {
+            return false
+        }
+        if self.offset >= max {
+            self.offset = 0;
+            self.wait = SCROLL_PAUSE_TICKS;
+        } else {
+            self.offset += 1;
+            self.wait = if self.offset == max { SCROLL_PAUSE_TICKS } else { SCROLL_STEP_TICKS };
+        }
+        true
+    }
+
+    pub fn show_and_wait(&self) {
+        run(&mut { *self })
+    }
+}
+
+impl Widget for ScrollingLabel<'_> {
+    type Output = ();
+
+    fn draw(&self) {
+        screen::clear();
+        let screen = screen::current();
+        match self.title {
+            Some(title) => {
+                LabelLine::new().text(title).pos(0, screen.y(12)).bold().paint();
+                LabelLine::new().text(self.window()).pos(0, screen.y(26)).paint();
+            }
+            None => {
+                let label = LabelLine::new().text(self.window());
+                label.pos(0, screen.y(label.pos.1)).paint();
+            }
+        }
+    }
+
+    fn handle(&mut self, event: Event) -> Option<()> {
+        if event == Event::Ticker {
+            if self.tick() {
+                self.draw();
+            }
+            return None
+        }
+        event.action().map(|_| ())
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("ScrollingLabel", 0, 1).actions(Actions::ALL)
+    }
+
+    fn on_idle(&mut self) -> Option<()> {
+        Some(())
+    }
+}
+
+
+/// Longest amount drawn with `AMOUNT_FONT`,
+/// longer ones fall back to the regular font
+pub const AMOUNT_MAX_LARGE_CHARS: usize = 12;
+pub const AMOUNT_FONT: Font = Font::OpenSansRegular13_18px;
+
+/// Displays an amount on its own page, in
+/// a large font under a small title, so the
+/// most important value of a review stands out
+#[derive(Copy, Clone)]
+pub struct AmountPage<'a> {
+    pub(crate) title: &'a str,
+    pub(crate) amount: &'a str,
+}
+
+impl<'a> AmountPage<'a> {
+    pub const fn new(title: &'a str, amount: &'a str) -> Self {
+        AmountPage { title, amount }
+    }
+
+    pub fn try_new(title: &'a str, amount: &'a str) -> Result<Self, Error> {
+        check_text(title)?;
+        check_text(amount)?;
+        Ok(Self::new(title, amount))
+    }
+
+    pub fn show(&self) {
+        let screen = screen::current();
+        LabelLine::new().dims(128, 11).pos(0, screen.y(10)).text(self.title).display();
+        let amount = LabelLine::new().text(self.amount);
+        if self.amount.chars().count() <= AMOUNT_MAX_LARGE_CHARS {
+            amount.font(AMOUNT_FONT).dims(128, 18).pos(0, screen.y(29)).paint();
+        } else {
+            amount.dims(128, 11).pos(0, screen.y(26)).bold().paint();
+        }
+    }
+
+    /// Display the amount and wait
+    /// for any kind of button release
+    pub fn show_and_wait(&self) {
+        run(&mut { *self })
+    }
+}
+
+impl Widget for AmountPage<'_> {
+    type Output = ();
+
+    fn draw(&self) {
+        self.show();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<()> {
+        event.action().map(|_| ())
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("AmountPage", 0, 1).actions(Actions::ALL)
+    }
+
+    fn on_idle(&mut self) -> Option<()> {
+        Some(())
+    }
+}
+
+/// Warning screen: an icon and two lines,
+/// optionally followed by a page the user must
+/// confirm to acknowledge it
+#[derive(Copy, Clone)]
+pub struct Warning<'a> {
+    pub(crate) icon: Icon,
+    pub(crate) lines: [&'a str; 2],
+    pub(crate) ack: bool,
+    /// Page currently shown, the
+    /// acknowledgement being page 1
+    pub(crate) page: usize,
+}
+
+impl<'a> Warning<'a> {
+    pub const fn new(icon: Icons, line1: &'a str, line2: &'a str) -> Self {
+        Warning { icon: Icon::new(icon).pos(16, 12), lines: [line1, line2], ack: false, page: 0 }
+    }
+
+    /// Only dismiss the warning once the user
+    /// confirmed the acknowledgement page
+    pub const fn require_ack(self) -> Self {
+        Warning { ack: true, ..self }
+    }
+
+    /// The warning as a step of a flow
+    pub const fn layout(&self) -> crate::flow::Layout<'a> {
+        crate::flow::Layout::Pnn(self.icon, self.lines[0], self.lines[1])
+    }
+
+    /// Show the warning until dismissed. Returns
+    /// `Approved` once acknowledged, `Rejected`
+    /// if the user rejects it.
+    pub fn show(&self) -> Outcome {
+        run(&mut { *self })
+    }
+}
+
+impl Widget for Warning<'_> {
+    type Output = Outcome;
+
+    fn draw(&self) {
+        BLANK.paint();
+        match self.page {
+            0 => Page { icon: Some(self.icon), lines: &self.lines }.paint(),
+            _ => Page::new(&[tr(Text::Understood)]).icon(Icons::CheckBadge).paint(),
+        }
+        if self.ack && self.page == 0 {
+            RIGHT_ARROW.paint();
+        }
+        if self.page == 1 {
+            LEFT_ARROW.paint();
+        }
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Outcome> {
+        let action = event.action()?;
+        if !self.ack {
+            return Some(Outcome::Approved)
+        }
+        match action {
+            Action::Next if self.page == 0 => {
+                self.page = 1;
+                self.draw();
+            }
+            Action::Previous if self.page == 1 => {
+                self.page = 0;
+                self.draw();
+            }
+            Action::Confirm if self.page == 1 => return Some(Outcome::Approved),
+            Action::Reject => return Some(Outcome::Rejected),
+            _ => ()
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        if !self.ack {
+            return WidgetState::new("Warning", 0, 1).actions(Actions::ALL)
+        }
+        WidgetState::new("Warning", self.page, 2)
+            .actions(Actions::PREVIOUS.when(self.page == 1)
+                .with(Actions::NEXT.when(self.page == 0))
+                .with(Actions::CONFIRM.when(self.page == 1)))
+    }
+}
+
+
+/// Describes how a message is split
+/// into pages by the scrolling widgets
+#[derive(Copy, Clone)]
+pub struct PageLayout {
+    /// Maximum number of characters shown on a page
+    pub chars_per_page: usize,
+}
+
+impl PageLayout {
+    pub const fn new(chars_per_page: usize) -> Self {
+        PageLayout { chars_per_page }
+    }
+
+    /// Number of pages spanned by a message
+    /// of `len` characters. Usable in const contexts.
+    pub const fn page_count(&self, len: usize) -> usize {
+        if self.chars_per_page == 0 {
+            return 0
+        }
+        len.div_ceil(self.chars_per_page)
+    }
+}
+
+/// Layout used by `MessageScroller`
+pub const SCROLLER_LAYOUT: PageLayout = PageLayout::new(16);
+
+/// Number of characters of `s`, which pages
+/// are measured in. Usable in const contexts.
+pub const fn char_count(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let (mut i, mut count) = (0, 0);
+    while i < bytes.len() {
+        // Skip UTF-8 continuation bytes
+        if bytes[i] & 0xc0 != 0x80 {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+/// Number of pages a static message spans,
+/// computed at compile time. Uses
+/// `SCROLLER_LAYOUT` unless a layout is given.
+#[macro_export]
+macro_rules! page_count {
+    ($msg:expr) => {
+        $crate::ui::SCROLLER_LAYOUT.page_count($crate::ui::char_count($msg))
+    };
+    ($msg:expr, $layout:expr) => {
+        $layout.page_count($crate::ui::char_count($msg))
+    };
+}
+
+/// Fails the build if any of the static
+/// messages spans more than `$max` pages
+#[macro_export]
+macro_rules! assert_max_pages {
+    ($max:expr; $($msg:expr),+ $(,)?) => {
+        $(const _: () = assert!($crate::page_count!($msg) <= $max,
+            "static message spans too many pages");)+
+    };
+}
+
+/// Number of pages needed to show `message`
+/// with `layout`. Lets apps check that
+/// host-provided data fits before starting a flow.
+pub fn pages_for(message: &str, layout: &PageLayout) -> usize {
+    pages(message, layout).count()
+}
+
+/// Iterate over the exact substrings that will
+/// be shown on each page, cut between characters
+pub fn pages<'a>(message: &'a str, layout: &PageLayout) -> Pages<'a> {
+    Pages { rest: message, chunk: layout.chars_per_page, lines: 0 }
+}
+
+/// Like `pages`, but wrapping `message` between
+/// words into `lines` lines per page, each of
+/// at most `layout.chars_per_page` characters
+pub fn wrapped_pages<'a>(message: &'a str, layout: &PageLayout, lines: usize) -> Pages<'a> {
+    Pages { rest: message, chunk: layout.chars_per_page, lines }
+}
+
+/// Iterator returned by `pages`
+#[derive(Clone)]
+pub struct Pages<'a> {
+    rest: &'a str,
+    chunk: usize,
+    /// Wrapped lines per page, or 0
+    /// to cut every `chunk` characters
+    lines: usize,
+}
+
+impl<'a> Iterator for Pages<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.rest.is_empty() || self.chunk == 0 {
+            return None
+        }
+        if self.lines > 0 {
+            let rest = self.rest.trim_start_matches(' ');
+            let mut lines = wrap(rest, self.chunk);
+            if lines.by_ref().take(self.lines).count() == 0 {
+                self.rest = "";
+                return None
+            }
+            self.rest = lines.rest;
+            return Some(rest[..rest.len() - lines.rest.len()].trim_end())
+        }
+        let end = self.rest.char_indices().nth(self.chunk).map_or(self.rest.len(), |(i, _)| i);
+        let (page, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(page)
+    }
+}
+
+/// Iterate over the lines of `text` wrapped to
+/// `width` characters: lines break at newlines
+/// and between words, unless a word is longer
+/// than a line
+pub fn wrap(text: &str, width: usize) -> Lines<'_> {
+    Lines { rest: text, width }
+}
+
+/// Iterator returned by `wrap`
+#[derive(Clone)]
+pub struct Lines<'a> {
+    rest: &'a str,
+    width: usize,
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let rest = self.rest.trim_start_matches(' ');
+        if rest.is_empty() || self.width == 0 {
+            self.rest = "";
+            return None
+        }
+        let end = rest.char_indices().nth(self.width).map_or(rest.len(), |(i, _)| i);
+        let (line, next) = if let Some(newline) = rest[..end].find('\n') {
+            (&rest[..newline], &rest[newline + 1..])
+        } else if rest[end..].starts_with('\n') {
+            (&rest[..end], &rest[end + 1..])
+        } else if end == rest.len() || rest[end..].starts_with(' ') {
+            (&rest[..end], &rest[end..])
+        } else {
+            match rest[..end].rfind(' ') {
+                Some(space) if space > 0 => (&rest[..space], &rest[space..]),
+                _ => (&rest[..end], &rest[end..]),
+            }
+        };
+        self.rest = next;
+        Some(line.trim_end())
+    }
+}
+
+/// Most lines per page of a
+/// word-wrapping `MessageScroller`
+pub const SCROLLER_MAX_LINES: usize = 3;
+
+/// A horizontal scroller that 
+/// splits any given message
+/// over several panes in chunks
+/// of `SCROLLER_LAYOUT.chars_per_page` characters
+/// (see `chunk`), or in pages of word-wrapped
+/// lines (see `wrap`), under an optional title.
+/// Press both buttons to exit, or to go to the
+/// next page with `confirm_advances`.
+#[derive(Copy, Clone)]
+pub struct MessageScroller<'a> {
+    pub(crate) message: &'a str,
+    pub(crate) layout: PageLayout,
+    /// Bold line above the message, taking
+    /// one of its lines when wrapped
+    pub(crate) title: Option<&'a str>,
+    /// Both buttons go to the next page
+    /// rather than exit, but on the last one
+    pub(crate) advance: bool,
+    pub(crate) arrows: bool,
+    /// Wrapped lines per page, or 0 to cut
+    /// the message in single-line chunks
+    pub(crate) lines: usize,
+    /// Page currently shown
+    pub(crate) page: usize,
+    pub(crate) repeat: AutoRepeat,
+    /// Pages formatted on demand instead
+    /// of cut from `message`
+    pub(crate) provider: Option<(usize, PageProvider<'a>)>,
+    /// Show the "k/n" page indicator
+    pub(crate) indicator: bool,
+    /// Buttons giving each action, instead
+    /// of the installed mapper
+    pub(crate) keys: Option<Keys>,
+}
+
+impl<'a> MessageScroller<'a> {
+    pub const fn new(message: &'a str) -> Self {
+        MessageScroller { message, layout: SCROLLER_LAYOUT, title: None, advance: false, arrows: true,
+            lines: 0, page: 0, repeat: AutoRepeat::new(), provider: None, indicator: false, keys: None }
+    }
+
+    /// Cut the message every `chars` characters
+    /// instead of `SCROLLER_LAYOUT`'s, at least one
+    pub const fn chunk(self, chars: usize) -> Self {
+        let chars = if chars == 0 { 1 } else { chars };
+        MessageScroller { layout: PageLayout::new(chars), ..self }
+    }
+
+    /// Show `title` in bold above the message.
+    /// Pages of a `provided` scroller have no title.
+    pub const fn title(self, title: &'a str) -> Self {
+        MessageScroller { title: Some(title), ..self }
+    }
+
+    /// Go to the next page on both buttons,
+    /// exiting only from the last page
+    pub const fn confirm_advances(self) -> Self {
+        MessageScroller { advance: true, ..self }
+    }
+
+    /// Do not draw the left and right arrows
+    pub const fn hide_arrows(self) -> Self {
+        MessageScroller { arrows: false, ..self }
+    }
+
+    /// Take actions from the buttons as `keys`
+    /// maps them, e.g. `KeyMap::HOLD_TO_CONFIRM`
+    pub const fn keys(self, keys: KeyMap) -> Self {
+        MessageScroller { keys: Some(Keys::new(keys)), ..self }
+    }
+
+    /// Scroll through `count` pages, each
+    /// formatted by `provider` only when shown
+    pub const fn provided(count: usize, provider: PageProvider<'a>) -> Self {
+        MessageScroller { provider: Some((count, provider)), ..Self::new("") }
+    }
+
+    /// Wrap the message between words, showing
+    /// `lines` lines per page: 2, or up to
+    /// `SCROLLER_MAX_LINES` on taller screens
+    pub const fn wrap(self, lines: usize) -> Self {
+        let lines = if lines > SCROLLER_MAX_LINES { SCROLLER_MAX_LINES } else { lines };
+        MessageScroller { lines, ..self }
+    }
+
+    /// Show the number of the page in
+    /// a corner, see `Pager::queue_indicator`
+    pub const fn page_indicator(self) -> Self {
+        MessageScroller { indicator: true, ..self }
+    }
+
+    pub fn try_new(message: &'a str) -> Result<Self, Error> {
+        let scroller = Self::new(message);
+        match scroller.page_count() {
+            0 => Err(Error::Empty),
+            _ => Ok(scroller),
+        }
+    }
+
+    /// Number of pages this scroller will show
+    pub fn page_count(&self) -> usize {
+        match self.provider {
+            Some((count, _)) => count,
+            None => self.pages().count(),
+        }
+    }
+
+    /// Iterate over the pages this scroller will show
+    pub fn pages(&self) -> Pages<'a> {
+        wrapped_pages(self.message, &self.layout, self.page_lines())
+    }
+
+    /// Wrapped lines per page, leaving
+    /// room for the title
+    pub(crate) fn page_lines(&self) -> usize {
+        match self.title {
+            Some(_) if self.lines > 0 => self.lines.min(screen::current().lines - 1).max(1),
+            _ => self.lines,
+        }
+    }
+
+    pub fn event_loop(&self) {
+        if self.page_count() == 0 {
+            return
+        }
+        run(&mut { *self })
+    }
+}
+
+impl Widget for MessageScroller<'_> {
+    type Output = ();
+
+    fn enter(&mut self) {
+        if let Some(keys) = &mut self.keys {
+            keys.reset();
+        }
+    }
+
+    fn draw(&self) {
+        let screen = screen::current();
+        let chunk = self.pages().nth(self.page).unwrap_or("");
+        let mut bufs = [[0u8; LINE_BUF_LEN]; PAGE_MAX_LINES];
+        let mut provided = PageBuffer::new();
+        if let Some((_, provider)) = self.provider {
+            provider(self.page, &mut provided);
+        }
+        let provided_lines = provided.lines();
+        let mut indicator = PageText::<INDICATOR_LEN>::new();
+        let mut batch = Batch::new();
+        let title = self.title.filter(|_| self.provider.is_none());
+        if let Some(title) = title {
+            batch.push(Bagl::LABELLINE(LabelLine::new().text(title).pos(0, screen.y(12)).bold()));
+        }
+        if self.provider.is_some() {
+            Page::new(&provided_lines[..provided.len()]).queue(&mut batch, None, &mut bufs);
+        } else if self.lines == 0 {
+            let y = if title.is_some() { BOTTOM_LINE_Y } else { 20 };
+            batch.push(Bagl::LABELLINE(LabelLine::new().text(chunk).pos(0, screen.y(y))));
+        } else {
+            let style = style::get();
+            let count = self.page_lines();
+            let lines = wrap(chunk, self.layout.chars_per_page).take(count);
+            let top = screen.y(BOTTOM_LINE_Y) - (count as i16 - 1) * style.line_spacing;
+            for (i, line) in lines.enumerate() {
+                batch.push(Bagl::LABELLINE(LabelLine::new().text(line).pos(0, top + i as i16 * style.line_spacing)));
+            }
+        }
+        let pager = Pager::new(self.page, self.page_count());
+        if self.arrows {
+            pager.queue_arrows(&mut batch);
+        }
+        if self.indicator {
+            pager.queue_indicator(&mut batch, &mut indicator);
+        }
+        batch.swap();
+    }
+
+    fn handle(&mut self, event: Event) -> Option<()> {
+        match self.repeat.input_with(event, &mut self.keys)? {
+            Input::Pending(Action::Previous) if self.arrows => {
+                screen::current().place(LEFT_S_ARROW).paint();
+            }
+            Input::Pending(Action::Next) if self.arrows => {
+                screen::current().place(RIGHT_S_ARROW).paint();
+            }
+            Input::Complete(action @ (Action::Previous | Action::Next)) => {
+                if let Some(page) = Pager::new(self.page, self.page_count()).turn(action) {
+                    self.page = page;
+                }
+                // We need to draw anyway to clear button press arrow
+                self.draw();
+            }
+            Input::Complete(Action::Confirm) if self.advance && self.page + 1 < self.page_count() => {
+                self.page += 1;
+                self.draw();
+            }
+            Input::Complete(Action::Confirm | Action::Reject) => return Some(()),
+            _ => ()
+        }
+        None
+    }
+
+    fn state(&self) -> WidgetState {
+        let page_count = self.page_count();
+        WidgetState::new("MessageScroller", self.page, page_count)
+            .actions(Pager::new(self.page, page_count).actions().with(Actions::CONFIRM))
+    }
+
+    fn on_idle(&mut self) -> Option<()> {
+        Some(())
+    }
+}
+/// Horizontal scroller that
+/// displays a number of Bagls 
+/// over the same number of panes,
+/// or panes made of several Bagls
+/// each (see `composite`). Both buttons exit,
+/// or pick the current pane with `select`.
+#[derive(Copy, Clone)]
+pub struct HScroller<'a> {
+    pub(crate) screens: &'a[Bagl<'a>],
+    /// Panes of several Bagls, drawn
+    /// together, after `screens`
+    pub(crate) composite: &'a [&'a [Bagl<'a>]],
+    /// Pages shown after `composite`
+    pub(crate) pages: &'a [Page<'a>],
+    pub(crate) truncation: Option<&'a dyn TruncationPolicy>,
+    /// Screen currently shown
+    pub(crate) index: usize,
+    /// Go from the last pane to the first
+    /// one and back
+    pub(crate) wrap: bool,
+    /// Buttons giving each action, instead
+    /// of the installed mapper
+    pub(crate) keys: Option<Keys>,
+}
+
+impl<'a> HScroller<'a> {
+    pub const fn new(screens: &'a [Bagl<'a>]) -> Self {
+        HScroller { screens, composite: &[], pages: &[], truncation: None, index: 0, wrap: false,
+            keys: None }
+    }
+
+    /// One pane per slice of Bagls, e.g.
+    /// an icon and two labels
+    pub const fn composite(screens: &'a [&'a [Bagl<'a>]]) -> Self {
+        HScroller { composite: screens, ..Self::new(&[]) }
+    }
+
+    pub fn try_new(screens: &'a [Bagl<'a>]) -> Result<Self, Error> {
+        if screens.is_empty() {
+            return Err(Error::Empty)
+        }
+        Ok(Self::new(screens))
+    }
+
+    pub fn try_composite(screens: &'a [&'a [Bagl<'a>]]) -> Result<Self, Error> {
+        if screens.iter().all(|s| s.is_empty()) {
+            return Err(Error::Empty)
+        }
+        Ok(Self::composite(screens))
+    }
+
+    /// Show `pages` after the screens
+    pub const fn pages(self, pages: &'a [Page<'a>]) -> Self {
+        HScroller { pages, ..self }
+    }
+
+    /// Number of panes: screens, composite
+    /// ones, then pages
+    pub const fn len(&self) -> usize {
+        self.screens.len() + self.composite.len() + self.pages.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Shorten long labels with `policy`
+    /// instead of the default one
+    pub const fn truncation(self, policy: &'a dyn TruncationPolicy) -> Self {
+        HScroller { truncation: Some(policy), ..self }
+    }
+
+    /// Go from the last pane to the
+    /// first one and back
+    pub const fn wrap(self) -> Self {
+        HScroller { wrap: true, ..self }
+    }
+
+    /// Take actions from the buttons as `keys`
+    /// maps them, e.g. `KeyMap::HOLD_TO_CONFIRM`
+    pub const fn keys(self, keys: KeyMap) -> Self {
+        HScroller { keys: Some(Keys::new(keys)), ..self }
+    }
+
+    /// `handle`, for input already
+    /// mapped from the event
+    fn handle_input(&mut self, input: Input) -> Option<()> {
+        match input {
+            Input::Pending(Action::Previous) => {
+                LEFT_S_ARROW.paint();
+            }
+            Input::Pending(Action::Next) => {
+                RIGHT_S_ARROW.paint();
+            }
+            Input::Complete(action @ (Action::Previous | Action::Next)) => {
+                // Otherwise block onto the first or last panel
+                if let Some(index) = self.pager().turn(action) {
+                    self.index = index;
+                }
+                self.draw();
+            }
+            Input::Complete(Action::Confirm | Action::Reject) => return Some(()),
+            _ => ()
+        }
+        None
+    }
+
+    fn pager(&self) -> Pager {
+        Pager::new(self.index, self.len()).wrap(self.wrap)
+    }
+
+    fn paint_screen(&self, index: usize) {
+        if let Some(screen) = self.screens.get(index) {
+            return self.paint_bagl(*screen)
+        }
+        let index = index - self.screens.len();
+        if let Some(screen) = self.composite.get(index) {
+            return screen.iter().for_each(|bagl| self.paint_bagl(*bagl))
+        }
+        match self.pages.get(index - self.composite.len()) {
+            Some(page) => page.paint_with(self.truncation),
+            // Nothing to browse
+            None => LabelLine::new().text(tr(Text::NoEntries)).pos(0, screen::current().y(20)).paint(),
+        }
+    }
+
+    fn paint_bagl(&self, bagl: Bagl) {
+        match bagl {
+            Bagl::LABELLINE(label @ LabelLine { text: Some(text), .. }) => {
+                let mut buf = [0u8; LINE_BUF_LEN];
+                label.text(fit(text, self.truncation, &mut buf)).paint();
+            }
+            bagl => bagl.paint(),
+        }
+    }
+
+    pub fn event_loop(&self) {
+        run(&mut { *self })
+    }
+
+    /// Browse the panes until both buttons pick
+    /// one, like a `Menu`. Never times out. An
+    /// empty scroller shows `Text::NoEntries`
+    /// until confirmed, giving `MenuResult::Back`.
+    pub fn select(&self) -> MenuResult {
+        run(&mut Selecting(*self))
+    }
+}
+
+impl Widget for HScroller<'_> {
+    type Output = ();
+
+    fn enter(&mut self) {
+        if let Some(keys) = &mut self.keys {
+            keys.reset();
+        }
+    }
+
+    fn draw(&self) {
+        BLANK.paint();
+        self.pager().paint_arrows();
+        self.paint_screen(self.index);
+    }
+
+    fn handle(&mut self, event: Event) -> Option<()> {
+        let input = input_of(&mut self.keys, event)?;
+        self.handle_input(input)
+    }
+
+    fn state(&self) -> WidgetState {
+        WidgetState::new("HScroller", self.index, self.len())
+            .actions(self.pager().actions().with(Actions::CONFIRM))
+    }
+
+    fn on_idle(&mut self) -> Option<()> {
+        Some(())
+    }
+}
+
+/// `HScroller` giving the pane picked
+/// with both buttons
+struct Selecting<'a>(HScroller<'a>);
+
+impl Widget for Selecting<'_> {
+    type Output = MenuResult;
+
+    fn enter(&mut self) {
+        self.0.enter()
+    }
+
+    fn draw(&self) {
+        self.0.draw()
+    }
+
+    fn handle(&mut self, event: Event) -> Option<MenuResult> {
+        let input = input_of(&mut self.0.keys, event)?;
+        self.0.handle_input(input)?;
+        let confirmed = input == Input::Complete(Action::Confirm) && !self.0.is_empty();
+        match confirmed {
+            true => Some(MenuResult::Selected(self.0.index)),
+            false => Some(MenuResult::Back),
+        }
+    }
+
+    fn state(&self) -> WidgetState {
+        self.0.state()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Multi-byte characters straddling the
+    /// 16-character page boundary
+    const MESSAGES: [&str; 4] = [
+        "ééééééééééééééééééé",
+        "abcdefghijklmno…pqrstu…",
+        "0123456789abcde😀😀f",
+        "Attendez… le café 😀 est prêt, éé ééé fin…",
+    ];
+
+    /// Offset of `page` in `message`, checking
+    /// it is a slice cut between characters
+    fn offset_in(message: &str, page: &str) -> usize {
+        let start = page.as_ptr() as usize - message.as_ptr() as usize;
+        assert!(start + page.len() <= message.len());
+        assert!(message.is_char_boundary(start) && message.is_char_boundary(start + page.len()));
+        start
+    }
+
+    #[test]
+    fn pages_cut_between_chars() {
+        for message in MESSAGES.iter() {
+            let mut end = 0;
+            for (i, page) in pages(message, &SCROLLER_LAYOUT).enumerate() {
+                assert_eq!(offset_in(message, page), end);
+                end += page.len();
+                // Only the last page is short
+                if end < message.len() {
+                    assert_eq!(char_count(page), 16, "page {} of {:?}", i, message);
+                } else {
+                    assert!(char_count(page) <= 16);
+                }
+            }
+            assert_eq!(end, message.len());
+            assert_eq!(pages_for(message, &SCROLLER_LAYOUT), page_count!(message));
+        }
+    }
+
+    #[test]
+    fn wrapped_pages_cut_between_chars() {
+        for message in MESSAGES.iter() {
+            for lines in 1..=SCROLLER_MAX_LINES {
+                let mut end = 0;
+                for page in wrapped_pages(message, &SCROLLER_LAYOUT, lines) {
+                    let start = offset_in(message, page);
+                    assert!(start >= end && !page.is_empty());
+                    end = start + page.len();
+                    assert!(wrap(page, 16).count() <= lines);
+                    for line in wrap(page, 16) {
+                        offset_in(message, line);
+                        assert!(char_count(line) <= 16);
                     }
-                    self.screens[cur_idx].paint();
                 }
-                Some(ButtonEvent::BothButtonsRelease) => {
-                    break;
+                assert_eq!(message[end..].trim(), "");
+            }
+        }
+    }
+
+    #[cfg(feature = "sim")]
+    mod sim {
+        use super::*;
+        use crate::sim::*;
+        use std::string::String;
+        use std::vec::Vec;
+
+        /// Page through `scroller` past its end,
+        /// returning the texts shown after each
+        /// right press and the number of pages drawn
+        fn draw_pages(scroller: &MessageScroller) -> (Vec<Vec<String>>, usize) {
+            reset();
+            let shown = (0..8).map(|_| {
+                let id = push_checkpoint();
+                push_keys("r");
+                id
+            }).collect::<Vec<_>>();
+            push_keys("b");
+            scroller.event_loop();
+            let drawn = state_history().iter().map(|s| s.index + 1).max().unwrap_or(0);
+            (shown.into_iter().map(|id| checkpoint(id).unwrap()).collect(), drawn)
+        }
+
+        #[test]
+        fn pages_for_matches_drawn_pages() {
+            for message in MESSAGES.iter() {
+                let (shown, drawn) = draw_pages(&MessageScroller::new(message));
+                let pages = pages(message, &SCROLLER_LAYOUT).collect::<Vec<_>>();
+                assert_eq!(drawn, pages_for(message, &SCROLLER_LAYOUT));
+                for (i, texts) in shown.iter().enumerate() {
+                    let page = pages[i.min(pages.len() - 1)];
+                    assert!(texts.iter().any(|t| t == page), "{:?} not in {:?}", page, texts);
                 }
-                Some(_) | None => ()
             }
         }
-    } 
-}
\ No newline at end of file
+
+        #[test]
+        fn wrapped_page_count_matches_drawn_pages() {
+            for message in MESSAGES.iter() {
+                let scroller = MessageScroller::new(message).wrap(2);
+                let (shown, drawn) = draw_pages(&scroller);
+                let pages = scroller.pages().collect::<Vec<_>>();
+                assert_eq!(drawn, scroller.page_count());
+                assert_eq!(drawn, pages.len());
+                for (i, texts) in shown.iter().enumerate() {
+                    for line in wrap(pages[i.min(pages.len() - 1)], 16) {
+                        assert!(texts.iter().any(|t| t == line), "{:?} not in {:?}", line, texts);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//! Common interface of the widgets, so that
+//! containers such as flows or screen stacks
+//! can drive any of them.
+//!
+//! A widget draws its current state, and updates
+//! it from the events it is given until it
+//! produces an output. The blocking methods of
+//! the widgets (`ask`, `show`...) are `run`
+//! applied to a copy of the widget. Apps with
+//! a main loop of their own can drive them
+//! with `handle_event` and `render` instead.
+
+use crate::buttons::{ButtonEvent, ButtonsState};
+use crate::canvas::{self, Canvas, Discard};
+use crate::context::{ActiveWidget, UiContext};
+use crate::input::{self, Action, Input};
+use crate::screensaver;
+use crate::status;
+use crate::touch::TouchEvent;
+use crate::ui::{get_event, report, WidgetState};
+
+/// Input delivered to widgets
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    Button(ButtonEvent),
+    /// Logical input from a backend that
+    /// produces it directly
+    Input(Input),
+    /// Periodic tick, every 100 ms, for
+    /// timeouts and animations
+    Ticker,
+    /// An APDU with this instruction byte
+    /// arrived, see `sign::run_with_comm`
+    Command(u8),
+    /// Battery charge, in percent (Nano X)
+    BatteryLevel(u8),
+    /// A BLE host connected or
+    /// disconnected (Nano X)
+    BleConnection(bool),
+    /// Finger on the screen (Stax, Flex)
+    Touch(TouchEvent),
+    /// A SEPH packet this crate does not model,
+    /// e.g. vendor-specific: its tag and the
+    /// first bytes of its payload, zero-padded.
+    /// Display processed events are part of
+    /// painting and never given.
+    Unknown { tag: u8, data: [u8; UNKNOWN_DATA_LEN] },
+}
+
+/// Payload bytes kept by `Event::Unknown`
+pub const UNKNOWN_DATA_LEN: usize = 5;
+
+impl Event {
+    /// The button event, if this is one
+    pub const fn button(self) -> Option<ButtonEvent> {
+        match self {
+            Event::Button(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// The logical input, as given
+    /// by the installed mapper
+    pub fn input(self) -> Option<Input> {
+        input::mapper().map(self)
+    }
+
+    /// The completed action, if any
+    pub fn action(self) -> Option<Action> {
+        match self.input()? {
+            Input::Complete(action) => Some(action),
+            Input::Pending(_) => None,
+        }
+    }
+}
+
+/// How a confirmation ended
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Approved,
+    Rejected,
+    /// No decision was made in time
+    TimedOut,
+    /// Ended by something other than the user
+    Aborted(AbortReason),
+}
+
+/// Why a confirmation was aborted
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AbortReason {
+    /// An APDU required leaving the screen
+    Command,
+    /// The device PIN was not entered
+    Pin,
+    /// The app ended it
+    App,
+}
+
+impl Outcome {
+    pub const fn is_approved(self) -> bool {
+        matches!(self, Outcome::Approved)
+    }
+}
+
+impl From<bool> for Outcome {
+    fn from(approved: bool) -> Self {
+        if approved { Outcome::Approved } else { Outcome::Rejected }
+    }
+}
+
+pub trait Widget {
+    /// Value produced once the user is done
+    /// with the widget
+    type Output;
+
+    /// Called when the widget takes the display,
+    /// before it is drawn, e.g. to forget the
+    /// buttons held for the previous one
+    fn enter(&mut self) {}
+
+    /// Draw the whole screen for the current state
+    fn draw(&self);
+
+    /// Update the state from `event`, repainting
+    /// as needed. Returns the output once done.
+    fn handle(&mut self, event: Event) -> Option<Self::Output>;
+
+    /// `handle`, without painting: for loops
+    /// that get events on their own and draw
+    /// with `render` when they see fit
+    fn handle_event(&mut self, event: Event) -> Option<Self::Output> {
+        let mut output = None;
+        canvas::capture(&mut Discard, || output = self.handle(event));
+        output
+    }
+
+    /// `draw`, on `canvas` instead of the screen
+    fn render(&self, canvas: &mut dyn Canvas) {
+        canvas::capture(canvas, || self.draw());
+    }
+
+    /// Navigation state, reported to the test
+    /// and debug backends
+    fn state(&self) -> WidgetState;
+
+    /// Output once left idle for the idle
+    /// timeout. Only informational screens
+    /// give one: confirmations never expire.
+    fn on_idle(&mut self) -> Option<Self::Output> {
+        None
+    }
+}
+
+/// Ticker events without button activity after
+/// which `run` dismisses informational screens,
+/// returning to the app's home screen. 0 never
+/// dismisses them.
+static mut IDLE_TIMEOUT: u32 = 0;
+
+/// Dismiss informational screens after `ticks`
+/// ticker events (10 per second) of inactivity,
+/// or never if 0
+pub fn set_idle_timeout(ticks: u32) {
+    unsafe { IDLE_TIMEOUT = ticks };
+}
+
+pub fn idle_timeout() -> u32 {
+    unsafe { IDLE_TIMEOUT }
+}
+
+/// Draw `widget`, then feed it events until
+/// it produces its output. The widget owns the
+/// display (see `UiContext`) while it runs.
+pub fn run<W: Widget>(widget: &mut W) -> W::Output {
+    match run_until(widget, || false) {
+        Some(output) => output,
+        None => unreachable!(),
+    }
+}
+
+/// `run`, giving up once `should_abort` holds,
+/// e.g. when the host disconnected or sent a
+/// cancel APDU. It is checked after each event,
+/// so at least every 100 ms. None if aborted.
+pub fn run_until<W: Widget>(widget: &mut W, should_abort: impl Fn() -> bool) -> Option<W::Output> {
+    let mut session = Session::start(widget);
+    let output = loop {
+        if let Some(output) = session.next_event(widget) {
+            break Some(output)
+        }
+        if should_abort() {
+            break None
+        }
+        if let Some(output) = session.check_idle(widget) {
+            break Some(output)
+        }
+    };
+    session.end();
+    output
+}
+
+/// `run`, giving up after `ticks` ticker
+/// events (10 per second). None if it timed
+/// out. Ticks do not count while the screen
+/// saver blanks the screen.
+pub fn run_for<W: Widget>(widget: &mut W, ticks: u32) -> Option<W::Output> {
+    run(&mut Timed { widget, left: ticks })
+}
+
+/// Widget given up on after some ticks
+struct Timed<'w, W> {
+    widget: &'w mut W,
+    left: u32,
+}
+
+impl<W: Widget> Widget for Timed<'_, W> {
+    type Output = Option<W::Output>;
+
+    fn enter(&mut self) {
+        self.widget.enter()
+    }
+
+    fn draw(&self) {
+        self.widget.draw()
+    }
+
+    fn handle(&mut self, event: Event) -> Option<Self::Output> {
+        if let Event::Ticker = event {
+            self.left = self.left.saturating_sub(1);
+            if self.left == 0 {
+                return Some(None)
+            }
+        }
+        self.widget.handle(event).map(Some)
+    }
+
+    fn state(&self) -> WidgetState {
+        self.widget.state()
+    }
+
+    fn on_idle(&mut self) -> Option<Self::Output> {
+        self.widget.on_idle().map(Some)
+    }
+}
+
+/// What `run_until` keeps between two
+/// events, for the loops that drive a
+/// widget one event at a time
+pub(crate) struct Session {
+    buttons: ButtonsState,
+    /// Display owner before the widget
+    previous: Option<ActiveWidget>,
+}
+
+impl Session {
+    /// Give the display to `widget` and draw it
+    pub(crate) fn start<W: Widget>(widget: &mut W) -> Self {
+        widget.enter();
+        let previous = UiContext::enter(widget);
+        screensaver::reset();
+        UiContext::exclusive(|| {
+            widget.draw();
+            status::paint_overlay();
+        });
+        Session { buttons: ButtonsState::new(), previous }
+    }
+
+    /// Wait for the next event and hand it
+    /// to `widget`, unless the screen is blank
+    pub(crate) fn next_event<W: Widget>(&mut self, widget: &mut W) -> Option<W::Output> {
+        report(widget.state());
+        let event = get_event(&mut self.buttons)?;
+        // Not for the widget while blanked
+        if screensaver::filter(event) {
+            self.buttons.idle_ticks = 0;
+            return None
+        }
+        let output = widget.handle(event);
+        if output.is_none() && !matches!(event, Event::Ticker | Event::Unknown { .. }) {
+            status::paint_overlay();
+        }
+        output
+    }
+
+    /// Output of `widget` if left idle
+    /// for the idle timeout
+    pub(crate) fn check_idle<W: Widget>(&mut self, widget: &mut W) -> Option<W::Output> {
+        let timeout = idle_timeout();
+        if timeout > 0 && self.buttons.idle_ticks >= timeout {
+            self.buttons.idle_ticks = 0;
+            return widget.on_idle()
+        }
+        None
+    }
+
+    /// Give the display back
+    pub(crate) fn end(self) {
+        UiContext::leave(self.previous);
+    }
+}